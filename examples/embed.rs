@@ -0,0 +1,25 @@
+//! Uses the analyzer as a plain library, the way an embedder built with
+//! `--no-default-features` would: no shell, no colors, everything comes back as data.
+//!
+//! ```text
+//! cargo run --example embed --no-default-features -- C:/Users/me/Downloads
+//! ```
+use rusty_analyser::{AnalyzerError, SortOrder, StorageAnalyzer};
+use std::env;
+
+fn main() -> Result<(), AnalyzerError> {
+    let folder = env::args()
+        .nth(1)
+        .ok_or_else(|| AnalyzerError::InvalidArgument("usage: embed <folder>".to_string()))?;
+
+    let mut analyzer = StorageAnalyzer::builder().quiet(true).build()?;
+    let files = analyzer.get_largest_files(&folder, SortOrder::Descending)?;
+    for file in files.iter().take(5) {
+        println!("{:>14}  {}", file.size_bytes, file.full_path.display());
+    }
+
+    for (ext, size_bytes, count, _) in analyzer.get_file_type_distribution(&folder)?.iter().take(5) {
+        println!("{:>14}  {} files of .{}", size_bytes, count, ext);
+    }
+    Ok(())
+}
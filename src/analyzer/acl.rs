@@ -0,0 +1,219 @@
+use super::{
+    error::{AnalyzerError, Result},
+    constants::OWNER_SAMPLE_SIZE,
+    printers,
+    storage::StorageAnalyzer,
+    types::AclSummary,
+};
+use rayon::prelude::*;
+use std::{
+    collections::HashMap,
+    ffi::OsStr,
+    io,
+    os::windows::ffi::OsStrExt,
+    path::{Path, PathBuf},
+    ptr,
+};
+use walkdir::WalkDir;
+use winapi::{
+    shared::minwindef::DWORD,
+    um::{
+        accctrl::SE_FILE_OBJECT,
+        aclapi::GetNamedSecurityInfoW,
+        securitybaseapi::{CreateWellKnownSid, EqualSid, GetAce, GetLengthSid, GetSecurityDescriptorControl},
+        winbase::{LocalFree, LookupAccountSidW},
+        winnt::{
+            WinWorldSid, ACCESS_ALLOWED_ACE, ACCESS_ALLOWED_ACE_TYPE, ACE_HEADER, DACL_SECURITY_INFORMATION,
+            OWNER_SECURITY_INFORMATION, PACL, PSECURITY_DESCRIPTOR, PSID, SECURITY_INFORMATION,
+            SECURITY_MAX_SID_SIZE, SE_DACL_PROTECTED,
+        },
+    },
+};
+
+// the security descriptor of a file or folder, `owner` and `dacl` point into it
+// so they're only valid until it's passed to LocalFree
+fn security_info(path: &Path, info: SECURITY_INFORMATION, owner: &mut PSID, dacl: &mut PACL) -> io::Result<PSECURITY_DESCRIPTOR> {
+    let wide_path: Vec<u16> = OsStr::new(path).encode_wide().chain(Some(0)).collect();
+    let mut descriptor: PSECURITY_DESCRIPTOR = ptr::null_mut();
+    let result = unsafe {
+        GetNamedSecurityInfoW(
+            wide_path.as_ptr(),
+            SE_FILE_OBJECT,
+            info,
+            owner,
+            ptr::null_mut(),
+            dacl,
+            ptr::null_mut(),
+            &mut descriptor,
+        )
+    };
+    if result != 0 {
+        return Err(io::Error::from_raw_os_error(result as i32));
+    }
+    Ok(descriptor)
+}
+
+// the owner's SID as plain bytes, so files can be grouped by it before any name lookups
+fn owner_sid(path: &Path) -> io::Result<Vec<u8>> {
+    let mut owner: PSID = ptr::null_mut();
+    let descriptor = security_info(path, OWNER_SECURITY_INFORMATION, &mut owner, &mut ptr::null_mut())?;
+    let sid = unsafe {
+        let sid = std::slice::from_raw_parts(owner as *const u8, GetLengthSid(owner) as usize).to_vec();
+        LocalFree(descriptor);
+        sid
+    };
+    Ok(sid)
+}
+
+// DOMAIN\user for a SID, accounts that no longer exist get a placeholder
+fn account_name(sid: &mut [u8]) -> String {
+    let mut name = [0u16; 256];
+    let mut name_len = name.len() as DWORD;
+    let mut domain = [0u16; 256];
+    let mut domain_len = domain.len() as DWORD;
+    let mut sid_use = 0;
+    let found = unsafe {
+        LookupAccountSidW(
+            ptr::null(),
+            sid.as_mut_ptr().cast(),
+            name.as_mut_ptr(),
+            &mut name_len,
+            domain.as_mut_ptr(),
+            &mut domain_len,
+            &mut sid_use,
+        )
+    };
+    if found == 0 {
+        return "(deleted or unknown account)".to_string();
+    }
+    let name = String::from_utf16_lossy(&name[..name_len as usize]);
+    match domain_len {
+        0 => name,
+        _ => format!("{}\\{}", String::from_utf16_lossy(&domain[..domain_len as usize]), name),
+    }
+}
+
+// reads the DACL of one file or folder, only looks and never changes anything
+fn read_acl(path: &Path) -> io::Result<AclSummary> {
+    let mut dacl: PACL = ptr::null_mut();
+    let descriptor = security_info(path, DACL_SECURITY_INFORMATION, &mut ptr::null_mut(), &mut dacl)?;
+
+    let mut world_sid = [0u8; SECURITY_MAX_SID_SIZE];
+    let mut world_sid_len = world_sid.len() as DWORD;
+    let mut control = 0;
+    let mut revision = 0;
+    // a zeroed SID or control word would quietly report the wrong access, so either failing is an error
+    let read = unsafe {
+        CreateWellKnownSid(WinWorldSid, ptr::null_mut(), world_sid.as_mut_ptr().cast(), &mut world_sid_len) != 0
+            && GetSecurityDescriptorControl(descriptor, &mut control, &mut revision) != 0
+    };
+    if !read {
+        let error = io::Error::last_os_error();
+        unsafe { LocalFree(descriptor) };
+        return Err(error);
+    }
+
+    let summary = unsafe {
+        // a missing DACL means nobody is denied anything
+        let (ace_count, has_everyone_access) = if dacl.is_null() {
+            (0, true)
+        } else {
+            let ace_count = (*dacl).AceCount as u32;
+            let everyone = (0..ace_count).any(|i| {
+                let mut ace = ptr::null_mut();
+                if GetAce(dacl, i, &mut ace) == 0 {
+                    return false;
+                }
+                let header = &*(ace as *const ACE_HEADER);
+                if header.AceType != ACCESS_ALLOWED_ACE_TYPE {
+                    return false;
+                }
+                let allowed = ace as *mut ACCESS_ALLOWED_ACE;
+                EqualSid((&mut (*allowed).SidStart as *mut DWORD).cast(), world_sid.as_mut_ptr().cast()) != 0
+            });
+            (ace_count, everyone)
+        };
+
+        LocalFree(descriptor);
+        AclSummary {
+            has_everyone_access,
+            ace_count,
+            inherits_parent: control & SE_DACL_PROTECTED == 0,
+        }
+    };
+    Ok(summary)
+}
+
+impl StorageAnalyzer {
+    // who owns the biggest files, as (account, file count, total bytes) sorted by size
+    // only the largest OWNER_SAMPLE_SIZE files are looked up, one security query each
+    pub fn top_creators_by_size(&mut self, drive: &str, limit: usize) -> Result<Vec<(String, usize, u64)>> {
+        self.collect_and_cache_files(drive)?;
+        let mut files: Vec<(&Path, u64)> =
+            self.scanned_files(drive)?.iter().map(|f| (f.full_path.as_path(), f.size_bytes)).collect();
+        files.par_sort_unstable_by_key(|(_, size)| std::cmp::Reverse(*size));
+        files.truncate(OWNER_SAMPLE_SIZE);
+
+        let owned: Vec<(Vec<u8>, u64)> = files
+            .par_iter()
+            .filter_map(|(path, size)| Some((owner_sid(Path::new(path)).ok()?, *size)))
+            .collect();
+        let mut by_sid: HashMap<Vec<u8>, (usize, u64)> = HashMap::new();
+        for (sid, size) in owned {
+            let entry = by_sid.entry(sid).or_default();
+            entry.0 += 1;
+            entry.1 += size;
+        }
+
+        // several SIDs can resolve to the same name, e.g. two deleted accounts
+        let mut by_name: HashMap<String, (usize, u64)> = HashMap::new();
+        for (mut sid, (count, size)) in by_sid {
+            let entry = by_name.entry(account_name(&mut sid)).or_default();
+            entry.0 += count;
+            entry.1 += size;
+        }
+        let mut creators: Vec<(String, usize, u64)> =
+            by_name.into_iter().map(|(name, (count, size))| (name, count, size)).collect();
+        creators.sort_by_key(|creator| std::cmp::Reverse(creator.2));
+        creators.truncate(limit);
+        Ok(creators)
+    }
+
+    pub fn print_top_creators(&mut self, drive: &str) -> Result<()> {
+        let creators = self.top_creators_by_size(drive, self.config().display.top_n)?;
+        Ok(printers::top_creators(&mut self.out(), self.units(), drive, OWNER_SAMPLE_SIZE, &creators)?)
+    }
+
+    // folders up to `depth` levels below the drive root with a summary of their DACL
+    // folders whose security info can't be read (usually access denied) are left out
+    pub fn analyze_acls(&self, drive: &str, depth: usize) -> Result<Vec<(String, AclSummary)>> {
+        let mut summaries: Vec<(String, AclSummary)> = WalkDir::new(drive)
+            .min_depth(1)
+            .max_depth(depth)
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter(|e| e.file_type().is_dir())
+            .par_bridge()
+            .filter_map(|entry| {
+                let summary = read_acl(entry.path()).ok()?;
+                Some((entry.path().to_string_lossy().to_string(), summary))
+            })
+            .collect();
+        summaries.par_sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        Ok(summaries)
+    }
+
+    pub fn print_acl_report(&mut self, drive: &str, depth: usize) -> Result<()> {
+        let summaries = self.analyze_acls(drive, depth)?;
+        if !summaries.is_empty() {
+            self.set_last_listing(
+                summaries
+                    .iter()
+                    .filter(|(_, summary)| summary.has_everyone_access)
+                    .map(|(folder, _)| PathBuf::from(folder))
+                    .collect(),
+            );
+        }
+        Ok(printers::acl_report(&mut self.out(), drive, &summaries)?)
+    }
+}
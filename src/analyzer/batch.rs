@@ -0,0 +1,153 @@
+use super::{
+    error::{AnalyzerError, Result},
+    printers,
+    progress::{scan_cancelled, ScanDeadline, ScanGuard},
+    storage::StorageAnalyzer,
+    types::ScanStatus,
+    utils::{comparable_path, normalize_path},
+};
+use log::warn;
+use rayon::prelude::*;
+use std::{collections::HashSet, fs, path::Path};
+
+/// The drives and folders in a scan manifest, one per line. Blank lines and lines starting
+/// with `#` are left out, a bare letter like `Z` means the drive `Z:/`.
+pub fn parse_manifest(text: &str) -> Vec<String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| match line.len() == 1 && line.chars().all(|c| c.is_ascii_alphabetic()) {
+            true => format!("{}:/", line.to_uppercase()),
+            false => normalize_path(line),
+        })
+        .collect()
+}
+
+/// [`parse_manifest`] on the contents of the file at `path`.
+pub fn read_manifest(path: &Path) -> Result<Vec<String>> {
+    Ok(parse_manifest(&fs::read_to_string(path)?))
+}
+
+impl StorageAnalyzer {
+    /// Scans every drive or folder in `targets` into the cache, one after the other or with
+    /// `parallel` all at once, and returns each one's outcome in the same order.
+    ///
+    /// Targets that don't exist are skipped with a warning, a cached scan is reused like
+    /// with any other command, and a target listed twice is only scanned once. The scan timeout applies to each target in turn, or to the
+    /// whole batch when it runs in parallel. A target it cut short is [`ScanStatus::Partial`].
+    pub fn scan_batch(&mut self, targets: &[String], parallel: bool) -> Vec<(String, Result<ScanStatus>)> {
+        let mut seen = HashSet::new();
+        let targets: Vec<String> = targets
+            .iter()
+            .map(|target| normalize_path(target))
+            .filter(|target| seen.insert(comparable_path(target)))
+            .filter(|target| {
+                let exists = Path::new(target).is_dir();
+                if !exists {
+                    warn!("{} isn't a drive or folder, skipping it", target);
+                }
+                exists
+            })
+            .collect();
+
+        if !parallel {
+            let mut results = Vec::new();
+            for target in targets {
+                let result = self.collect_and_cache_files(&target);
+                let cancelled = matches!(result, Err(AnalyzerError::Cancelled));
+                results.push((target, result));
+                if cancelled {
+                    break;
+                }
+            }
+            return results;
+        }
+
+        // caches are checked and roots listed up front, only the walks themselves run side by side
+        // each result keeps its place in `targets`, they're put back in that order at the end
+        let mut results = Vec::new();
+        let mut pending = Vec::new();
+        for (index, target) in targets.into_iter().enumerate() {
+            if self.has_usable_cache(&target) {
+                let status = self.cached_scan_status(&target);
+                results.push((index, target, Ok(status)));
+                continue;
+            }
+            match self.checked_scan_roots(&target) {
+                Ok(roots) => pending.push((index, target, roots)),
+                Err(e) => results.push((index, target, Err(e))),
+            }
+        }
+        if !pending.is_empty() {
+            results.extend(self.walk_batch(pending));
+        }
+        results.sort_by_key(|&(index, ..)| index);
+        results.into_iter().map(|(_, target, result)| (target, result)).collect()
+    }
+
+    // walks every pending target at once and caches what finished
+    // a target whose walk was still going when Ctrl+C came in is Cancelled, one the timeout cut short is Partial
+    fn walk_batch(&mut self, pending: Vec<(usize, String, Vec<String>)>) -> Vec<(usize, String, Result<ScanStatus>)> {
+        self.status(log::Level::Info, &format!("Scanning {} drives at once..", pending.len()));
+        let scanning = ScanGuard::start();
+        let deadline = self.scan_timeout().map(ScanDeadline::start);
+        let this = &*self;
+        // several live tables would draw over each other, the summary at the end has to do
+        let walk = || {
+            pending
+                .into_par_iter()
+                .map(|(index, target, roots)| {
+                    let scan = this.walk_roots(&roots, false);
+                    // a walk that ends after the deadline or Ctrl+C went off may have been cut short
+                    let cut_short = scan_cancelled();
+                    (index, target, scan, cut_short)
+                })
+                .collect::<Vec<_>>()
+        };
+        let scans = this.in_scan_pool(walk);
+        let timed_out = deadline.is_some_and(ScanDeadline::finish);
+        drop(scanning);
+
+        let mut results = Vec::new();
+        for (index, target, scan, cut_short) in scans {
+            let status = match (cut_short, timed_out) {
+                // nothing gets cached from a walk Ctrl+C stopped, like a single scan
+                (true, false) => {
+                    results.push((index, target, Err(AnalyzerError::Cancelled)));
+                    continue;
+                }
+                (true, true) => ScanStatus::Partial,
+                (false, _) => ScanStatus::Complete,
+            };
+            self.store_scan(&target, scan);
+            if status == ScanStatus::Partial {
+                self.mark_timed_out(&target);
+            }
+            results.push((index, target, Ok(status)));
+        }
+        results
+    }
+
+    pub fn print_scan_batch(&mut self, targets: &[String], parallel: bool) -> Result<()> {
+        let results = self.scan_batch(targets, parallel);
+        let rows: Vec<_> = results
+            .iter()
+            .map(|(target, result)| {
+                let outcome = result.as_ref().copied().map_err(|e| e.to_string());
+                (target.as_str(), outcome, self.cached_file_totals(target))
+            })
+            .collect();
+        Ok(printers::scan_batch(&mut self.out(), self.units(), &rows)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manifests_skip_comments_and_blank_lines() {
+        let manifest = "# mapped drives\nZ\n\n  y:\\  \n# the NAS\n\\\\nas\\backups\nD:/Projects\n";
+        assert_eq!(parse_manifest(manifest), ["Z:/", "Y:/", "//nas/backups", "D:/Projects"]);
+    }
+}
@@ -0,0 +1,157 @@
+use super::{
+    config::{Config, ScanOptions},
+    error::{AnalyzerError, Result},
+    exclude::scan_glob_set,
+    storage::StorageAnalyzer,
+    utils::parse_size,
+};
+
+/// Collects the options for a [`StorageAnalyzer`] and checks them together in [`build`](Self::build).
+///
+/// Start from [`StorageAnalyzer::builder`]. Everything not set comes from the [`Config`]
+/// passed to [`config`](Self::config), or from [`Config::default`] when there isn't one.
+///
+/// ```
+/// use rusty_analyser::StorageAnalyzer;
+///
+/// let analyzer = StorageAnalyzer::builder()
+///     .threads(2)
+///     .include_hidden(true)
+///     .exclude("**/node_modules")
+///     .min_file_size("1KB")
+///     .quiet(true)
+///     .build()?;
+///
+/// assert!(analyzer.include_hidden());
+/// # Ok::<(), rusty_analyser::AnalyzerError>(())
+/// ```
+#[derive(Debug, Default)]
+pub struct StorageAnalyzerBuilder {
+    config: Config,
+    threads: Option<usize>,
+    include_removable: bool,
+    include_hidden: bool,
+    excludes: Vec<String>,
+    min_file_size: Option<String>,
+    follow_links: Option<bool>,
+    quiet: bool,
+    live_ui: bool,
+    read_only: bool,
+}
+
+impl StorageAnalyzerBuilder {
+    /// The config everything else is layered over, usually the one [`Config::load`] returned.
+    pub fn config(mut self, config: Config) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Scans run on a pool of this many threads instead of rayon's global one.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = Some(threads);
+        self
+    }
+
+    /// Adds the removable drives plugged in right now to [`StorageAnalyzer::drives`].
+    pub fn include_removable(mut self, include: bool) -> Self {
+        self.include_removable = include;
+        self
+    }
+
+    /// Lists folders starting with a dot, see [`StorageAnalyzer::set_include_hidden`].
+    pub fn include_hidden(mut self, include: bool) -> Self {
+        self.include_hidden = include;
+        self
+    }
+
+    /// Skips everything matching `pattern`, on top of the config's `[scan] exclude`.
+    pub fn exclude(mut self, pattern: &str) -> Self {
+        self.excludes.push(pattern.to_string());
+        self
+    }
+
+    /// Leaves files smaller than `size` (e.g. "1MB") out of every scan.
+    pub fn min_file_size(mut self, size: &str) -> Self {
+        self.min_file_size = Some(size.to_string());
+        self
+    }
+
+    /// Walks into symlinked and junctioned folders, each target only once.
+    pub fn follow_links(mut self, follow: bool) -> Self {
+        self.follow_links = Some(follow);
+        self
+    }
+
+    /// Scan progress is only logged at debug level.
+    pub fn quiet(mut self, quiet: bool) -> Self {
+        self.quiet = quiet;
+        self
+    }
+
+    /// Redraws a live counter table while scans run, needs a terminal.
+    pub fn live_ui(mut self, live_ui: bool) -> Self {
+        self.live_ui = live_ui;
+        self
+    }
+
+    /// Refuses every command that deletes or moves files.
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Checks the options against each other and the config, then builds the analyzer.
+    ///
+    /// Fails with [`AnalyzerError::InvalidArgument`] for zero threads, a size that doesn't
+    /// parse, a broken [`exclude`](Self::exclude) pattern or asking for the live table on a quiet
+    /// analyzer. The config's own patterns are [`Config::load`]'s to reject, one that slipped
+    /// through is only warned about.
+    pub fn build(self) -> Result<StorageAnalyzer> {
+        let mut config = self.config;
+        let threads = self.threads.or(config.scan.threads);
+        if threads == Some(0) {
+            return Err(AnalyzerError::InvalidArgument("threads has to be at least 1".to_string()));
+        }
+        if self.quiet && self.live_ui {
+            return Err(AnalyzerError::InvalidArgument("a quiet analyzer can't show the live scan table".to_string()));
+        }
+        if let Some(size) = &self.min_file_size {
+            parse_size(size, config.display.units).map_err(|e| AnalyzerError::InvalidArgument(format!("min file size: {}", e)))?;
+        }
+        // with_config() would only warn about these and carry on without them
+        scan_glob_set(&self.excludes)?;
+        config.scan.exclude.extend(self.excludes);
+
+        // the pool is set up below, with_config() would size the global one otherwise
+        config.scan.threads = None;
+        let mut analyzer = StorageAnalyzer::with_config(config);
+        if let Some(threads) = threads {
+            analyzer.set_thread_count(threads)?;
+        }
+        if self.include_removable {
+            for drive in analyzer.list_removable_drives() {
+                if !analyzer.drives.contains(&drive) {
+                    analyzer.drives.push(drive);
+                }
+            }
+            analyzer.publish_drives();
+        }
+        analyzer.set_base_options(ScanOptions {
+            min_file_size: self.min_file_size,
+            follow_links: self.follow_links,
+            ..ScanOptions::default()
+        });
+        analyzer.set_include_hidden(self.include_hidden);
+        analyzer.set_quiet(self.quiet);
+        analyzer.set_live_ui(self.live_ui);
+        analyzer.set_read_only(self.read_only);
+        Ok(analyzer)
+    }
+}
+
+impl StorageAnalyzer {
+    /// The documented way to make an analyzer, see [`StorageAnalyzerBuilder`].
+    pub fn builder() -> StorageAnalyzerBuilder {
+        StorageAnalyzerBuilder::default()
+    }
+}
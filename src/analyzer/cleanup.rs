@@ -0,0 +1,285 @@
+use super::{
+    config::Units,
+    error::{AnalyzerError, Result},
+    constants::*,
+    file_ops,
+    printers,
+    snapshot::Envelope,
+    storage::{temp_directories, StorageAnalyzer},
+    types::*,
+    utils::*
+};
+use chrono::{Duration, Utc};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    io::{self, Error, ErrorKind},
+    path::{Path, PathBuf},
+};
+
+// every heuristic looks at the cached files and says what it would delete
+// adding a new one is just writing the function and listing it here, stale_large_files is the
+// exception since its size cutoff is read in the analyzer's units
+type Heuristic = fn(&[FileInfo]) -> Vec<CleanupCandidate>;
+
+const HEURISTICS: &[Heuristic] = &[
+    temp_files,
+    leftover_installers,
+    dev_artifacts,
+    clutter_files,
+];
+
+// runs all heuristics, merges entries for the same path and drops entries
+// that are already covered by a candidate folder above them
+pub fn collect_candidates(files: &[FileInfo], units: Units) -> Vec<CleanupCandidate> {
+    let mut merged: HashMap<String, CleanupCandidate> = HashMap::new();
+    let found = HEURISTICS.iter().flat_map(|heuristic| heuristic(files)).chain(stale_large_files(files, units));
+    for candidate in found {
+        match merged.get_mut(&comparable_path(&candidate.path)) {
+            Some(existing) => {
+                if !existing.reason.contains(&candidate.reason) {
+                    existing.reason = format!("{}, {}", existing.reason, candidate.reason);
+                }
+                existing.confidence = existing.confidence.max(candidate.confidence);
+            }
+            None => {
+                merged.insert(comparable_path(&candidate.path), candidate);
+            }
+        }
+    }
+
+    let keys: HashSet<String> = merged.keys().cloned().collect();
+    let mut candidates: Vec<CleanupCandidate> = merged
+        .into_iter()
+        .filter(|(key, _)| {
+            !Path::new(key)
+                .ancestors()
+                .skip(1)
+                .any(|parent| keys.contains(&comparable_path(parent)))
+        })
+        .map(|(_, candidate)| candidate)
+        .collect();
+
+    candidates.sort_by(|a, b| BySize(b).cmp(&BySize(a)));
+    candidates
+}
+
+fn candidate(file: &FileInfo, reason: &str, confidence: Confidence) -> CleanupCandidate {
+    CleanupCandidate {
+        path: file.full_path.clone(),
+        path_lossy: file.full_path_lossy,
+        size_bytes: file.size_bytes,
+        reason: reason.to_string(),
+        confidence,
+    }
+}
+
+// anything in the temp directories is fair game once nothing is holding it open
+fn temp_files(files: &[FileInfo]) -> Vec<CleanupCandidate> {
+    let temp_dirs = temp_directories();
+    files
+        .iter()
+        .filter(|file| {
+            let path = comparable_path(&file.full_path);
+            temp_dirs.iter().any(|dir| is_under(&path, dir))
+        })
+        .map(|file| candidate(file, "temp file", Confidence::High))
+        .collect()
+}
+
+// C:/Windows/Installer is needed for repairs and uninstalls, so it only gets low confidence
+fn leftover_installers(files: &[FileInfo]) -> Vec<CleanupCandidate> {
+    let cache_dirs = StorageAnalyzer::installer_cache_dirs();
+    let windows_installer = comparable_path("C:/Windows/Installer");
+    files
+        .iter()
+        .filter(|file| StorageAnalyzer::is_cached_installer(file, &cache_dirs))
+        .map(|file| {
+            if is_under(&comparable_path(&file.full_path), &windows_installer) {
+                candidate(file, "windows installer cache", Confidence::Low)
+            } else {
+                candidate(file, "leftover installer", Confidence::Medium)
+            }
+        })
+        .collect()
+}
+
+// build outputs and dependency folders, reported as the outermost folder so they can go in one go
+fn dev_artifacts(files: &[FileInfo]) -> Vec<CleanupCandidate> {
+    let mut artifact_dirs: HashMap<PathBuf, u64> = HashMap::new();
+    for file in files {
+        let mut prefix = PathBuf::new();
+        for component in file.full_path.components() {
+            prefix.push(component);
+            let name = component.as_os_str().to_string_lossy().to_lowercase();
+            if DEV_ARTIFACT_DIRS.contains(&name.as_str()) {
+                *artifact_dirs.entry(prefix.clone()).or_default() += file.size_bytes;
+                break;
+            }
+        }
+    }
+
+    artifact_dirs
+        .into_iter()
+        .map(|(dir, size_bytes)| CleanupCandidate {
+            path: dir,
+            path_lossy: false,
+            size_bytes,
+            reason: "dev artifact".to_string(),
+            confidence: Confidence::Medium,
+        })
+        .collect()
+}
+
+// thumbnail caches and similar litter that the OS recreates on demand
+fn clutter_files(files: &[FileInfo]) -> Vec<CleanupCandidate> {
+    files
+        .iter()
+        .filter(|file| {
+            file.full_path
+                .file_name()
+                .map(|name| CLUTTER_FILE_NAMES.contains(&name.to_string_lossy().to_lowercase().as_str()))
+                .unwrap_or(false)
+        })
+        .map(|file| candidate(file, "clutter file", Confidence::High))
+        .collect()
+}
+
+// big files nobody has touched in a long time, only the user knows if they're still needed
+fn stale_large_files(files: &[FileInfo], units: Units) -> Vec<CleanupCandidate> {
+    let cutoff = Utc::now() - Duration::days(STALE_AGE_DAYS);
+    files
+        .iter()
+        .filter(|file| file.size_bytes >= mb_to_bytes(units, STALE_MIN_SIZE_MB))
+        .filter(|file| {
+            file.last_modified.map(|dt| dt < cutoff).unwrap_or(false)
+        })
+        .map(|file| candidate(file, "stale large file", Confidence::Low))
+        .collect()
+}
+
+// writes the list as JSON or CSV, picked by the file extension
+// the JSON is wrapped in the snapshot envelope so it can be read back with Envelope::from_json
+pub fn export_candidates(drive: &str, candidates: &[CleanupCandidate], path: &Path, units: Units) -> Result<()> {
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    let contents = match extension.as_str() {
+        "json" => Envelope::new(drive, candidates).to_json()?,
+        "csv" => {
+            let mut csv = String::from("path,size_bytes,size,reason,confidence\n");
+            for c in candidates {
+                csv.push_str(&format!(
+                    "{},{},{},{},{:?}\n",
+                    csv_field(&c.path.to_string_lossy()), c.size_bytes, csv_field(&human_readable_size(units, c.size_bytes)), csv_field(&c.reason), c.confidence
+                ));
+            }
+            csv
+        }
+        _ => {
+            return Err(AnalyzerError::InvalidArgument(
+                "export file must end in .json or .csv".to_string(),
+            ))
+        }
+    };
+
+    Ok(fs::write(path, contents)?)
+}
+
+fn source(name: &'static str, report: &'static str, tier: ReclaimTier, candidates: &[CleanupCandidate]) -> ReclaimSource {
+    ReclaimSource {
+        name,
+        report,
+        tier,
+        size_bytes: candidates.iter().map(|c| c.size_bytes).sum(),
+        items: candidates.len(),
+    }
+}
+
+// drops the files `candidates` cover from `remaining`, a candidate folder takes everything below it
+// with it, so a file is only ever counted by the first source that lists it
+fn claim(remaining: &mut Vec<FileInfo>, candidates: &[CleanupCandidate]) {
+    let claimed: HashSet<String> = candidates.iter().map(|c| comparable_path(&c.path)).collect();
+    remaining.retain(|file| !file.full_path.ancestors().any(|path| claimed.contains(&comparable_path(path))));
+}
+
+impl StorageAnalyzer {
+    // what each cleanup heuristic thinks could be freed, grouped into tiers
+    // nothing is touched, every source names the report that lists its items
+    // a file two heuristics agree on only counts in the first, safest one, so the tiers add up
+    pub fn reclaim_estimate(&mut self, drive: &str) -> Result<Vec<ReclaimSource>> {
+        let mut sources = Vec::new();
+
+        if let Ok((bytes, items)) = file_ops::recycle_bin_size(drive) {
+            sources.push(ReclaimSource {
+                name: "Recycle Bin",
+                report: "the Recycle Bin itself",
+                tier: ReclaimTier::Safe,
+                size_bytes: bytes,
+                items: items as usize,
+            });
+        }
+
+        let duplicates = self.find_duplicates(drive)?;
+        let mut files = match self.cached_files(drive) {
+            Some(files) => files.to_vec(),
+            None => return Ok(sources),
+        };
+
+        let temp = temp_files(&files);
+        claim(&mut files, &temp);
+        sources.push(source("Temp files", "cleanup-candidates (temp file)", ReclaimTier::Safe, &temp));
+        let clutter = clutter_files(&files);
+        claim(&mut files, &clutter);
+        sources.push(source("Clutter files", "cleanup-candidates (clutter file)", ReclaimTier::Safe, &clutter));
+
+        let cutoff = Utc::now() - Duration::days(INSTALLER_MIN_AGE_DAYS);
+        let aged_files: Vec<FileInfo> = files
+            .iter()
+            .filter(|file| {
+                file.last_modified.map(|dt| dt < cutoff).unwrap_or(false)
+            })
+            .cloned()
+            .collect();
+        let old_installers: Vec<CleanupCandidate> = leftover_installers(&aged_files)
+            .into_iter()
+            .filter(|c| c.confidence > Confidence::Low) // skips C:/Windows/Installer
+            .collect();
+        claim(&mut files, &old_installers);
+        sources.push(source(
+            "Installers older than 30 days",
+            "installer-cache (outside C:/Windows/Installer)",
+            ReclaimTier::ProbablySafe,
+            &old_installers,
+        ));
+        let artifacts = dev_artifacts(&files);
+        claim(&mut files, &artifacts);
+        sources.push(source("Dev artifacts", "cleanup-candidates (dev artifact)", ReclaimTier::ProbablySafe, &artifacts));
+
+        // one copy of every group stays, copies an earlier source already counted are gone anyway
+        let left: HashSet<String> = files.iter().map(|f| comparable_path(&f.full_path)).collect();
+        let copies: Vec<CleanupCandidate> = duplicates
+            .iter()
+            .flat_map(|group| {
+                group
+                    .iter()
+                    .filter(|f| left.contains(&comparable_path(&f.full_path)))
+                    .skip(1)
+                    .map(|f| candidate(f, "duplicate copy", Confidence::Low))
+            })
+            .collect();
+        claim(&mut files, &copies);
+        sources.push(source("Duplicate copies", "duplicates", ReclaimTier::Aggressive, &copies));
+        sources.push(source("Stale large files", "cleanup-candidates (stale large file)", ReclaimTier::Aggressive, &stale_large_files(&files, self.units())));
+
+        Ok(sources)
+    }
+
+    pub fn print_reclaim_estimate(&mut self, drive: &str) -> Result<()> {
+        let space = self.get_drive_space(drive)?;
+        let sources = self.reclaim_estimate(drive)?;
+        Ok(printers::reclaim_estimate(&mut self.out(), self.units(), &space, &sources)?)
+    }
+}
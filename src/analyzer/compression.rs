@@ -0,0 +1,162 @@
+use super::{
+    error::{AnalyzerError, Result},
+    constants::*,
+    printers,
+    storage::StorageAnalyzer,
+    types::*,
+    utils::mb_to_bytes
+};
+use chrono::{DateTime, Duration, Utc};
+use flate2::{write::DeflateEncoder, Compression};
+use rayon::prelude::*;
+use std::{
+    ffi::OsStr,
+    fs::{self, File},
+    io::{self, Read, Write},
+    os::windows::{ffi::OsStrExt, fs::MetadataExt},
+    path::Path,
+};
+use winapi::um::{
+    fileapi::{GetCompressedFileSizeW, INVALID_FILE_SIZE},
+    winnt::{FILE_ATTRIBUTE_COMPRESSED, FILE_ATTRIBUTE_SPARSE_FILE},
+};
+
+fn older_than(timestamp: Option<DateTime<Utc>>, days: i64) -> bool {
+    let cutoff = Utc::now() - Duration::days(days);
+    timestamp.map(|dt| dt < cutoff).unwrap_or(false)
+}
+
+fn is_ntfs_compressed(path: &Path) -> bool {
+    fs::metadata(path)
+        .map(|m| m.file_attributes() & FILE_ATTRIBUTE_COMPRESSED != 0)
+        .unwrap_or(false)
+}
+
+fn is_sparse(path: &Path) -> bool {
+    fs::metadata(path)
+        .map(|m| m.file_attributes() & FILE_ATTRIBUTE_SPARSE_FILE != 0)
+        .unwrap_or(false)
+}
+
+// bytes the file actually takes up on disk, less than its length when it's sparse or compressed
+fn physical_size(path: &Path) -> io::Result<u64> {
+    let wide_path: Vec<u16> = OsStr::new(path).encode_wide().chain(Some(0)).collect();
+    let mut high = 0;
+    let low = unsafe { GetCompressedFileSizeW(wide_path.as_ptr(), &mut high) };
+    // INVALID_FILE_SIZE is also a valid low half, only the last error tells them apart
+    if low == INVALID_FILE_SIZE {
+        let error = io::Error::last_os_error();
+        if error.raw_os_error() != Some(0) {
+            return Err(error);
+        }
+    }
+    Ok(((high as u64) << 32) | low as u64)
+}
+
+// deflates the start of the file in memory and returns compressed / original
+// NTFS uses the weaker LZNT1, so treat the result as an optimistic estimate
+fn sample_compression_ratio(path: &Path) -> io::Result<f64> {
+    let mut sample = Vec::new();
+    File::open(path)?.take(COMPRESS_SAMPLE_BYTES).read_to_end(&mut sample)?;
+    if sample.is_empty() {
+        return Ok(1.0);
+    }
+
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::fast());
+    encoder.write_all(&sample)?;
+    let compressed = encoder.finish()?;
+    Ok((compressed.len() as f64 / sample.len() as f64).min(1.0))
+}
+
+impl StorageAnalyzer {
+    // large, old, rarely touched files of compressible types that aren't compressed yet,
+    // sorted by estimated savings, this only reports and never changes anything
+    pub fn find_compression_candidates(&mut self, drive: &str) -> Result<Vec<CompressionCandidate>> {
+        self.collect_and_cache_files(drive)?;
+        let files = self.scanned_files(drive)?;
+
+        let min_bytes = mb_to_bytes(self.units(), COMPRESS_MIN_SIZE_MB);
+        let mut candidates: Vec<CompressionCandidate> = files
+            .par_iter()
+            .filter(|file| file.size_bytes >= min_bytes)
+            .filter(|file| {
+                file.full_path
+                    .extension()
+                    .map(|e| COMPRESSIBLE_EXTS.contains(&e.to_string_lossy().to_lowercase().as_str()))
+                    .unwrap_or(false)
+            })
+            .filter(|file| older_than(file.last_modified, COMPRESS_MIN_AGE_DAYS))
+            .filter(|file| older_than(file.last_accessed, COMPRESS_MIN_IDLE_DAYS))
+            .filter(|file| !is_ntfs_compressed(&file.full_path))
+            .filter_map(|file| {
+                let ratio = sample_compression_ratio(&file.full_path).ok()?;
+                Some(CompressionCandidate {
+                    file: file.clone(),
+                    estimated_ratio: ratio,
+                    estimated_savings_bytes: (file.size_bytes as f64 * (1.0 - ratio)) as u64,
+                })
+            })
+            .filter(|candidate| candidate.estimated_savings_bytes > 0)
+            .collect();
+
+        candidates.par_sort_unstable_by_key(|candidate| std::cmp::Reverse(candidate.estimated_savings_bytes));
+        Ok(candidates)
+    }
+
+    // every sparse file with its size on disk, the biggest difference first
+    pub fn find_sparse_files(&mut self, drive: &str) -> Result<Vec<(FileInfo, u64)>> {
+        self.collect_and_cache_files(drive)?;
+        let files = self.scanned_files(drive)?;
+
+        let mut sparse: Vec<(FileInfo, u64)> = files
+            .par_iter()
+            .filter(|file| is_sparse(&file.full_path))
+            .filter_map(|file| Some((file.clone(), physical_size(&file.full_path).ok()?)))
+            .collect();
+        sparse.par_sort_unstable_by_key(|(file, physical)| std::cmp::Reverse(file.size_bytes.saturating_sub(*physical)));
+        Ok(sparse)
+    }
+
+    // every NTFS-compressed file with what it actually takes up on disk, the biggest savings first
+    pub fn find_compressed_files(&mut self, drive: &str) -> Result<Vec<(FileInfo, u64)>> {
+        self.collect_and_cache_files(drive)?;
+        let files = self.scanned_files(drive)?;
+
+        let mut compressed: Vec<(FileInfo, u64)> = files
+            .par_iter()
+            .filter(|file| is_ntfs_compressed(&file.full_path))
+            .filter_map(|file| Some((file.clone(), physical_size(&file.full_path).ok()?)))
+            .collect();
+        compressed.par_sort_unstable_by_key(|(file, physical)| std::cmp::Reverse(file.size_bytes.saturating_sub(*physical)));
+        Ok(compressed)
+    }
+
+    pub fn print_compressed_files(&mut self, drive: &str) -> Result<()> {
+        let compressed = self.find_compressed_files(drive)?;
+        if !compressed.is_empty() {
+            self.set_last_listing(compressed.iter().map(|(file, _)| file.full_path.clone()).collect());
+        }
+        let top_n = self.config().display.top_n;
+        Ok(printers::compressed_files(&mut self.out(), self.units(), drive, &compressed, top_n)?)
+    }
+
+    pub fn print_sparse_files(&mut self, drive: &str) -> Result<()> {
+        let sparse: Vec<(FileInfo, u64)> = self
+            .find_sparse_files(drive)?
+            .into_iter()
+            .filter(|(file, physical)| (*physical as f64) < file.size_bytes as f64 * SPARSE_MAX_PHYSICAL_RATIO)
+            .collect();
+        if !sparse.is_empty() {
+            self.set_last_listing(sparse.iter().map(|(file, _)| file.full_path.clone()).collect());
+        }
+        Ok(printers::sparse_files(&mut self.out(), self.units(), drive, &sparse)?)
+    }
+
+    pub fn print_compression_candidates(&mut self, drive: &str) -> Result<()> {
+        let candidates = self.find_compression_candidates(drive)?;
+        if !candidates.is_empty() {
+            self.set_last_listing(candidates.iter().map(|c| c.file.full_path.clone()).collect());
+        }
+        Ok(printers::compression_candidates(&mut self.out(), self.units(), &candidates)?)
+    }
+}
@@ -0,0 +1,590 @@
+use super::{
+    constants::*,
+    exclude::{scan_glob_set, ExcludeList},
+    types::QuotaRule,
+    utils::{home_dir, is_valid_time_format, parse_days, parse_size}
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    env,
+    fs,
+    io::{self, Error, ErrorKind},
+    path::PathBuf,
+};
+
+// settings read from %APPDATA%\rusty-system-analyser\config.toml
+// every section and key is optional, whatever is missing keeps the compiled default
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    // paths or globs that scans never look at, managed with the `exclude` command
+    pub exclude: Vec<String>,
+    pub thresholds: Thresholds,
+    pub days: DayWindows,
+    pub display: Display,
+    pub scan: Scan,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub smtp: Option<SmtpConfig>,
+    // written as [[quota]] tables with `extension` and `limit_gb`
+    #[serde(rename = "quota")]
+    pub quotas: Vec<QuotaRule>,
+    // named bundles of scan options, written as [profile.<name>] and picked with --profile or `profile`
+    #[serde(rename = "profile")]
+    pub profiles: BTreeMap<String, ScanOptions>,
+    // keys that apply_env() overrode and the value they ended up with as flatten() shows it,
+    // never read from or written to the file
+    #[serde(skip)]
+    pub env_overrides: Vec<(&'static str, String)>,
+}
+
+/// Per-scan switches, passed to [`StorageAnalyzer::set_scan_options`](super::StorageAnalyzer::set_scan_options).
+///
+/// Anything left unset falls through to the next source: flags on the command win over
+/// the profile, the profile wins over the config defaults.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScanOptions {
+    pub max_depth: Option<usize>,
+    // e.g. "1MB", smaller files are left out of the scan entirely
+    pub min_file_size: Option<String>,
+    pub follow_links: Option<bool>,
+    pub include_system: Option<bool>,
+    pub user_data: Option<bool>,
+    pub no_exclude: Option<bool>,
+}
+
+impl ScanOptions {
+    // fills in whatever this leaves unset from `fallback`
+    pub fn or(self, fallback: &ScanOptions) -> ScanOptions {
+        ScanOptions {
+            max_depth: self.max_depth.or(fallback.max_depth),
+            min_file_size: self.min_file_size.or_else(|| fallback.min_file_size.clone()),
+            follow_links: self.follow_links.or(fallback.follow_links),
+            include_system: self.include_system.or(fallback.include_system),
+            user_data: self.user_data.or(fallback.user_data),
+            no_exclude: self.no_exclude.or(fallback.no_exclude),
+        }
+    }
+
+    // the same checks a profile gets when the config loads, for options built in code
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_depth == Some(0) {
+            return Err("max_depth has to be at least 1 (got 0)".to_string());
+        }
+        // only whether it parses, the scan reads it in the analyzer's units
+        if let Some(size) = &self.min_file_size {
+            parse_size(size, Units::Binary).map_err(|e| format!("min_file_size: {}", e))?;
+        }
+        Ok(())
+    }
+
+    // "max_depth = 4, min_file_size = 1MB" for the options that are set
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(depth) = self.max_depth {
+            parts.push(format!("max_depth = {}", depth));
+        }
+        if let Some(size) = &self.min_file_size {
+            parts.push(format!("min_file_size = {}", size));
+        }
+        for (key, value) in [
+            ("follow_links", self.follow_links),
+            ("include_system", self.include_system),
+            ("user_data", self.user_data),
+            ("no_exclude", self.no_exclude),
+        ] {
+            if let Some(value) = value {
+                parts.push(format!("{} = {}", key, value));
+            }
+        }
+        if parts.is_empty() {
+            "nothing, same as no profile".to_string()
+        } else {
+            parts.join(", ")
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Thresholds {
+    pub min_folder_size_gb: f64,
+    pub min_file_type_size_gb: f64,
+    pub low_free_space_percent: f64,
+    pub critical_free_space_percent: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DayWindows {
+    // what counts as "recent" and "old" in the large file reports
+    pub recent_window_days: i64,
+    pub old_window_days: i64,
+    // default window for files-by-day
+    pub activity: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Display {
+    // how many entries the top-N reports show
+    pub top_n: usize,
+    pub color: bool,
+    pub units: Units,
+    // chrono strftime syntax, e.g. "%d/%m/%Y %H:%M"
+    pub date_format: String,
+    // show times in UTC instead of the local timezone
+    pub utc: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Units {
+    // 1 GiB = 1024^3 bytes, what Explorer shows (and calls GB)
+    #[serde(alias = "iec")]
+    Binary,
+    // 1 GB = 1000^3 bytes, what the box the drive came in says
+    #[serde(alias = "si")]
+    Decimal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Scan {
+    // leave unset to use one thread per core
+    pub threads: Option<usize>,
+    // globs like "**/node_modules/**" or "*.tmp", matched against the whole path while walking
+    pub exclude: Vec<String>,
+    // leave out Windows, System Volume Information and $Recycle.Bin at the drive root
+    pub skip_system_dirs: bool,
+    // scanned along with the user profile by --user-data
+    pub user_data_roots: Vec<String>,
+    // walk into symlinked and junctioned folders, sizes then overlap with wherever they point
+    pub follow_links: bool,
+    // how many levels below the drive the file scan goes, unset scans everything
+    pub max_depth: Option<usize>,
+    // minutes a scan stays cached, 0 rescans for every query, unset keeps it until it's cleared
+    pub cache_ttl_minutes: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmtpConfig {
+    pub host: String,
+    #[serde(default = "default_smtp_port")]
+    pub port: u16,
+    pub username: String,
+    pub password: String,
+    // falls back to the username, which is what most providers expect anyway
+    pub from: Option<String>,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Thresholds {
+            min_folder_size_gb: MIN_FOLDER_SIZE_GB,
+            min_file_type_size_gb: MIN_FILE_TYPE_SIZE_GB,
+            low_free_space_percent: LOW_FREE_SPACE_PERCENT,
+            critical_free_space_percent: CRITICAL_FREE_SPACE_PERCENT,
+        }
+    }
+}
+
+impl Default for DayWindows {
+    fn default() -> Self {
+        DayWindows {
+            recent_window_days: RECENT_DAYS,
+            old_window_days: OLD_DAYS,
+            activity: DEFAULT_ACTIVITY_DAYS,
+        }
+    }
+}
+
+impl Default for Display {
+    fn default() -> Self {
+        Display {
+            top_n: DEFAULT_TOP_N,
+            color: true,
+            units: Units::Binary,
+            date_format: DATE_FORMAT.to_string(),
+            utc: false,
+        }
+    }
+}
+
+impl Default for Scan {
+    fn default() -> Self {
+        Scan {
+            threads: None,
+            exclude: Vec::new(),
+            skip_system_dirs: true,
+            user_data_roots: Vec::new(),
+            follow_links: false,
+            max_depth: None,
+            cache_ttl_minutes: None,
+        }
+    }
+}
+
+impl Units {
+    pub fn bytes_per_gb(self) -> f64 {
+        match self {
+            Units::Binary => GIB_TO_BYTES,
+            Units::Decimal => GB_TO_BYTES,
+        }
+    }
+
+    pub fn bytes_per_mb(self) -> f64 {
+        match self {
+            Units::Binary => MIB_TO_BYTES,
+            Units::Decimal => MB_TO_BYTES,
+        }
+    }
+
+    // the IEC names for binary units, so a label never claims 1000 when it means 1024
+    pub fn labels(self) -> [&'static str; 5] {
+        match self {
+            Units::Binary => ["B", "KiB", "MiB", "GiB", "TiB"],
+            Units::Decimal => ["B", "KB", "MB", "GB", "TB"],
+        }
+    }
+
+    pub fn base(self) -> f64 {
+        match self {
+            Units::Binary => 1024.0,
+            Units::Decimal => 1000.0,
+        }
+    }
+}
+
+// settings that can be changed mid-session with `set`, as (name, config key)
+// the rest only take effect at startup
+pub const TUNABLES: &[(&str, &str)] = &[
+    ("min-folder-size", "thresholds.min_folder_size_gb"),
+    ("min-filetype-size", "thresholds.min_file_type_size_gb"),
+    ("low-free-space", "thresholds.low_free_space_percent"),
+    ("critical-free-space", "thresholds.critical_free_space_percent"),
+    ("recent-window", "days.recent_window_days"),
+    ("old-window", "days.old_window_days"),
+    ("activity-days", "days.activity"),
+    ("top-n", "display.top_n"),
+];
+
+// every key that can come from the environment, as (config key, variable)
+// display.no_color only exists here, it's display.color flipped to match the NO_COLOR convention
+pub const ENV_OVERRIDES: &[(&str, &str)] = &[
+    ("exclude", "RSA_EXCLUDE"),
+    ("thresholds.min_folder_size_gb", "RSA_MIN_FOLDER_SIZE_GB"),
+    ("thresholds.min_file_type_size_gb", "RSA_MIN_FILE_TYPE_SIZE_GB"),
+    ("thresholds.low_free_space_percent", "RSA_LOW_FREE_SPACE_PERCENT"),
+    ("thresholds.critical_free_space_percent", "RSA_CRITICAL_FREE_SPACE_PERCENT"),
+    ("days.recent_window_days", "RSA_RECENT_WINDOW_DAYS"),
+    ("days.old_window_days", "RSA_OLD_WINDOW_DAYS"),
+    ("days.activity", "RSA_ACTIVITY_DAYS"),
+    ("display.top_n", "RSA_TOP_N"),
+    ("display.color", "RSA_COLOR"),
+    ("display.no_color", "RSA_NO_COLOR"),
+    ("display.units", "RSA_UNITS"),
+    ("display.date_format", "RSA_DATE_FORMAT"),
+    ("display.utc", "RSA_UTC"),
+    ("scan.threads", "RSA_THREADS"),
+    ("scan.exclude", "RSA_SCAN_EXCLUDE"),
+    ("scan.skip_system_dirs", "RSA_SKIP_SYSTEM_DIRS"),
+    ("scan.user_data_roots", "RSA_USER_DATA_ROOTS"),
+    ("scan.follow_links", "RSA_FOLLOW_LINKS"),
+    ("scan.max_depth", "RSA_MAX_DEPTH"),
+    ("scan.cache_ttl_minutes", "RSA_CACHE_TTL_MINUTES"),
+    ("smtp.host", "RSA_SMTP_HOST"),
+    ("smtp.port", "RSA_SMTP_PORT"),
+    ("smtp.username", "RSA_SMTP_USERNAME"),
+    ("smtp.password", "RSA_SMTP_PASSWORD"),
+    ("smtp.from", "RSA_SMTP_FROM"),
+];
+
+fn parse<T: std::str::FromStr>(value: &str) -> Result<T, String>
+where
+    T::Err: std::fmt::Display,
+{
+    value.trim().parse().map_err(|e: T::Err| e.to_string())
+}
+
+fn parse_bool(value: &str) -> Result<bool, String> {
+    match value.trim().to_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Ok(true),
+        "0" | "false" | "no" | "off" => Ok(false),
+        _ => Err("expected true or false".to_string()),
+    }
+}
+
+// lists from the environment are separated with ';', which no glob needs
+fn parse_list(value: &str) -> Vec<String> {
+    value.split(';').map(str::trim).filter(|s| !s.is_empty()).map(str::to_string).collect()
+}
+
+fn default_smtp_port() -> u16 {
+    587
+}
+
+// nested tables as ("section.key", value), arrays stay whole on one line
+fn flatten_into(prefix: &str, table: &toml::Table, out: &mut Vec<(String, String)>) {
+    for (key, value) in table {
+        let key = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+        match value {
+            toml::Value::Table(inner) => flatten_into(&key, inner, out),
+            toml::Value::String(s) => out.push((key, s.clone())),
+            other => out.push((key, other.to_string())),
+        }
+    }
+}
+
+impl Config {
+    pub const DIR_NAME: &'static str = "rusty-system-analyser";
+    pub const FILE_NAME: &'static str = "config.toml";
+    // where the config lived before it moved to %APPDATA%, still read if nothing newer exists
+    const LEGACY_FILE_NAME: &'static str = ".rusty-analyser.toml";
+
+    // the file load() reads, the legacy one only counts if there's nothing newer
+    fn existing_path() -> Option<PathBuf> {
+        let legacy = home_dir().map(|home| home.join(Self::LEGACY_FILE_NAME));
+        match (Self::path(), legacy) {
+            (Some(path), _) if path.exists() => Some(path),
+            (_, Some(legacy)) if legacy.exists() => Some(legacy),
+            _ => None,
+        }
+    }
+
+    pub fn path() -> Option<PathBuf> {
+        env::var_os("APPDATA")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("XDG_CONFIG_HOME").map(PathBuf::from))
+            .or_else(|| home_dir().map(|home| home.join(".config")))
+            .map(|dir| dir.join(Self::DIR_NAME).join(Self::FILE_NAME))
+    }
+
+    // where logs and other files we produce go, created on first use
+    pub fn data_dir() -> io::Result<PathBuf> {
+        let dir = home_dir()
+            .ok_or_else(|| Error::new(ErrorKind::NotFound, "couldn't find the home directory"))?
+            .join(".rusty-analyser");
+        fs::create_dir_all(&dir)?;
+        Ok(dir)
+    }
+
+    // a missing file is not an error, you just get the defaults
+    pub fn load() -> io::Result<Config> {
+        let path = match Self::existing_path() {
+            Some(path) => path,
+            None => return Ok(Config::default()),
+        };
+
+        let contents = fs::read_to_string(&path)?;
+        let invalid = |message: String| {
+            Error::new(ErrorKind::InvalidData, format!("{}: {}", path.display(), message))
+        };
+        let config: Config = toml::from_str(&contents).map_err(|e| invalid(e.to_string()))?;
+        config.validate().map_err(invalid)?;
+        Ok(config)
+    }
+
+    // rewrites the whole file, comments in it don't survive this
+    pub fn save(&self) -> io::Result<()> {
+        let path = Self::path()
+            .ok_or_else(|| Error::new(ErrorKind::NotFound, "couldn't find the config directory"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let contents = toml::to_string(self).map_err(Error::other)?;
+        fs::write(path, contents)
+    }
+
+    // the keys the file actually sets, as flatten() names them, without validating anything
+    pub fn file_keys() -> io::Result<Vec<String>> {
+        let path = match Self::existing_path() {
+            Some(path) => path,
+            None => return Ok(Vec::new()),
+        };
+        let table: toml::Table = toml::from_str(&fs::read_to_string(&path)?)
+            .map_err(|e| Error::new(ErrorKind::InvalidData, format!("{}: {}", path.display(), e)))?;
+        let mut keys = Vec::new();
+        flatten_into("", &table, &mut keys);
+        Ok(keys.into_iter().map(|(key, _)| key).collect())
+    }
+
+    // every setting as ("section.key", value) with the smtp login masked
+    pub fn flatten(&self) -> Vec<(String, String)> {
+        let mut settings = Vec::new();
+        if let Ok(table) = toml::Table::try_from(self) {
+            flatten_into("", &table, &mut settings);
+        }
+        for (key, value) in &mut settings {
+            if key == "smtp.username" || key == "smtp.password" {
+                *value = "********".to_string();
+            }
+        }
+        settings
+    }
+
+    pub fn get_tunable(&self, name: &str) -> Option<String> {
+        Some(match name {
+            "min-folder-size" => self.thresholds.min_folder_size_gb.to_string(),
+            "min-filetype-size" => self.thresholds.min_file_type_size_gb.to_string(),
+            "low-free-space" => self.thresholds.low_free_space_percent.to_string(),
+            "critical-free-space" => self.thresholds.critical_free_space_percent.to_string(),
+            "recent-window" => self.days.recent_window_days.to_string(),
+            "old-window" => self.days.old_window_days.to_string(),
+            "activity-days" => self.days.activity.to_string(),
+            "top-n" => self.display.top_n.to_string(),
+            _ => return None,
+        })
+    }
+
+    // parses and validates on a copy first, so a bad value leaves everything as it was
+    pub fn set_tunable(&mut self, name: &str, value: &str) -> Result<(), String> {
+        let key = TUNABLES
+            .iter()
+            .find(|(tunable, _)| *tunable == name)
+            .map(|(_, key)| *key)
+            .ok_or_else(|| format!("unknown setting '{}', run set to list them", name))?;
+        let mut updated = self.clone();
+        updated
+            .set_key(key, value)
+            .map_err(|e| format!("{}: '{}' is not a valid value ({})", name, value, e))?;
+        updated.validate()?;
+        *self = updated;
+        Ok(())
+    }
+
+    // an empty [smtp] section to fill in when only some of it comes from the environment
+    fn smtp_mut(&mut self) -> &mut SmtpConfig {
+        self.smtp.get_or_insert_with(|| SmtpConfig {
+            host: String::new(),
+            port: default_smtp_port(),
+            username: String::new(),
+            password: String::new(),
+            from: None,
+        })
+    }
+
+    // one key from its text form, as named in the file, without validating the result
+    fn set_key(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "exclude" => self.exclude = parse_list(value),
+            "thresholds.min_folder_size_gb" => self.thresholds.min_folder_size_gb = parse(value)?,
+            "thresholds.min_file_type_size_gb" => self.thresholds.min_file_type_size_gb = parse(value)?,
+            "thresholds.low_free_space_percent" => self.thresholds.low_free_space_percent = parse(value)?,
+            "thresholds.critical_free_space_percent" => self.thresholds.critical_free_space_percent = parse(value)?,
+            "days.recent_window_days" => self.days.recent_window_days = parse_days(value)?,
+            "days.old_window_days" => self.days.old_window_days = parse_days(value)?,
+            "days.activity" => self.days.activity = parse(value)?,
+            "display.top_n" => self.display.top_n = parse(value)?,
+            "display.color" => self.display.color = parse_bool(value)?,
+            "display.no_color" => self.display.color = !parse_bool(value)?,
+            "display.units" => {
+                self.display.units = match value.trim().to_lowercase().as_str() {
+                    "binary" | "iec" => Units::Binary,
+                    "decimal" | "si" => Units::Decimal,
+                    _ => return Err("expected binary, decimal, iec or si".to_string()),
+                }
+            }
+            "display.date_format" => self.display.date_format = value.to_string(),
+            "display.utc" => self.display.utc = parse_bool(value)?,
+            "scan.threads" => self.scan.threads = Some(parse(value)?),
+            "scan.exclude" => self.scan.exclude = parse_list(value),
+            "scan.skip_system_dirs" => self.scan.skip_system_dirs = parse_bool(value)?,
+            "scan.user_data_roots" => self.scan.user_data_roots = parse_list(value),
+            "scan.follow_links" => self.scan.follow_links = parse_bool(value)?,
+            "scan.max_depth" => self.scan.max_depth = Some(parse(value)?),
+            "scan.cache_ttl_minutes" => self.scan.cache_ttl_minutes = Some(parse(value)?),
+            "smtp.host" => self.smtp_mut().host = value.to_string(),
+            "smtp.port" => self.smtp_mut().port = parse(value)?,
+            "smtp.username" => self.smtp_mut().username = value.to_string(),
+            "smtp.password" => self.smtp_mut().password = value.to_string(),
+            "smtp.from" => self.smtp_mut().from = Some(value.to_string()),
+            _ => return Err(format!("unknown key '{}'", key)),
+        }
+        Ok(())
+    }
+
+    // applied after the file and before anything set in the shell
+    // a bad value is skipped with a warning, the rest still apply
+    pub fn apply_env(&mut self) {
+        for (key, var) in ENV_OVERRIDES {
+            let Ok(value) = env::var(var) else { continue };
+            let mut updated = self.clone();
+            match updated.set_key(key, &value).and_then(|_| updated.validate()) {
+                Ok(()) => {
+                    *self = updated;
+                    // RSA_NO_COLOR is reported as the key it changes
+                    let key = if *key == "display.no_color" { "display.color" } else { *key };
+                    let value = self.flatten().into_iter().find(|(k, _)| k == key).map(|(_, v)| v).unwrap_or(value);
+                    self.env_overrides.push((key, value));
+                }
+                Err(e) => log::warn!("Ignoring {}={}: {}", var, value, e),
+            }
+        }
+    }
+
+    // serde only checks the types, this catches values that parse but make no sense
+    fn validate(&self) -> Result<(), String> {
+        let t = &self.thresholds;
+        for (key, value) in [
+            ("thresholds.min_folder_size_gb", t.min_folder_size_gb),
+            ("thresholds.min_file_type_size_gb", t.min_file_type_size_gb),
+        ] {
+            if value < 0.0 || value.is_nan() {
+                return Err(format!("{} can't be negative (got {})", key, value));
+            }
+        }
+        for (key, value) in [
+            ("thresholds.low_free_space_percent", t.low_free_space_percent),
+            ("thresholds.critical_free_space_percent", t.critical_free_space_percent),
+        ] {
+            if !(0.0..=100.0).contains(&value) {
+                return Err(format!("{} has to be between 0 and 100 (got {})", key, value));
+            }
+        }
+        if t.critical_free_space_percent > t.low_free_space_percent {
+            return Err(format!(
+                "thresholds.critical_free_space_percent ({}) can't be above thresholds.low_free_space_percent ({})",
+                t.critical_free_space_percent, t.low_free_space_percent
+            ));
+        }
+
+        for (key, value) in [
+            ("days.recent_window_days", self.days.recent_window_days),
+            ("days.old_window_days", self.days.old_window_days),
+        ] {
+            if value < 1 {
+                return Err(format!("{} has to be at least 1 (got {})", key, value));
+            }
+        }
+        if self.days.activity == 0 {
+            return Err("days.activity has to be at least 1 (got 0)".to_string());
+        }
+        if !is_valid_time_format(&self.display.date_format) {
+            return Err(format!("display.date_format '{}' is not a valid format string", self.display.date_format));
+        }
+        if self.display.top_n == 0 {
+            return Err("display.top_n has to be at least 1 (got 0)".to_string());
+        }
+        if self.scan.max_depth == Some(0) {
+            return Err("scan.max_depth has to be at least 1 (got 0), leave it out to scan every level".to_string());
+        }
+        if self.scan.threads == Some(0) {
+            return Err("scan.threads has to be at least 1 (got 0), leave it out to use every core".to_string());
+        }
+        scan_glob_set(&self.scan.exclude).map_err(|e| e.to_string())?;
+        for (i, pattern) in self.exclude.iter().enumerate() {
+            ExcludeList::new(std::slice::from_ref(pattern)).map_err(|e| format!("exclude[{}]: {}", i, e))?;
+        }
+        for (name, profile) in &self.profiles {
+            profile.validate().map_err(|e| format!("profile.{}.{}", name, e))?;
+        }
+        for (i, rule) in self.quotas.iter().enumerate() {
+            if rule.limit_gb < 0.0 || rule.limit_gb.is_nan() {
+                return Err(format!("quota[{}].limit_gb can't be negative (got {})", i, rule.limit_gb));
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,10 +1,196 @@
-// storage size conversions
-pub const GB_TO_BYTES: f64 = 1_073_741_824.0;
-pub const MB_TO_BYTES: f64 = 1_048_576.0;
+// storage size conversions, IEC (what display.units = "binary" shows) and SI
+pub const GIB_TO_BYTES: f64 = 1_073_741_824.0;
+pub const MIB_TO_BYTES: f64 = 1_048_576.0;
+pub const GB_TO_BYTES: f64 = 1_000_000_000.0;
+pub const MB_TO_BYTES: f64 = 1_000_000.0;
+
+// free space warning levels, in percent
+pub const LOW_FREE_SPACE_PERCENT: f64 = 20.0;
+pub const CRITICAL_FREE_SPACE_PERCENT: f64 = 10.0;
 
 // minimum size thresholds for analysis
 pub const MIN_FOLDER_SIZE_GB: f64 = 0.1;
 pub const MIN_FILE_TYPE_SIZE_GB: f64 = 0.01;
 
+// defaults for the day windows and report lengths, all can be changed in the config file
+pub const RECENT_DAYS: i64 = 30;
+pub const OLD_DAYS: i64 = 180;
+pub const DEFAULT_TOP_N: usize = 10;
+
 // time format
-pub const DATE_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
\ No newline at end of file
+pub const DATE_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+// date part of DATE_FORMAT, for per-day grouping
+pub const DAY_FORMAT: &str = "%Y-%m-%d";
+
+// extension groups used for the category breakdown
+pub const MEDIA_EXTS: &[&str] = &[
+    "mp4", "mkv", "avi", "mov", "wmv", "flv", "webm", "m4v",
+    "mp3", "wav", "flac", "aac", "ogg", "m4a", "wma",
+    "jpg", "jpeg", "png", "gif", "bmp", "tiff", "webp", "heic", "raw", "psd",
+];
+pub const DOCUMENT_EXTS: &[&str] = &[
+    "pdf", "doc", "docx", "xls", "xlsx", "ppt", "pptx", "odt", "ods", "odp",
+    "txt", "rtf", "md", "csv", "epub",
+];
+pub const CODE_EXTS: &[&str] = &[
+    "rs", "c", "h", "cpp", "hpp", "cs", "java", "py", "js", "ts", "go", "rb",
+    "php", "html", "css", "json", "toml", "yaml", "yml", "xml", "sh", "ps1",
+];
+pub const ARCHIVE_EXTS: &[&str] = &[
+    "zip", "rar", "7z", "tar", "gz", "bz2", "xz", "iso", "cab",
+];
+pub const SYSTEM_EXTS: &[&str] = &[
+    "exe", "dll", "sys", "msi", "drv", "ocx", "cpl", "bin", "dat", "log", "tmp",
+];
+
+// folders at the root of a drive that scans leave out unless told otherwise,
+// mostly access denied and rarely anything the user can clean up
+pub const SYSTEM_DIRS: &[&str] = &["Windows", "System Volume Information", "$Recycle.Bin"];
+
+// top-creators only looks up the owners of this many of the largest files
+pub const OWNER_SAMPLE_SIZE: usize = 10_000;
+
+// metrics endpoint
+pub const DEFAULT_METRICS_ADDR: &str = "127.0.0.1:9184";
+// a scraper that connects and goes quiet is dropped after this
+pub const METRICS_CLIENT_TIMEOUT_SECS: u64 = 5;
+
+// installer packages that tend to be left behind after setup
+pub const INSTALLER_EXTS: &[&str] = &[
+    "msi", "msp", "exe", "pkg", "dmg", "deb", "rpm", "appx", "msix", "cab",
+];
+
+// files that can run something, for find-execs, Windows has no execute bit so the extension is all there is
+pub const EXECUTABLE_EXTS: &[&str] = &[
+    "exe", "dll", "sys", "bat", "cmd", "ps1", "sh", "com", "scr", "msi", "vbs",
+];
+// how many of them find-execs lists, the totals cover all of them
+pub const EXECUTABLES_SHOWN: usize = 20;
+
+// folder-age lists this many of the most frozen and the most active top-level folders
+pub const FOLDER_AGE_SHOWN: usize = 10;
+
+// unsynced-large, where the OneDrive client says its folders are, %USERPROFILE%\OneDrive is the fallback
+pub const ONEDRIVE_ENV_VARS: &[&str] = &["OneDrive", "OneDriveConsumer", "OneDriveCommercial"];
+pub const UNSYNCED_MIN_SIZE_MB: f64 = 100.0;
+
+// recent-large-folders without its optional arguments
+pub const RECENT_FOLDERS_DAYS: u32 = 30;
+pub const RECENT_FOLDERS_MIN_GB: f64 = 1.0;
+
+// activity report
+pub const DEFAULT_ACTIVITY_DAYS: u32 = 30;
+
+// cleanup heuristics
+pub const DEV_ARTIFACT_DIRS: &[&str] = &[
+    "node_modules", "target", "__pycache__", ".gradle", ".venv", ".pytest_cache", ".next",
+];
+pub const CLUTTER_FILE_NAMES: &[&str] = &["thumbs.db", "ehthumbs.db", ".ds_store"];
+pub const STALE_MIN_SIZE_MB: f64 = 500.0;
+pub const STALE_AGE_DAYS: i64 = 180;
+
+// roots the cleanup wizard will never touch, in comparable_path form
+pub const PROTECTED_ROOTS: &[&str] = &[
+    "c:/windows",
+    "c:/program files",
+    "c:/program files (x86)",
+    "c:/programdata/microsoft",
+    "c:/system volume information",
+    "c:/$recycle.bin",
+    "c:/recovery",
+    "c:/boot",
+];
+
+// compression candidates
+pub const COMPRESSIBLE_EXTS: &[&str] = &[
+    "txt", "log", "csv", "tsv", "json", "xml", "sql", "bmp", "pdb", "dmp", "etl",
+];
+pub const COMPRESS_MIN_SIZE_MB: f64 = 50.0;
+pub const COMPRESS_MIN_AGE_DAYS: i64 = 90;
+pub const COMPRESS_MIN_IDLE_DAYS: i64 = 30;
+pub const COMPRESS_SAMPLE_BYTES: u64 = 4 * 1024 * 1024;
+
+// sparse files only get listed when they use less than this much of their logical size
+pub const SPARSE_MAX_PHYSICAL_RATIO: f64 = 0.5;
+
+// duplicates and reclaim estimate
+pub const DUPLICATE_MIN_SIZE_MB: f64 = 1.0;
+pub const DUPLICATE_PREFIX_BYTES: u64 = 64 * 1024;
+// duplicate groups (or cross-drive pairs) listed, the totals count all of them
+pub const DUPLICATES_SHOWN: usize = 20;
+pub const INSTALLER_MIN_AGE_DAYS: i64 = 30;
+// how often the live scan table redraws
+pub const LIVE_UI_REFRESH_MS: u64 = 500;
+
+// drive-label, NTFS stops at 32 characters and label.exe refuses these
+pub const VOLUME_LABEL_MAX_CHARS: usize = 32;
+pub const VOLUME_LABEL_INVALID_CHARS: &[char] = &['*', '?', '/', '\\', '|', '.', ',', ';', ':', '+', '=', '[', ']', '<', '>', '"'];
+
+// top-disk-writers, how long the write counters are watched and how many processes are listed
+pub const DISK_WRITERS_SAMPLE_SECS: u64 = 3;
+pub const DISK_WRITERS_SHOWN: usize = 5;
+
+// a scan keeps this many of the paths it couldn't read, the rest are only counted
+pub const UNREADABLE_PATHS_KEPT: usize = 100;
+// reports built from a scan with at least this many unreadable entries say so
+pub const UNREADABLE_NOTE_MIN: usize = 100;
+
+// named snapshots live in this folder under the data dir, one <name>.json each
+pub const SNAPSHOT_DIR_NAME: &str = "snapshots";
+
+// usage-log writes a row per drive this often unless it's given an interval
+pub const USAGE_LOG_INTERVAL_SECS: u64 = 300;
+
+// shell history
+pub const HISTORY_FILE_NAME: &str = "history.txt";
+pub const HISTORY_SHOW_LAST: usize = 20;
+// commands that take longer than this get their run time shown above the next prompt
+pub const SHOW_DURATION_AFTER_MS: u64 = 1000;
+
+// show-env, only variables that say where files go or how output looks, nothing with secrets
+#[cfg(windows)]
+pub const ENV_REPORT_VARS: &[&str] =
+    &["USERPROFILE", "TEMP", "TMP", "APPDATA", "LOCALAPPDATA", "COMSPEC", "PATH", "RUST_LOG", "NO_COLOR"];
+#[cfg(not(windows))]
+pub const ENV_REPORT_VARS: &[&str] = &["HOME", "TMPDIR", "XDG_DATA_HOME", "PATH", "RUST_LOG", "NO_COLOR"];
+pub const ENV_REPORT_PATH_ENTRIES: usize = 5;
+
+// how much of one command's output copy-last keeps, a full file listing can run to megabytes
+pub const CAPTURE_LIMIT_BYTES: usize = 1024 * 1024;
+
+// Win32 codes that mean the disk or the filesystem on it is damaged rather than just busy or locked,
+// they come back as ErrorKind::Uncategorized so the kind alone doesn't catch them
+pub const FS_CORRUPTION_CODES: &[i32] = &[
+    23,   // ERROR_CRC
+    27,   // ERROR_SECTOR_NOT_FOUND
+    30,   // ERROR_READ_FAULT
+    1005, // ERROR_UNRECOGNIZED_VOLUME
+    1006, // ERROR_FILE_INVALID
+    1117, // ERROR_IO_DEVICE
+    1127, // ERROR_DISK_OPERATION_FAILED
+    1392, // ERROR_FILE_CORRUPT
+    1393, // ERROR_DISK_CORRUPT
+];
+
+// how deep NOTs and parentheses can nest in a filter-cache expression, every level is a stack frame
+pub const FILTER_MAX_DEPTH: usize = 64;
+
+pub const READ_ONLY_MESSAGE: &str = "Read-only mode is active. Use set-readonly false to enable writes.";
+
+// analyze-downloads lists files older than this as old
+pub const DOWNLOADS_OLD_DAYS: i64 = 90;
+
+// ext-mismatch only reads the start of the biggest files
+pub const MISMATCH_SAMPLE_FILES: usize = 1000;
+pub const MISMATCH_HEADER_BYTES: u64 = 512;
+// extensions that are the same format as what the header says, or a container built on it
+pub const MISMATCH_ALIASES: &[(&str, &[&str])] = &[
+    ("jpg", &["jpeg", "jpe", "jfif"]),
+    ("tif", &["tiff"]),
+    ("mp4", &["m4v", "m4a", "m4b", "mov", "3gp", "3g2"]),
+    ("mkv", &["webm", "mka", "mk3d"]),
+    ("gz", &["tgz"]),
+    ("zip", &["docx", "xlsx", "pptx", "jar", "apk", "epub", "odt", "ods", "odp", "vsix", "nupkg", "whl", "xpi", "appx", "msix", "kmz"]),
+    ("exe", &["dll", "sys", "scr", "ocx", "cpl", "efi", "mui", "drv"]),
+    ("msi", &["msp", "doc", "xls", "ppt", "msg"]),
+];
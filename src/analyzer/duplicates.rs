@@ -0,0 +1,118 @@
+use super::{
+    error::{AnalyzerError, Result},
+    constants::*,
+    file_ops::{hash_file, hash_file_prefix, sha256_file},
+    printers,
+    storage::StorageAnalyzer,
+    types::*,
+    utils::{comparable_path, is_under, mb_to_bytes, normalize_path}
+};
+use rayon::prelude::*;
+use std::{collections::{HashMap, HashSet}, hash::Hash, io, path::Path};
+
+// splits each group further by a hash, dropping anything that ends up alone
+fn regroup_by<F, H>(groups: Vec<Vec<FileInfo>>, hash: F) -> Vec<Vec<FileInfo>>
+where
+    F: Fn(&Path) -> io::Result<H> + Sync,
+    H: Hash + Eq,
+{
+    groups
+        .into_par_iter()
+        .flat_map(|group| {
+            let mut by_hash: HashMap<H, Vec<FileInfo>> = HashMap::new();
+            for file in group {
+                if let Ok(h) = hash(&file.full_path) {
+                    by_hash.entry(h).or_default().push(file);
+                }
+            }
+            by_hash.into_values().filter(|g| g.len() > 1).collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+impl StorageAnalyzer {
+    // groups of files with identical contents, the groups wasting the most space first
+    // sizes are compared first, then a hash of the first 64 KB, and only then the full contents
+    pub fn find_duplicates(&mut self, drive: &str) -> Result<Vec<Vec<FileInfo>>> {
+        self.collect_and_cache_files(drive)?;
+        let files = self.scanned_files(drive)?;
+
+        let mut by_size: HashMap<u64, Vec<FileInfo>> = HashMap::new();
+        let min_bytes = mb_to_bytes(self.units(), DUPLICATE_MIN_SIZE_MB);
+        for file in files.iter().filter(|f| f.size_bytes >= min_bytes) {
+            by_size.entry(file.size_bytes).or_default().push(file.clone());
+        }
+        let candidates: Vec<Vec<FileInfo>> = by_size.into_values().filter(|g| g.len() > 1).collect();
+
+        let candidates = regroup_by(candidates, |path| hash_file_prefix(path, DUPLICATE_PREFIX_BYTES));
+        let mut duplicates = regroup_by(candidates, hash_file);
+
+        duplicates.par_sort_unstable_by_key(|group| std::cmp::Reverse(group[0].size_bytes * (group.len() - 1) as u64));
+        Ok(duplicates)
+    }
+
+    /// Files on `drive_a` with an identical copy on `drive_b`, as (file on a, copy on b), the
+    /// biggest first. A file with several copies on b is paired with one of them.
+    ///
+    /// Only files of at least `min_bytes` are compared, 1 MB when it's `None`. They're matched by size, then the first
+    /// 64 KB, then a SHA-256 of the whole contents, since one side is meant to be deleted on the
+    /// strength of it. Fails with [`AnalyzerError::InvalidArgument`] when one of the two is
+    /// inside the other.
+    pub fn find_cross_drive_duplicates(&mut self, drive_a: &str, drive_b: &str, min_bytes: Option<u64>) -> Result<Vec<(FileInfo, FileInfo)>> {
+        let (drive_a, drive_b) = (normalize_path(drive_a), normalize_path(drive_b));
+        let (a, b) = (comparable_path(&drive_a), comparable_path(&drive_b));
+        if is_under(&a, &b) || is_under(&b, &a) {
+            return Err(AnalyzerError::InvalidArgument(format!("{} and {} overlap, pick two separate drives", drive_a, drive_b)));
+        }
+        self.collect_and_cache_files(&drive_a)?;
+        self.collect_and_cache_files(&drive_b)?;
+
+        let min_bytes = min_bytes.unwrap_or_else(|| mb_to_bytes(self.units(), DUPLICATE_MIN_SIZE_MB));
+        let mut by_size: HashMap<u64, Vec<FileInfo>> = HashMap::new();
+        for file in self.scanned_files(&drive_a)?.iter().filter(|f| f.size_bytes >= min_bytes) {
+            by_size.entry(file.size_bytes).or_default().push(file.clone());
+        }
+        // only sizes that turn up on both drives are worth hashing
+        let mut on_b = HashSet::new();
+        for file in self.scanned_files(&drive_b)? {
+            if let Some(group) = by_size.get_mut(&file.size_bytes) {
+                on_b.insert(file.size_bytes);
+                group.push(file.clone());
+            }
+        }
+        let candidates: Vec<Vec<FileInfo>> = by_size.into_iter().filter(|(size, _)| on_b.contains(size)).map(|(_, g)| g).collect();
+
+        let candidates = regroup_by(candidates, |path| hash_file_prefix(path, DUPLICATE_PREFIX_BYTES));
+        let mut pairs: Vec<(FileInfo, FileInfo)> = regroup_by(candidates, sha256_file)
+            .into_iter()
+            .flat_map(|group| {
+                let (on_a, on_b): (Vec<_>, Vec<_>) =
+                    group.into_iter().partition(|f| is_under(&comparable_path(&f.full_path), &a));
+                let copy = on_b.into_iter().next();
+                on_a.into_iter().filter_map(move |file| Some((file, copy.clone()?)))
+            })
+            .collect();
+
+        pairs.par_sort_unstable_by(|x, y| y.0.size_bytes.cmp(&x.0.size_bytes).then_with(|| x.0.full_path.cmp(&y.0.full_path)));
+        Ok(pairs)
+    }
+
+    pub fn print_cross_drive_duplicates(&mut self, drive_a: &str, drive_b: &str, min_bytes: Option<u64>) -> Result<()> {
+        let units = self.units();
+        let pairs = self.find_cross_drive_duplicates(drive_a, drive_b, min_bytes)?;
+        let smallest = min_bytes.unwrap_or_else(|| mb_to_bytes(units, DUPLICATE_MIN_SIZE_MB));
+        // the copies on a are numbered, that's the side a backup on b makes redundant
+        if !pairs.is_empty() {
+            self.set_last_listing(pairs.iter().take(DUPLICATES_SHOWN).map(|(file, _)| file.full_path.clone()).collect());
+        }
+        Ok(printers::cross_drive_duplicates(&mut self.out(), units, drive_a, drive_b, smallest, &pairs, DUPLICATES_SHOWN)?)
+    }
+
+    pub fn print_duplicates(&mut self, drive: &str) -> Result<()> {
+        let duplicates = self.find_duplicates(drive)?;
+        if !duplicates.is_empty() {
+            self.set_last_listing(duplicates.iter().take(DUPLICATES_SHOWN).flatten().map(|file| file.full_path.clone()).collect());
+        }
+        Ok(printers::duplicates(&mut self.out(), self.units(), &duplicates, DUPLICATES_SHOWN)?)
+    }
+}
@@ -0,0 +1,32 @@
+use super::config::SmtpConfig;
+use lettre::{
+    message::header::ContentType,
+    transport::smtp::authentication::Credentials,
+    Message, SmtpTransport, Transport,
+};
+use std::io::{self, Error, ErrorKind};
+
+// sends an HTML body over SMTP with STARTTLS, the connection is never sent in plain text
+pub fn send_html_email(smtp: &SmtpConfig, recipient: &str, subject: &str, html: String) -> io::Result<()> {
+    let from = smtp.from.as_deref().unwrap_or(&smtp.username);
+    let invalid = |what: &str, e: &dyn std::fmt::Display| {
+        Error::new(ErrorKind::InvalidInput, format!("invalid {}: {}", what, e))
+    };
+
+    let message = Message::builder()
+        .from(from.parse().map_err(|e| invalid("sender address", &e))?)
+        .to(recipient.parse().map_err(|e| invalid("recipient address", &e))?)
+        .subject(subject)
+        .header(ContentType::TEXT_HTML)
+        .body(html)
+        .map_err(|e| invalid("email", &e))?;
+
+    let mailer = SmtpTransport::starttls_relay(&smtp.host)
+        .map_err(|e| invalid("SMTP host", &e))?
+        .port(smtp.port)
+        .credentials(Credentials::new(smtp.username.clone(), smtp.password.clone()))
+        .build();
+
+    mailer.send(&message).map_err(Error::other)?;
+    Ok(())
+}
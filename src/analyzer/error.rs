@@ -0,0 +1,87 @@
+use std::io;
+use thiserror::Error;
+
+// what went wrong in terms a caller can act on, the shell turns these into messages and exit codes
+#[derive(Debug, Error)]
+pub enum AnalyzerError {
+    #[error("{drive} is not a valid fixed drive, valid drives are: {valid:?}")]
+    DriveNotFound { drive: String, valid: Vec<String> },
+    #[error("{0} does not exist")]
+    RootNotFound(String),
+    #[error("{0} reports no usable size, it may be a card reader without media or a failing volume")]
+    DriveUnavailable(String),
+    #[error("access denied on {0}, running as administrator may help")]
+    AccessDenied(String),
+    #[error("scan cancelled")]
+    Cancelled,
+    #[error("there is no cached file scan of {0}, run a scan of it first")]
+    CacheMissing(String),
+    #[error("{0}")]
+    InvalidArgument(String),
+    #[error("this file uses schema {found}, this version of Rusty-Analyser only reads up to {supported}, update it to open the file")]
+    UnsupportedSchema { found: u32, supported: u32 },
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+pub type Result<T, E = AnalyzerError> = std::result::Result<T, E>;
+
+impl AnalyzerError {
+    // for the headless CLI, 2 is also what a bad command line exits with
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            AnalyzerError::Io(_) => 1,
+            AnalyzerError::InvalidArgument(_) => 2,
+            AnalyzerError::DriveNotFound { .. } => 3,
+            AnalyzerError::RootNotFound(_) => 4,
+            AnalyzerError::AccessDenied(_) => 5,
+            AnalyzerError::CacheMissing(_) => 6,
+            AnalyzerError::UnsupportedSchema { .. } => 7,
+            AnalyzerError::DriveUnavailable(_) => 8,
+            AnalyzerError::Cancelled => 130,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::StorageAnalyzer;
+
+    #[test]
+    fn scanning_a_missing_root_is_root_not_found() {
+        let root = std::env::temp_dir().join("rusty-analyser-test-missing-root");
+        let _ = std::fs::remove_dir_all(&root);
+        let root = root.to_string_lossy().to_string();
+
+        let mut analyzer = StorageAnalyzer::new();
+        analyzer.set_quiet(true);
+        let err = analyzer.collect_and_cache_files(&root).unwrap_err();
+
+        match err {
+            AnalyzerError::RootNotFound(path) => assert!(path.contains("rusty-analyser-test-missing-root")),
+            other => panic!("expected RootNotFound, got {:?}", other),
+        }
+        // nothing half scanned is left behind either
+        assert!(analyzer.cached_files(&root).is_none());
+    }
+
+    #[test]
+    fn exit_codes_are_distinct() {
+        let errors = [
+            AnalyzerError::Io(io::Error::other("disk")),
+            AnalyzerError::InvalidArgument("bad".into()),
+            AnalyzerError::DriveNotFound { drive: "Q:/".into(), valid: vec!["C:/".into()] },
+            AnalyzerError::RootNotFound("Q:/".into()),
+            AnalyzerError::AccessDenied("C:/".into()),
+            AnalyzerError::CacheMissing("C:/".into()),
+            AnalyzerError::UnsupportedSchema { found: 2, supported: 1 },
+            AnalyzerError::DriveUnavailable("E:/".into()),
+            AnalyzerError::Cancelled,
+        ];
+        let mut codes: Vec<i32> = errors.iter().map(AnalyzerError::exit_code).collect();
+        codes.sort();
+        codes.dedup();
+        assert_eq!(codes.len(), errors.len());
+    }
+}
@@ -0,0 +1,67 @@
+use super::{
+    error::Result,
+    storage::StorageAnalyzer,
+    utils::normalize_path
+};
+use globset::{GlobBuilder, GlobMatcher, GlobSet, GlobSetBuilder};
+use std::{
+    io::{self, Error, ErrorKind},
+    path::Path,
+};
+
+// paths and globs from the `exclude` list in the config, compiled once
+// an entry excludes whatever it matches and everything below that
+#[derive(Default)]
+pub struct ExcludeList {
+    patterns: Vec<String>,
+    matchers: Vec<(GlobMatcher, bool)>,
+}
+
+impl ExcludeList {
+    pub fn new(patterns: &[String]) -> Result<ExcludeList> {
+        let matchers = patterns
+            .iter()
+            .map(|p| StorageAnalyzer::compile_pattern(&normalize_path(p)))
+            .collect::<Result<_>>()?;
+        Ok(ExcludeList { patterns: patterns.to_vec(), matchers })
+    }
+
+    pub fn patterns(&self) -> &[String] {
+        &self.patterns
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    // only looks at the path itself, used while walking where parents were already checked
+    pub fn matches(&self, path: &Path) -> bool {
+        let full_path = normalize_path(&path.to_string_lossy());
+        self.matchers.iter().any(|(matcher, match_full_path)| {
+            if *match_full_path {
+                matcher.is_match(&full_path)
+            } else {
+                path.file_name().map(|name| matcher.is_match(name)).unwrap_or(false)
+            }
+        })
+    }
+
+    pub fn is_excluded(&self, path: impl AsRef<Path>) -> bool {
+        !self.is_empty() && path.as_ref().ancestors().any(|p| self.matches(p))
+    }
+}
+
+// the `[scan] exclude` globs, always matched against the whole path so "*.tmp" works anywhere
+// case-insensitive, and backslashes in a pattern are read as forward slashes
+pub fn scan_glob_set(patterns: &[String]) -> io::Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for (i, pattern) in patterns.iter().enumerate() {
+        let glob = GlobBuilder::new(&pattern.replace('\\', "/"))
+            .case_insensitive(true)
+            .literal_separator(false)
+            .build()
+            .map_err(|e| Error::new(ErrorKind::InvalidInput, format!("scan.exclude[{}]: {}", i, e)))?;
+        builder.add(glob);
+    }
+    builder.build().map_err(|e| Error::new(ErrorKind::InvalidInput, e))
+}
@@ -0,0 +1,349 @@
+use super::{
+    constants::PROTECTED_ROOTS,
+    utils::{comparable_path, is_under}
+};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::hash_map::DefaultHasher,
+    ffi::OsStr,
+    fs::{self, File},
+    hash::Hasher,
+    io::{self, Error, ErrorKind, Read, Write},
+    os::windows::ffi::OsStrExt,
+    path::{Component, Path, PathBuf},
+    process::Command,
+    ptr,
+};
+use walkdir::WalkDir;
+use winapi::um::shellapi::{
+    SHFileOperationW, SHQueryRecycleBinW, SHFILEOPSTRUCTW, SHQUERYRBINFO, FO_DELETE,
+    FOF_ALLOWUNDO, FOF_NOCONFIRMATION, FOF_NOERRORUI, FOF_SILENT, FOF_WANTNUKEWARNING,
+};
+
+// moves a file or folder to the Recycle Bin, so it can still be restored from Explorer
+pub fn safe_delete(path: &Path) -> io::Result<()> {
+    if !path.exists() {
+        return Err(Error::new(ErrorKind::NotFound, format!("{} does not exist", path.display())));
+    }
+    safe_delete_many(&[path])
+}
+
+// recycles several paths in a single shell operation
+// refuses up front when a path is on a volume without a Recycle Bin (USB sticks, network
+// shares), Windows would delete it for good there without saying so
+pub fn safe_delete_many(paths: &[&Path]) -> io::Result<()> {
+    if paths.is_empty() {
+        return Ok(());
+    }
+
+    // SHFileOperationW wants absolute, backslashed paths, each null terminated
+    // and the whole list ending with an extra null
+    let mut wide_from: Vec<u16> = Vec::new();
+    for path in paths {
+        let absolute = std::path::absolute(path)?;
+        if let Some(drive) = drive_prefix(&absolute).filter(|drive| recycle_bin_size(&format!("{}\\", drive)).is_err()) {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                format!("{} has no Recycle Bin, nothing was deleted (delete --permanent removes it for good)", drive),
+            ));
+        }
+        let from = absolute.to_string_lossy().replace('/', "\\");
+        wide_from.extend(OsStr::new(&from).encode_wide().chain(Some(0)));
+    }
+    wide_from.push(0);
+
+    let mut op = SHFILEOPSTRUCTW {
+        hwnd: ptr::null_mut(),
+        wFunc: FO_DELETE as u32,
+        pFrom: wide_from.as_ptr(),
+        pTo: ptr::null(),
+        // an item too big for the bin is still asked about, declining it aborts the operation
+        fFlags: FOF_ALLOWUNDO | FOF_NOCONFIRMATION | FOF_NOERRORUI | FOF_SILENT | FOF_WANTNUKEWARNING,
+        fAnyOperationsAborted: 0,
+        hNameMappings: ptr::null_mut(),
+        lpszProgressTitle: ptr::null(),
+    };
+
+    let result = unsafe { SHFileOperationW(&mut op) };
+    if result != 0 {
+        // these aren't regular win32 error codes, but close enough to be useful in a message
+        return Err(Error::other(format!("SHFileOperationW failed with code 0x{:X}", result)));
+    }
+    if op.fAnyOperationsAborted != 0 {
+        return Err(Error::new(ErrorKind::Interrupted, "deletion was aborted"));
+    }
+    Ok(())
+}
+
+// (bytes, items) currently sitting in the Recycle Bin of a drive
+pub fn recycle_bin_size(drive: &str) -> io::Result<(u64, u64)> {
+    let root = drive.replace('/', "\\");
+    let wide_root: Vec<u16> = OsStr::new(&root).encode_wide().chain(Some(0)).collect();
+    let mut info = SHQUERYRBINFO {
+        cbSize: std::mem::size_of::<SHQUERYRBINFO>() as u32,
+        i64Size: 0,
+        i64NumItems: 0,
+    };
+
+    let result = unsafe { SHQueryRecycleBinW(wide_root.as_ptr(), &mut info) };
+    if result != 0 {
+        return Err(Error::other(format!("SHQueryRecycleBinW failed with HRESULT 0x{:X}", result)));
+    }
+    Ok((info.i64Size.max(0) as u64, info.i64NumItems.max(0) as u64))
+}
+
+// whether a path is inside one of the system folders we refuse to clean
+pub fn is_protected(path: &Path) -> bool {
+    let path = comparable_path(path);
+    PROTECTED_ROOTS.iter().any(|root| is_under(&path, root))
+}
+
+// deletes a file or folder for good, bypassing the Recycle Bin
+pub fn delete_permanently(path: &Path) -> io::Result<()> {
+    if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+// shows a path in the system file manager, files get selected inside their folder
+// the process is only spawned, we don't wait for the window to close
+pub fn open_in_file_manager(path: &Path) -> io::Result<()> {
+    if !path.exists() {
+        return Err(Error::new(ErrorKind::NotFound, format!("{} does not exist", path.display())));
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        let absolute = std::path::absolute(path)?;
+        let windows_path = absolute.to_string_lossy().replace('/', "\\");
+        let mut explorer = Command::new("explorer");
+        if path.is_dir() {
+            explorer.raw_arg(format!("\"{}\"", windows_path));
+        } else {
+            // explorer only understands /select when the path is quoted after the comma
+            explorer.raw_arg(format!("/select,\"{}\"", windows_path));
+        }
+        explorer.spawn()?;
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let folder = if path.is_dir() {
+            path
+        } else {
+            path.parent().unwrap_or(path)
+        };
+        let opener = if cfg!(target_os = "macos") { "open" } else { "xdg-open" };
+        Command::new(opener).arg(folder).spawn()?;
+    }
+
+    Ok(())
+}
+
+const COPY_CHUNK_BYTES: usize = 1024 * 1024;
+
+fn drive_prefix(path: &Path) -> Option<String> {
+    match std::path::absolute(path).ok()?.components().next()? {
+        Component::Prefix(prefix) => Some(prefix.as_os_str().to_string_lossy().to_uppercase()),
+        _ => None,
+    }
+}
+
+// only hashes the first `limit` bytes, handy to rule out most non-duplicates cheaply
+pub fn hash_file_prefix(path: &Path, limit: u64) -> io::Result<u64> {
+    let mut file = File::open(path)?.take(limit);
+    let mut hasher = DefaultHasher::new();
+    let mut buffer = vec![0u8; COPY_CHUNK_BYTES];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            return Ok(hasher.finish());
+        }
+        hasher.write(&buffer[..read]);
+    }
+}
+
+pub fn hash_file(path: &Path) -> io::Result<u64> {
+    let mut file = File::open(path)?;
+    let mut hasher = DefaultHasher::new();
+    let mut buffer = vec![0u8; COPY_CHUNK_BYTES];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            return Ok(hasher.finish());
+        }
+        hasher.write(&buffer[..read]);
+    }
+}
+
+// a cryptographic digest of the whole file, for when a match decides what the user may delete
+pub fn sha256_file(path: &Path) -> io::Result<[u8; 32]> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; COPY_CHUNK_BYTES];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            return Ok(hasher.finalize().into());
+        }
+        hasher.update(&buffer[..read]);
+    }
+}
+
+// copies one file in chunks, reporting every chunk through `progress`
+fn copy_file_chunked(src: &Path, dest: &Path, progress: &mut dyn FnMut(u64)) -> io::Result<()> {
+    let mut reader = File::open(src)?;
+    let mut writer = File::create_new(dest)?;
+    let mut buffer = vec![0u8; COPY_CHUNK_BYTES];
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        writer.write_all(&buffer[..read])?;
+        progress(read as u64);
+    }
+    writer.sync_all()?;
+
+    if let Ok(modified) = fs::metadata(src).and_then(|m| m.modified()) {
+        let _ = writer.set_modified(modified);
+    }
+    Ok(())
+}
+
+// what a cross-drive move has to recreate, paths relative to the source
+// an empty relative path is the source itself when that's a single file
+#[derive(Default)]
+struct MovePlan {
+    dirs: Vec<PathBuf>,
+    files: Vec<(PathBuf, u64)>,
+}
+
+// links and junctions would either be copied as what they point to or not at all,
+// neither is a move, so they stop it before anything is touched
+fn is_reparse_point(metadata: &fs::Metadata) -> bool {
+    use std::os::windows::fs::MetadataExt;
+    const FILE_ATTRIBUTE_REPARSE_POINT: u32 = 0x400;
+    metadata.file_type().is_symlink() || metadata.file_attributes() & FILE_ATTRIBUTE_REPARSE_POINT != 0
+}
+
+// everything below `src`, failing on the first entry that can't be read instead of leaving it behind
+fn plan_move(src: &Path) -> io::Result<MovePlan> {
+    let mut plan = MovePlan::default();
+    for entry in WalkDir::new(src) {
+        let entry = entry.map_err(|e| {
+            let path = e.path().map(|p| p.display().to_string()).unwrap_or_else(|| src.display().to_string());
+            Error::other(format!("can't read {}, nothing was moved: {}", path, e))
+        })?;
+        let metadata = entry.metadata().map_err(|e| Error::other(format!("can't read {}, nothing was moved: {}", entry.path().display(), e)))?;
+        if is_reparse_point(&metadata) {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                format!("{} is a link or junction, move it or its target by hand", entry.path().display()),
+            ));
+        }
+
+        let relative = entry.path().strip_prefix(src).unwrap_or(entry.path()).to_path_buf();
+        if metadata.is_dir() {
+            plan.dirs.push(relative);
+        } else if metadata.is_file() {
+            plan.files.push((relative, metadata.len()));
+        } else {
+            return Err(Error::new(ErrorKind::Unsupported, format!("{} isn't a file or folder", entry.path().display())));
+        }
+    }
+    Ok(plan)
+}
+
+// the folders first (empty ones included, walkdir lists parents before children), then the files
+fn copy_tree(src: &Path, dest: &Path, plan: &MovePlan, verify_hash: bool, progress: &mut dyn FnMut(u64, u64)) -> io::Result<()> {
+    let total: u64 = plan.files.iter().map(|(_, size)| size).sum();
+    let mut copied = 0u64;
+    for relative in &plan.dirs {
+        fs::create_dir(dest.join(relative))?;
+    }
+
+    for (relative, size) in &plan.files {
+        let (from, to) = if relative.as_os_str().is_empty() {
+            (src.to_path_buf(), dest.to_path_buf())
+        } else {
+            (src.join(relative), dest.join(relative))
+        };
+
+        copy_file_chunked(&from, &to, &mut |chunk| {
+            copied += chunk;
+            progress(copied, total);
+        })?;
+
+        if fs::metadata(&to)?.len() != *size {
+            return Err(Error::new(ErrorKind::InvalidData, format!("size mismatch after copying {}", from.display())));
+        }
+        if verify_hash && hash_file(&from)? != hash_file(&to)? {
+            return Err(Error::new(ErrorKind::InvalidData, format!("contents differ after copying {}", from.display())));
+        }
+    }
+    Ok(())
+}
+
+// removes exactly what was copied, a folder that gained something since the walk is left
+// standing (remove_dir refuses non-empty ones) instead of taking the newcomer with it
+fn remove_moved(src: &Path, plan: &MovePlan) -> io::Result<()> {
+    for (relative, _) in &plan.files {
+        fs::remove_file(src.join(relative))?;
+    }
+    for relative in plan.dirs.iter().rev() {
+        fs::remove_dir(src.join(relative)).map_err(|e| {
+            Error::new(e.kind(), format!("copied everything but couldn't remove {}: {}", src.join(relative).display(), e))
+        })?;
+    }
+    Ok(())
+}
+
+// moves a file or folder into `dest_dir` and returns where it ended up
+// on the same drive this is a rename, across drives every file is copied and verified
+// and the source only goes away once everything arrived intact
+// `progress` gets (bytes copied so far, total bytes)
+pub fn move_path(
+    src: &Path,
+    dest_dir: &Path,
+    verify_hash: bool,
+    progress: &mut dyn FnMut(u64, u64),
+) -> io::Result<PathBuf> {
+    let name = src
+        .file_name()
+        .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "can't move a drive root"))?;
+    if !dest_dir.is_dir() {
+        return Err(Error::new(ErrorKind::NotFound, format!("{} is not a folder", dest_dir.display())));
+    }
+    let dest = dest_dir.join(name);
+    if dest.exists() {
+        return Err(Error::new(ErrorKind::AlreadyExists, format!("{} already exists", dest.display())));
+    }
+
+    if drive_prefix(src).is_some() && drive_prefix(src) == drive_prefix(dest_dir) {
+        fs::rename(src, &dest)?;
+        return Ok(dest);
+    }
+
+    let plan = if src.is_dir() {
+        plan_move(src)?
+    } else {
+        let metadata = fs::symlink_metadata(src)?;
+        if is_reparse_point(&metadata) {
+            return Err(Error::new(ErrorKind::Unsupported, format!("{} is a link, move its target by hand", src.display())));
+        }
+        MovePlan { dirs: Vec::new(), files: vec![(PathBuf::new(), metadata.len())] }
+    };
+
+    if let Err(e) = copy_tree(src, &dest, &plan, verify_hash, progress) {
+        // don't leave half a copy behind, e.g. when the destination filled up
+        let _ = delete_permanently(&dest);
+        return Err(e);
+    }
+
+    remove_moved(src, &plan)?;
+    Ok(dest)
+}
@@ -0,0 +1,388 @@
+// the small query language behind filter-cache, e.g. "size_mb > 500 AND NOT ext == 'tmp'"
+//
+// expr    := or
+// or      := and (OR and)*
+// and     := not (AND not)*
+// not     := NOT not | '(' expr ')' | field op value
+// op      := > < >= <= == !=
+//
+// keywords and field names are case insensitive, text goes in single or double quotes
+// (a bare word works too), dates are YYYY-MM-DD and compare by the day they fall on
+use super::{
+    config::Units,
+    constants::FILTER_MAX_DEPTH,
+    error::{AnalyzerError, Result},
+    printers,
+    storage::StorageAnalyzer,
+    types::FileInfo,
+    utils::{bytes_to_mb, display_date, normalize_extension, normalize_path},
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use std::cmp::Ordering;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field {
+    SizeMb,
+    Ext,
+    LastModified,
+    LastAccessed,
+}
+
+impl Field {
+    const NAMES: [&'static str; 4] = ["size_mb", "ext", "last_modified", "last_accessed"];
+
+    fn parse(word: &str) -> Option<Field> {
+        match word.to_lowercase().as_str() {
+            "size_mb" => Some(Field::SizeMb),
+            "ext" => Some(Field::Ext),
+            "last_modified" => Some(Field::LastModified),
+            "last_accessed" => Some(Field::LastAccessed),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Gt,
+    Lt,
+    Ge,
+    Le,
+    Eq,
+    Ne,
+}
+
+impl Op {
+    fn holds(self, ordering: Ordering) -> bool {
+        match self {
+            Op::Gt => ordering == Ordering::Greater,
+            Op::Lt => ordering == Ordering::Less,
+            Op::Ge => ordering != Ordering::Less,
+            Op::Le => ordering != Ordering::Greater,
+            Op::Eq => ordering == Ordering::Equal,
+            Op::Ne => ordering != Ordering::Equal,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Number(f64),
+    Text(String),
+    Date(NaiveDate),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    // a chain like a AND b AND c is one node with every term in it, so however long it gets
+    // matching and dropping it only goes one level deep
+    And(Vec<Node>),
+    Or(Vec<Node>),
+    Not(Box<Node>),
+    Compare(Field, Op, Value),
+}
+
+impl Node {
+    fn matches(&self, file: &FileInfo, units: Units) -> bool {
+        match self {
+            Node::And(terms) => terms.iter().all(|term| term.matches(file, units)),
+            Node::Or(terms) => terms.iter().any(|term| term.matches(file, units)),
+            Node::Not(node) => !node.matches(file, units),
+            Node::Compare(field, op, value) => match (field, value) {
+                (Field::SizeMb, Value::Number(mb)) => bytes_to_mb(units, file.size_bytes)
+                    .partial_cmp(mb)
+                    .is_some_and(|ordering| op.holds(ordering)),
+                (Field::Ext, Value::Text(ext)) => {
+                    let file_ext = file.full_path.extension().map(|e| normalize_extension(&e.to_string_lossy())).unwrap_or_default();
+                    op.holds(file_ext.cmp(ext))
+                }
+                (Field::LastModified, Value::Date(date)) => compare_date(file.last_modified, *op, *date),
+                (Field::LastAccessed, Value::Date(date)) => compare_date(file.last_accessed, *op, *date),
+                // the parser only builds the pairs above
+                _ => false,
+            },
+        }
+    }
+}
+
+/// A parsed filter-cache expression, [`matches`](Self::matches) checks one file against it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilterExpr(Node);
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    Op(Op),
+    Open,
+    Close,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' | ')' => {
+                chars.next();
+                tokens.push(if c == '(' { Token::Open } else { Token::Close });
+            }
+            '\'' | '"' => {
+                chars.next();
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some(next) if next == c => break,
+                        Some(next) => text.push(next),
+                        None => return Err(format!("the {} quote before '{}' is never closed", c, text)),
+                    }
+                }
+                tokens.push(Token::Quoted(text));
+            }
+            '>' | '<' | '=' | '!' => {
+                chars.next();
+                let eq = chars.next_if_eq(&'=').is_some();
+                tokens.push(Token::Op(match (c, eq) {
+                    ('>', false) => Op::Gt,
+                    ('>', true) => Op::Ge,
+                    ('<', false) => Op::Lt,
+                    ('<', true) => Op::Le,
+                    ('=', true) => Op::Eq,
+                    ('!', true) => Op::Ne,
+                    _ => return Err(format!("'{}' isn't an operator, use >, <, >=, <=, == or !=", c)),
+                }));
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(next) = chars.next_if(|&next| !next.is_whitespace() && !"()'\"<>=!".contains(next)) {
+                    word.push(next);
+                }
+                tokens.push(Token::Word(word));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    // NOTs and parentheses the parser is inside of right now
+    depth: usize,
+}
+
+impl Parser {
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.tokens.get(self.pos), Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword))
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn or(&mut self) -> Result<Node, String> {
+        let mut terms = vec![self.and()?];
+        while self.peek_keyword("or") {
+            self.pos += 1;
+            terms.push(self.and()?);
+        }
+        Ok(if terms.len() == 1 { terms.remove(0) } else { Node::Or(terms) })
+    }
+
+    fn and(&mut self) -> Result<Node, String> {
+        let mut terms = vec![self.not()?];
+        while self.peek_keyword("and") {
+            self.pos += 1;
+            terms.push(self.not()?);
+        }
+        Ok(if terms.len() == 1 { terms.remove(0) } else { Node::And(terms) })
+    }
+
+    fn descend(&mut self) -> Result<(), String> {
+        self.depth += 1;
+        if self.depth > FILTER_MAX_DEPTH {
+            return Err(format!("more than {} NOTs and parentheses nested in each other", FILTER_MAX_DEPTH));
+        }
+        Ok(())
+    }
+
+    fn not(&mut self) -> Result<Node, String> {
+        if self.peek_keyword("not") {
+            self.pos += 1;
+            self.descend()?;
+            let expr = Node::Not(Box::new(self.not()?));
+            self.depth -= 1;
+            return Ok(expr);
+        }
+        match self.next() {
+            Some(Token::Open) => {
+                self.descend()?;
+                let expr = self.or()?;
+                self.depth -= 1;
+                match self.next() {
+                    Some(Token::Close) => Ok(expr),
+                    _ => Err("a '(' is never closed".to_string()),
+                }
+            }
+            Some(Token::Word(word)) => self.comparison(&word),
+            Some(token) => Err(format!("expected a field, got {}", describe(&token))),
+            None => Err("the expression ends where a field was expected".to_string()),
+        }
+    }
+
+    fn comparison(&mut self, word: &str) -> Result<Node, String> {
+        let field = Field::parse(word)
+            .ok_or_else(|| format!("'{}' isn't a field, use one of {}", word, Field::NAMES.join(", ")))?;
+        let op = match self.next() {
+            Some(Token::Op(op)) => op,
+            Some(token) => return Err(format!("expected an operator after {}, got {}", word, describe(&token))),
+            None => return Err(format!("{} needs an operator and a value", word)),
+        };
+        let literal = match self.next() {
+            Some(Token::Word(text) | Token::Quoted(text)) => text,
+            Some(token) => return Err(format!("expected a value after {}, got {}", word, describe(&token))),
+            None => return Err(format!("{} needs a value to compare with", word)),
+        };
+
+        let value = match field {
+            Field::SizeMb => Value::Number(
+                literal.parse().map_err(|_| format!("size_mb compares with a number, not '{}'", literal))?,
+            ),
+            Field::Ext if matches!(op, Op::Eq | Op::Ne) => Value::Text(normalize_extension(&literal)),
+            Field::Ext => return Err("ext only works with == and !=".to_string()),
+            Field::LastModified | Field::LastAccessed => Value::Date(
+                NaiveDate::parse_from_str(&literal, "%Y-%m-%d")
+                    .map_err(|_| format!("{} compares with a date like 2024-01-31, not '{}'", word, literal))?,
+            ),
+        };
+        Ok(Node::Compare(field, op, value))
+    }
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Word(word) => format!("'{}'", word),
+        Token::Quoted(text) => format!("'{}'", text),
+        Token::Op(_) => "an operator".to_string(),
+        Token::Open => "'('".to_string(),
+        Token::Close => "')'".to_string(),
+    }
+}
+
+fn compare_date(time: Option<DateTime<Utc>>, op: Op, date: NaiveDate) -> bool {
+    // a file without the timestamp can't be said to be before or after anything
+    time.is_some_and(|time| op.holds(display_date(time).cmp(&date)))
+}
+
+impl FilterExpr {
+    /// Parses `input`, the error says what's wrong and where in words.
+    pub fn parse(input: &str) -> Result<FilterExpr, String> {
+        let mut parser = Parser { tokens: tokenize(input)?, pos: 0, depth: 0 };
+        let node = parser.or()?;
+        match parser.next() {
+            None => Ok(FilterExpr(node)),
+            Some(token) => Err(format!("unexpected {} after a complete expression, missing AND or OR?", describe(&token))),
+        }
+    }
+
+    /// `size_mb` is read in `units`, the MB or MiB the sizes are being shown in.
+    pub fn matches(&self, file: &FileInfo, units: Units) -> bool {
+        self.0.matches(file, units)
+    }
+}
+
+impl StorageAnalyzer {
+    /// Every cached file on `drive` that `expr` matches, biggest first, e.g.
+    /// `size_mb > 500 AND (ext == 'mp4' OR ext == 'mkv')`.
+    ///
+    /// Fails with [`AnalyzerError::InvalidArgument`] when `expr` doesn't parse.
+    pub fn filter_cache(&mut self, drive: &str, expr: &str) -> Result<Vec<FileInfo>> {
+        let filter = FilterExpr::parse(expr).map_err(AnalyzerError::InvalidArgument)?;
+        let drive = normalize_path(drive);
+        self.collect_and_cache_files(&drive)?;
+        let mut files: Vec<FileInfo> = self.scanned_files(&drive)?.iter().filter(|file| filter.matches(file, self.units())).cloned().collect();
+        files.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes).then_with(|| a.full_path.cmp(&b.full_path)));
+        Ok(files)
+    }
+
+    pub fn print_filter_cache(&mut self, drive: &str, expr: &str) -> Result<()> {
+        let files = self.filter_cache(drive, expr)?;
+        self.set_last_listing(files.iter().take(50).map(|f| f.full_path.clone()).collect());
+        Ok(printers::files_matching(&mut self.out(), self.units(), expr, &files)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::test_util;
+    use chrono::TimeZone;
+
+    fn file(path: &str, size_mb: u64, modified: Option<(i32, u32, u32)>) -> FileInfo {
+        FileInfo {
+            last_modified: modified.map(|(y, m, d)| Utc.with_ymd_and_hms(y, m, d, 12, 0, 0).unwrap()),
+            ..test_util::file(path, size_mb * 1024 * 1024)
+        }
+    }
+
+    fn matching<'a>(expr: &str, files: &'a [FileInfo]) -> Vec<&'a str> {
+        let filter = FilterExpr::parse(expr).unwrap();
+        files.iter().filter(|f| filter.matches(f, Units::Binary)).map(|f| f.full_path.to_str().unwrap()).collect()
+    }
+
+    #[test]
+    fn comparisons_combine_with_and_or_not() {
+        let files = [
+            file("C:/movie.MP4", 900, Some((2023, 5, 1))),
+            file("C:/clip.mp4", 100, Some((2024, 6, 1))),
+            file("C:/disk.iso", 700, None),
+        ];
+        assert_eq!(matching("size_mb > 500 AND ext == 'mp4'", &files), ["C:/movie.MP4"]);
+        assert_eq!(matching("ext == mp4 or SIZE_MB >= 700", &files), ["C:/movie.MP4", "C:/clip.mp4", "C:/disk.iso"]);
+        assert_eq!(matching("NOT ext == \".mp4\"", &files), ["C:/disk.iso"]);
+        assert_eq!(matching("size_mb < 800 AND (ext != 'iso' OR last_modified < '2000-01-01')", &files), ["C:/clip.mp4"]);
+        // no timestamp never matches a date, either way round
+        assert_eq!(matching("last_modified < 2024-01-01 or last_modified >= 2024-01-01", &files), ["C:/movie.MP4", "C:/clip.mp4"]);
+    }
+
+    #[test]
+    fn broken_expressions_say_what_is_wrong() {
+        for (expr, complaint) in [
+            ("size > 5", "isn't a field"),
+            ("size_mb > big", "compares with a number"),
+            ("ext > 'mp4'", "only works with == and !="),
+            ("last_modified < 31/01/2024", "like 2024-01-31"),
+            ("(size_mb > 5", "never closed"),
+            ("size_mb > 5 ext == 'a'", "missing AND or OR"),
+            ("size_mb = 5", "isn't an operator"),
+            ("", "ends where a field"),
+            ("ext == 'mp4", "never closed"),
+            ("ext == \"mp4' OR size_mb > 5", "never closed"),
+        ] {
+            let err = FilterExpr::parse(expr).unwrap_err();
+            assert!(err.contains(complaint), "{}: {}", expr, err);
+        }
+    }
+
+    #[test]
+    fn nesting_is_capped() {
+        let nested = |depth: usize| format!("{}size_mb > 5{}", "(".repeat(depth), ")".repeat(depth));
+        assert!(FilterExpr::parse(&nested(FILTER_MAX_DEPTH)).is_ok());
+        assert!(FilterExpr::parse(&nested(FILTER_MAX_DEPTH + 1)).unwrap_err().contains("nested"));
+        assert!(FilterExpr::parse(&format!("{}size_mb > 5", "NOT ".repeat(100_000))).unwrap_err().contains("nested"));
+    }
+
+    #[test]
+    fn long_chains_stay_flat() {
+        let files = [file("C:/a.mp4", 900, None), file("C:/b.iso", 10, None)];
+        let and_chain = vec!["size_mb > 5"; 100_000].join(" AND ");
+        assert_eq!(matching(&and_chain, &files), ["C:/a.mp4", "C:/b.iso"]);
+        let or_chain = vec!["size_mb > 500"; 100_000].join(" OR ");
+        assert_eq!(matching(&format!("{} OR ext == iso", or_chain), &files), ["C:/a.mp4", "C:/b.iso"]);
+    }
+}
@@ -0,0 +1,84 @@
+use super::{
+    error::Result,
+    printers,
+    storage::StorageAnalyzer,
+    utils::normalize_path,
+};
+use log::Level;
+use std::{fs, path::Path};
+use walkdir::WalkDir;
+
+fn display_path(path: &Path) -> String {
+    normalize_path(&path.to_string_lossy())
+}
+
+// junctions are reparse points like symlinks, a loop made of them is just as endless
+#[cfg(windows)]
+fn is_reparse_point(path: &Path) -> bool {
+    use std::os::windows::fs::MetadataExt;
+    use winapi::um::winnt::FILE_ATTRIBUTE_REPARSE_POINT;
+    fs::symlink_metadata(path)
+        .map(|m| m.file_attributes() & FILE_ATTRIBUTE_REPARSE_POINT != 0)
+        .unwrap_or(false)
+}
+
+impl StorageAnalyzer {
+    /// Symlinks and junctions under `drive` that lead back into a folder above them, as
+    /// "link -> target". Walking into one of them never ends, so every scan keeps out of them.
+    ///
+    /// Outside of Windows a folder reached a second time through any link counts as well.
+    pub fn detect_symlink_cycles(&self, drive: &str) -> Result<Vec<String>> {
+        let mut cycles = Vec::new();
+        #[cfg(unix)]
+        let mut visited = std::collections::HashSet::new();
+
+        for root in self.scan_roots(&normalize_path(drive)) {
+            let mut walker = WalkDir::new(&root)
+                .follow_links(true)
+                .into_iter()
+                .filter_entry(|e| !self.is_system_dir(e) && !self.is_skipped(e.path()));
+            while let Some(entry) = walker.next() {
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    // walkdir stops at links to one of the folders it's in and says which
+                    Err(e) => {
+                        if let (Some(path), Some(ancestor)) = (e.path(), e.loop_ancestor()) {
+                            cycles.push(format!("{} -> {}", display_path(path), display_path(ancestor)));
+                        }
+                        continue;
+                    }
+                };
+                if !entry.file_type().is_dir() {
+                    continue;
+                }
+
+                #[cfg(windows)]
+                if is_reparse_point(entry.path())
+                    && let (Ok(target), Some(Ok(parent))) =
+                        (fs::canonicalize(entry.path()), entry.path().parent().map(fs::canonicalize))
+                    && parent.starts_with(&target) {
+                    cycles.push(format!("{} -> {}", display_path(entry.path()), display_path(&target)));
+                    walker.skip_current_dir();
+                }
+
+                #[cfg(unix)]
+                {
+                    use std::os::unix::fs::MetadataExt;
+                    if let Ok(meta) = entry.metadata()
+                        && !visited.insert((meta.dev(), meta.ino())) {
+                        let target = fs::canonicalize(entry.path()).unwrap_or_else(|_| entry.path().to_path_buf());
+                        cycles.push(format!("{} -> {}", display_path(entry.path()), display_path(&target)));
+                        walker.skip_current_dir();
+                    }
+                }
+            }
+        }
+        Ok(cycles)
+    }
+
+    pub fn print_symlink_cycles(&self, drive: &str) -> Result<()> {
+        self.status(Level::Info, &format!("Checking {}, this walks the whole drive and follows every link..", drive));
+        let cycles = self.detect_symlink_cycles(drive)?;
+        Ok(printers::symlink_cycles(&mut self.out(), drive, &cycles)?)
+    }
+}
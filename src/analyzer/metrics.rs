@@ -0,0 +1,178 @@
+use super::{
+    constants::METRICS_CLIENT_TIMEOUT_SECS,
+    platform::{DrivePlatform, WinApiDrives},
+    types::*
+};
+use std::{
+    collections::HashMap,
+    fmt::Write as _,
+    io::{self, BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    sync::{Arc, Mutex, MutexGuard, PoisonError, RwLock},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+// what the last scan of a drive found, kept around for the metrics endpoint
+#[derive(Debug, Clone)]
+pub struct ScanMetrics {
+    pub file_count: usize,
+    pub scanned_at: SystemTime,
+    pub category_bytes: HashMap<FileCategory, u64>,
+}
+
+// shared between the analyzer (writer) and the metrics thread (reader)
+pub type MetricsRegistry = Arc<Mutex<HashMap<String, ScanMetrics>>>;
+
+// the analyzer's drive list as the metrics thread sees it, republished whenever the drives are listed again
+pub type SharedDrives = Arc<RwLock<Vec<String>>>;
+
+pub fn read_drives(drives: &SharedDrives) -> Vec<String> {
+    drives.read().unwrap_or_else(PoisonError::into_inner).clone()
+}
+
+// a reader that panicked can't have left the map half written, so a poisoned lock is still usable
+// and a scan never stops halfway through being cached because of it
+pub fn lock_registry(registry: &MetricsRegistry) -> MutexGuard<'_, HashMap<String, ScanMetrics>> {
+    registry.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl ScanMetrics {
+    pub fn from_files(files: &[FileInfo]) -> Self {
+        let mut category_bytes: HashMap<FileCategory, u64> = HashMap::new();
+        for file in files {
+            *category_bytes.entry(FileCategory::of_path(&file.full_path)).or_default() += file.size_bytes;
+        }
+
+        ScanMetrics {
+            file_count: files.len(),
+            scanned_at: SystemTime::now(),
+            category_bytes,
+        }
+    }
+}
+
+// renders the registry in the Prometheus text exposition format
+// metric names and labels are part of the public contract, don't rename them
+pub fn render_metrics(drives: &[String], registry: &MetricsRegistry) -> String {
+    let scans = lock_registry(registry).clone();
+
+    // report every fixed drive plus anything that was scanned by path
+    let mut all_drives: Vec<String> = drives.to_vec();
+    for drive in scans.keys() {
+        if !all_drives.contains(drive) {
+            all_drives.push(drive.clone());
+        }
+    }
+    all_drives.sort();
+
+    let mut out = String::new();
+
+    out.push_str("# HELP rusty_analyser_drive_total_bytes Total size of the drive in bytes.\n");
+    out.push_str("# TYPE rusty_analyser_drive_total_bytes gauge\n");
+    let spaces: Vec<_> = all_drives
+        .iter()
+        .map(|drive| (drive, WinApiDrives.drive_space(drive).ok()))
+        .collect();
+    for (drive, space) in &spaces {
+        if let Some((total, _)) = space {
+            let _ = writeln!(out, "rusty_analyser_drive_total_bytes{{drive=\"{}\"}} {}", escape_label(drive), total);
+        }
+    }
+
+    out.push_str("# HELP rusty_analyser_drive_free_bytes Free space on the drive in bytes.\n");
+    out.push_str("# TYPE rusty_analyser_drive_free_bytes gauge\n");
+    for (drive, space) in &spaces {
+        if let Some((_, free)) = space {
+            let _ = writeln!(out, "rusty_analyser_drive_free_bytes{{drive=\"{}\"}} {}", escape_label(drive), free);
+        }
+    }
+
+    out.push_str("# HELP rusty_analyser_scanned_files Number of files found by the last scan.\n");
+    out.push_str("# TYPE rusty_analyser_scanned_files gauge\n");
+    for drive in &all_drives {
+        if let Some(scan) = scans.get(drive) {
+            let _ = writeln!(out, "rusty_analyser_scanned_files{{drive=\"{}\"}} {}", escape_label(drive), scan.file_count);
+        }
+    }
+
+    out.push_str("# HELP rusty_analyser_scan_age_seconds Seconds since the last scan finished.\n");
+    out.push_str("# TYPE rusty_analyser_scan_age_seconds gauge\n");
+    for drive in &all_drives {
+        if let Some(scan) = scans.get(drive) {
+            let age = scan.scanned_at.elapsed().map(|d| d.as_secs()).unwrap_or(0);
+            let _ = writeln!(out, "rusty_analyser_scan_age_seconds{{drive=\"{}\"}} {}", escape_label(drive), age);
+        }
+    }
+
+    out.push_str("# HELP rusty_analyser_category_bytes Bytes per file category from the last scan.\n");
+    out.push_str("# TYPE rusty_analyser_category_bytes gauge\n");
+    for drive in &all_drives {
+        if let Some(scan) = scans.get(drive) {
+            for category in FileCategory::ALL {
+                let bytes = scan.category_bytes.get(&category).copied().unwrap_or(0);
+                let _ = writeln!(
+                    out,
+                    "rusty_analyser_category_bytes{{drive=\"{}\",category=\"{}\"}} {}",
+                    escape_label(drive), category.as_str(), bytes
+                );
+            }
+        }
+    }
+
+    out
+}
+
+// starts the metrics listener on a background thread
+// fails right away if the address can't be bound so the user finds out at startup
+pub fn serve_metrics(addr: &str, drives: SharedDrives, registry: MetricsRegistry) -> io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    thread::spawn(move || {
+        for stream in listener.incoming().filter_map(Result::ok) {
+            // each client gets its own thread and a deadline, so a slow or silent one
+            // can't hold up the next scrape, and a broken one can't take the endpoint down
+            let timeout = Some(Duration::from_secs(METRICS_CLIENT_TIMEOUT_SECS));
+            if stream.set_read_timeout(timeout).and_then(|_| stream.set_write_timeout(timeout)).is_err() {
+                continue;
+            }
+            let (drives, registry) = (Arc::clone(&drives), Arc::clone(&registry));
+            thread::spawn(move || {
+                let _ = handle_request(stream, &drives, &registry);
+            });
+        }
+    });
+    Ok(())
+}
+
+fn handle_request(stream: TcpStream, drives: &SharedDrives, registry: &MetricsRegistry) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+
+    // drain the headers, we don't care about any of them
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (status, body) = match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/metrics")) => ("200 OK", render_metrics(&read_drives(drives), registry)),
+        (Some("GET"), _) => ("404 Not Found", "not found, try /metrics\n".to_string()),
+        _ => ("405 Method Not Allowed", "only GET is supported\n".to_string()),
+    };
+
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
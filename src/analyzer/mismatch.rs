@@ -0,0 +1,55 @@
+use super::{
+    error::{AnalyzerError, Result},
+    constants::*,
+    printers,
+    storage::StorageAnalyzer,
+    types::*,
+    utils::normalize_extension
+};
+use rayon::prelude::*;
+use std::{
+    fs::File,
+    io::{self, Read},
+    path::Path,
+};
+
+// the type the first bytes of the file say it is, None when there's no header infer knows
+fn sniff_type(path: &Path) -> io::Result<Option<infer::Type>> {
+    let mut header = Vec::new();
+    File::open(path)?.take(MISMATCH_HEADER_BYTES).read_to_end(&mut header)?;
+    Ok(infer::get(&header))
+}
+
+fn same_format(declared: &str, actual: &str) -> bool {
+    declared == actual
+        || MISMATCH_ALIASES.iter().any(|(base, aliases)| {
+            let matches = |ext: &str| ext == *base || aliases.contains(&ext);
+            matches(declared) && matches(actual)
+        })
+}
+
+impl StorageAnalyzer {
+    // the biggest files whose header says something other than their extension, with what it says
+    // files without an extension or without a header infer recognizes are left out
+    pub fn find_extension_mismatches(&mut self, drive: &str) -> Result<Vec<(FileInfo, String)>> {
+        let files = self.get_largest_files(drive, SortOrder::Descending)?;
+        let mismatches = files
+            .into_par_iter()
+            .take(MISMATCH_SAMPLE_FILES)
+            .filter_map(|file| {
+                let declared = normalize_extension(&file.full_path.extension()?.to_string_lossy());
+                let actual = sniff_type(&file.full_path).ok()??;
+                (!same_format(&declared, actual.extension())).then(|| (file, actual.extension().to_string()))
+            })
+            .collect();
+        Ok(mismatches)
+    }
+
+    pub fn print_extension_mismatches(&mut self, drive: &str) -> Result<()> {
+        let mismatches = self.find_extension_mismatches(drive)?;
+        if !mismatches.is_empty() {
+            self.set_last_listing(mismatches.iter().map(|(file, _)| file.full_path.clone()).collect());
+        }
+        Ok(printers::extension_mismatches(&mut self.out(), self.units(), drive, MISMATCH_SAMPLE_FILES, &mismatches)?)
+    }
+}
@@ -1,10 +1,42 @@
 #![allow(unused_imports)]
+pub mod acl;
+pub mod batch;
+pub mod builder;
+pub mod cleanup;
+pub mod compression;
+pub mod config;
 pub mod constants;
+pub mod duplicates;
+pub mod email;
+pub mod error;
+pub mod exclude;
+pub mod file_ops;
+pub mod filter_eval;
+pub mod links;
+pub mod metrics;
+pub mod mismatch;
+pub mod notifications;
+pub mod output;
+pub mod perf_counters;
+pub mod platform;
+pub mod printers;
+pub mod progress;
+pub mod quota;
+pub mod report;
+pub mod snapshot;
 pub mod storage;
+mod style;
+#[cfg(test)]
+mod test_util;
+pub mod timeline;
 pub mod types;
+pub mod usage_log;
 pub mod utils;
 
 // re-export commonly used items
+pub use builder::StorageAnalyzerBuilder;
+pub use config::{Config, ScanOptions};
+pub use error::AnalyzerError;
 pub use storage::StorageAnalyzer;
 pub use types::*;
 pub use constants::*;
\ No newline at end of file
@@ -0,0 +1,26 @@
+use super::storage::StorageAnalyzer;
+
+impl StorageAnalyzer {
+    // pops up a desktop notification, failures are only reported
+    // since a missing notification shouldn't break whatever triggered it
+    pub fn notify_on_complete(&self, title: &str, message: &str) {
+        #[cfg(target_os = "windows")]
+        let result = winrt_notification::Toast::new(winrt_notification::Toast::POWERSHELL_APP_ID)
+            .title(title)
+            .text1(message)
+            .show()
+            .map_err(|e| e.to_string());
+
+        #[cfg(not(target_os = "windows"))]
+        let result = notify_rust::Notification::new()
+            .summary(title)
+            .body(message)
+            .show()
+            .map(|_| ())
+            .map_err(|e| e.to_string());
+
+        if let Err(e) = result {
+            log::warn!("Couldn't show notification: {}", e);
+        }
+    }
+}
@@ -0,0 +1,120 @@
+// where the analyzer's reports go: always stdout, and while a capture is running a bounded copy
+// in memory as well, which is what the shell's copy-last puts on the clipboard
+use super::constants::CAPTURE_LIMIT_BYTES;
+use std::{
+    io::{self, Write},
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+#[derive(Debug)]
+pub struct OutputCapture {
+    // None while nothing is being captured
+    buffer: Mutex<Option<Captured>>,
+    limit: usize,
+}
+
+impl Default for OutputCapture {
+    fn default() -> Self {
+        OutputCapture::with_limit(CAPTURE_LIMIT_BYTES)
+    }
+}
+
+#[derive(Debug, Default)]
+struct Captured {
+    bytes: Vec<u8>,
+    truncated: bool,
+}
+
+impl OutputCapture {
+    pub fn with_limit(limit: usize) -> Self {
+        OutputCapture { buffer: Mutex::new(None), limit }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Option<Captured>> {
+        self.buffer.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn start(&self) {
+        *self.lock() = Some(Captured::default());
+    }
+
+    /// Stops capturing and returns what was written since [`start`](Self::start), colors removed.
+    pub fn finish(&self) -> String {
+        let Some(captured) = self.lock().take() else {
+            return String::new();
+        };
+        let mut text = strip_ansi(&String::from_utf8_lossy(&captured.bytes));
+        if captured.truncated {
+            text.push_str("\n[output truncated]\n");
+        }
+        text
+    }
+}
+
+/// stdout, plus the running capture if there is one.
+pub struct Out<'a> {
+    capture: &'a OutputCapture,
+}
+
+impl<'a> Out<'a> {
+    pub fn new(capture: &'a OutputCapture) -> Self {
+        Out { capture }
+    }
+}
+
+impl Write for Out<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        io::stdout().write_all(buf)?;
+        if let Some(captured) = self.capture.lock().as_mut() {
+            let room = self.capture.limit.saturating_sub(captured.bytes.len());
+            captured.bytes.extend_from_slice(&buf[..buf.len().min(room)]);
+            captured.truncated |= buf.len() > room;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stdout().flush()
+    }
+}
+
+// drops terminal escape sequences like the color codes from `colored`
+fn strip_ansi(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\u{1b}' {
+            plain.push(c);
+            continue;
+        }
+        if chars.next() == Some('[') {
+            // parameters until the final byte, which is in @..~
+            for c in chars.by_ref() {
+                if ('@'..='~').contains(&c) {
+                    break;
+                }
+            }
+        }
+    }
+    plain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn captures_are_bounded_and_plain_text() {
+        let capture = OutputCapture::with_limit(24);
+        write!(Out::new(&capture), "not captured").unwrap();
+        assert_eq!(capture.finish(), "");
+
+        capture.start();
+        write!(Out::new(&capture), "\u{1b}[1;31mred\u{1b}[0m text").unwrap();
+        assert_eq!(capture.finish(), "red text");
+
+        capture.start();
+        write!(Out::new(&capture), "{}", "x".repeat(30)).unwrap();
+        assert_eq!(capture.finish(), format!("{}\n[output truncated]\n", "x".repeat(24)));
+    }
+}
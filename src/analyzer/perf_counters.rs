@@ -0,0 +1,155 @@
+// the performance data helper (PDH) query, collect, read cycle, so far only for top-disk-writers
+// none of the counters used here need elevation
+use super::{
+    constants::{DISK_WRITERS_SAMPLE_SECS, DISK_WRITERS_SHOWN},
+    error::Result,
+    printers,
+    storage::StorageAnalyzer,
+};
+use log::Level;
+use std::{
+    collections::HashMap,
+    ffi::OsStr,
+    io::Error,
+    mem,
+    os::windows::ffi::OsStrExt,
+    ptr,
+    thread,
+    time::Duration,
+};
+use winapi::um::pdh::{
+    PdhAddEnglishCounterW, PdhCloseQuery, PdhCollectQueryData, PdhGetFormattedCounterArrayW, PdhOpenQueryW,
+    PDH_FMT_COUNTERVALUE_ITEM_W, PDH_FMT_DOUBLE, PDH_FMT_NOCAP100, PDH_HCOUNTER, PDH_HQUERY,
+};
+
+// winapi doesn't have these
+const PDH_MORE_DATA: i32 = 0x800007D2_u32 as i32;
+const PDH_CSTATUS_VALID_DATA: u32 = 0;
+const PDH_CSTATUS_NEW_DATA: u32 = 1;
+
+// per process, PhysicalDisk counters only exist per disk and can't say who did the writing
+const PROCESS_WRITE_BYTES: &str = "\\Process(*)\\IO Write Bytes/sec";
+
+fn check(status: i32, what: &str) -> Result<()> {
+    match status {
+        0 => Ok(()),
+        _ => Err(Error::other(format!("{} failed with PDH status {:#010x}", what, status as u32)).into()),
+    }
+}
+
+// closes the query however the sampling ends
+struct Query(PDH_HQUERY);
+
+impl Drop for Query {
+    fn drop(&mut self) {
+        unsafe { PdhCloseQuery(self.0) };
+    }
+}
+
+// every instance of a wildcard counter as (instance name, value) from the last two collections
+fn counter_values(counter: PDH_HCOUNTER) -> Result<Vec<(String, f64)>> {
+    let format = PDH_FMT_DOUBLE | PDH_FMT_NOCAP100;
+    let (mut size, mut count) = (0u32, 0u32);
+    // the names are written into the same buffer after the items, so it's sized in bytes
+    let item_size = mem::size_of::<PDH_FMT_COUNTERVALUE_ITEM_W>();
+    let mut items: Vec<PDH_FMT_COUNTERVALUE_ITEM_W> = Vec::new();
+    // processes can start between asking for the size and reading, so it's asked again until it fits
+    loop {
+        let buffer = if items.capacity() == 0 { ptr::null_mut() } else { items.as_mut_ptr() };
+        let status = unsafe { PdhGetFormattedCounterArrayW(counter, format, &mut size, &mut count, buffer) };
+        if status != PDH_MORE_DATA {
+            check(status, "PdhGetFormattedCounterArrayW")?;
+            break;
+        }
+        items = Vec::with_capacity((size as usize).div_ceil(item_size));
+    }
+    if items.capacity() == 0 {
+        return Ok(Vec::new());
+    }
+    unsafe { items.set_len(count as usize) };
+
+    Ok(items
+        .iter()
+        .filter(|item| matches!(item.FmtValue.CStatus, PDH_CSTATUS_VALID_DATA | PDH_CSTATUS_NEW_DATA))
+        .map(|item| {
+            let name = unsafe {
+                let len = (0..).take_while(|&i| *item.szName.add(i) != 0).count();
+                String::from_utf16_lossy(std::slice::from_raw_parts(item.szName, len))
+            };
+            (name, unsafe { *item.FmtValue.u.doubleValue() })
+        })
+        .collect())
+}
+
+// "chrome#3" is the fourth chrome.exe running, they're added up under one name
+fn process_name(instance: &str) -> &str {
+    match instance.rsplit_once('#') {
+        Some((name, n)) if n.chars().all(|c| c.is_ascii_digit()) => name,
+        _ => instance,
+    }
+}
+
+// (process, bytes written per second) from the raw counter instances, most first
+fn rank_writers(instances: Vec<(String, f64)>) -> Vec<(String, f64)> {
+    let mut per_process: HashMap<String, f64> = HashMap::new();
+    for (instance, rate) in instances {
+        // _Total is every process together and Idle never writes anything
+        if instance == "_Total" || instance == "Idle" {
+            continue;
+        }
+        *per_process.entry(process_name(&instance).to_string()).or_default() += rate;
+    }
+    let mut writers: Vec<(String, f64)> = per_process.into_iter().filter(|(_, rate)| *rate > 0.0).collect();
+    writers.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    writers
+}
+
+/// Bytes each process wrote per second on average over `sample`, most first.
+///
+/// Windows counts every write a process makes, to any drive, the network or a pipe, so this
+/// says who is busy writing rather than exactly where it lands.
+pub fn process_write_rates(sample: Duration) -> Result<Vec<(String, f64)>> {
+    let mut query = ptr::null_mut();
+    check(unsafe { PdhOpenQueryW(ptr::null(), 0, &mut query) }, "PdhOpenQueryW")?;
+    let query = Query(query);
+
+    let path: Vec<u16> = OsStr::new(PROCESS_WRITE_BYTES).encode_wide().chain(Some(0)).collect();
+    let mut counter = ptr::null_mut();
+    check(unsafe { PdhAddEnglishCounterW(query.0, path.as_ptr(), 0, &mut counter) }, "PdhAddEnglishCounterW")?;
+
+    // a rate needs two samples, the value is the average between them
+    check(unsafe { PdhCollectQueryData(query.0) }, "PdhCollectQueryData")?;
+    thread::sleep(sample);
+    check(unsafe { PdhCollectQueryData(query.0) }, "PdhCollectQueryData")?;
+    Ok(rank_writers(counter_values(counter)?))
+}
+
+impl StorageAnalyzer {
+    pub fn print_top_disk_writers(&self) -> Result<()> {
+        self.status(Level::Info, &format!("Watching write activity for {}s..", DISK_WRITERS_SAMPLE_SECS));
+        let writers = process_write_rates(Duration::from_secs(DISK_WRITERS_SAMPLE_SECS))?;
+        Ok(printers::top_disk_writers(&mut self.out(), self.units(), &writers, DISK_WRITERS_SAMPLE_SECS, DISK_WRITERS_SHOWN)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn instances_of_one_program_are_added_up() {
+        let instances = vec![
+            ("_Total".to_string(), 900.0),
+            ("Idle".to_string(), 0.0),
+            ("chrome".to_string(), 100.0),
+            ("chrome#1".to_string(), 50.0),
+            ("svchost#12".to_string(), 300.0),
+            ("notepad".to_string(), 0.0),
+            ("my#app".to_string(), 10.0),
+        ];
+        assert_eq!(
+            rank_writers(instances),
+            [("svchost".to_string(), 300.0), ("chrome".to_string(), 150.0), ("my#app".to_string(), 10.0)]
+        );
+    }
+}
@@ -0,0 +1,285 @@
+// where drives and their free space come from, StorageAnalyzer only talks to the OS through
+// this so everything built on top of it can be tested with made up drives
+use super::{
+    constants::{VOLUME_LABEL_INVALID_CHARS, VOLUME_LABEL_MAX_CHARS},
+    error::{AnalyzerError, Result},
+};
+use std::{
+    ffi::{OsStr, OsString},
+    io::{Error, ErrorKind},
+    os::windows::ffi::{OsStrExt, OsStringExt},
+};
+use winapi::um::{
+    fileapi::{GetDiskFreeSpaceExW, GetDriveTypeW, GetLogicalDriveStringsW, GetVolumeInformationW},
+    winbase::{SetVolumeLabelW, DRIVE_FIXED, DRIVE_REMOVABLE},
+};
+
+/// What kind of device a drive letter belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DriveKind {
+    Fixed,
+    Removable,
+    // network shares, CD drives, RAM disks, nothing the analyzer lists by itself
+    Other,
+}
+
+/// The OS calls [`StorageAnalyzer`](super::StorageAnalyzer) needs for drives.
+///
+/// [`WinApiDrives`] is what the analyzer uses unless it was made with
+/// [`StorageAnalyzer::with_platform`](super::StorageAnalyzer::with_platform).
+pub trait DrivePlatform: Send + Sync {
+    /// Every drive root the OS reports (e.g. `C:\`) and what kind it is.
+    fn list_drives(&self) -> Vec<(String, DriveKind)>;
+
+    /// Total and free bytes of `drive`.
+    fn drive_space(&self, drive: &str) -> Result<(u64, u64)>;
+
+    /// The volume label of `drive`, None when it has none or it can't be read.
+    fn drive_label(&self, _drive: &str) -> Option<String> {
+        None
+    }
+
+    /// Renames the volume of `drive`, an empty `label` removes it.
+    fn set_drive_label(&self, _drive: &str, _label: &str) -> Result<()> {
+        Err(Error::from(ErrorKind::Unsupported).into())
+    }
+}
+
+/// At most [`VOLUME_LABEL_MAX_CHARS`] characters, none of [`VOLUME_LABEL_INVALID_CHARS`] and no control characters.
+pub fn validate_volume_label(label: &str) -> Result<()> {
+    if label.chars().count() > VOLUME_LABEL_MAX_CHARS {
+        return Err(AnalyzerError::InvalidArgument(format!("a volume label can be at most {} characters", VOLUME_LABEL_MAX_CHARS)));
+    }
+    match label.chars().find(|c| VOLUME_LABEL_INVALID_CHARS.contains(c) || c.is_control()) {
+        Some(c) => Err(AnalyzerError::InvalidArgument(format!("{:?} can't be used in a volume label", c))),
+        None => Ok(()),
+    }
+}
+
+/// The real drives, through `GetLogicalDriveStringsW` and `GetDiskFreeSpaceExW`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WinApiDrives;
+
+impl DrivePlatform for WinApiDrives {
+    #[cfg(target_os = "windows")]
+    fn list_drives(&self) -> Vec<(String, DriveKind)> {
+        let mut buffer = [0u16; 256];
+        let len = unsafe { GetLogicalDriveStringsW(buffer.len() as u32, buffer.as_mut_ptr()) };
+
+        if len == 0 {
+            return Vec::new();
+        }
+
+        buffer[..len as usize]
+            .split(|&c| c == 0)
+            .filter(|slice| !slice.is_empty())
+            .map(|slice| {
+                let drive = OsString::from_wide(slice);
+                let kind = match unsafe { GetDriveTypeW(slice.as_ptr()) } {
+                    DRIVE_FIXED => DriveKind::Fixed,
+                    DRIVE_REMOVABLE => DriveKind::Removable,
+                    _ => DriveKind::Other,
+                };
+                (drive.to_string_lossy().into_owned(), kind)
+            })
+            .collect()
+    }
+
+    // placeholder for non-Windows platforms, no bloody idea what to do
+    #[cfg(not(target_os = "windows"))]
+    fn list_drives(&self) -> Vec<(String, DriveKind)> {
+        Vec::new()
+    }
+
+    fn drive_space(&self, drive: &str) -> Result<(u64, u64)> {
+        use winapi::um::winnt::ULARGE_INTEGER;
+        let mut free_bytes_available: ULARGE_INTEGER = unsafe { std::mem::zeroed() };
+        let mut total_bytes: ULARGE_INTEGER = unsafe { std::mem::zeroed() };
+        let mut total_free_bytes: ULARGE_INTEGER = unsafe { std::mem::zeroed() };
+
+        // convert drive path to wide string for Windows API
+        let wide_drive: Vec<u16> = OsStr::new(drive).encode_wide().chain(Some(0)).collect();
+
+        let success = unsafe {
+            GetDiskFreeSpaceExW(
+                wide_drive.as_ptr(),
+                &mut free_bytes_available as *mut _ as *mut _,
+                &mut total_bytes as *mut _ as *mut _,
+                &mut total_free_bytes as *mut _ as *mut _,
+            )
+        };
+
+        if success == 0 {
+            return Err(Error::last_os_error().into());
+        }
+
+        Ok(unsafe { (*total_bytes.QuadPart(), *total_free_bytes.QuadPart()) })
+    }
+
+    fn drive_label(&self, drive: &str) -> Option<String> {
+        let mut name = [0u16; 261];
+        let wide_drive: Vec<u16> = OsStr::new(drive).encode_wide().chain(Some(0)).collect();
+
+        let success = unsafe {
+            GetVolumeInformationW(
+                wide_drive.as_ptr(),
+                name.as_mut_ptr(),
+                name.len() as u32,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                0,
+            )
+        };
+
+        let len = name.iter().position(|&c| c == 0).unwrap_or(name.len());
+        (success != 0 && len > 0).then(|| OsString::from_wide(&name[..len]).to_string_lossy().into_owned())
+    }
+
+    fn set_drive_label(&self, drive: &str, label: &str) -> Result<()> {
+        let wide_drive: Vec<u16> = OsStr::new(drive).encode_wide().chain(Some(0)).collect();
+        let wide_label: Vec<u16> = OsStr::new(label).encode_wide().chain(Some(0)).collect();
+        // changing a label needs administrator rights on most drives
+        match unsafe { SetVolumeLabelW(wide_drive.as_ptr(), wide_label.as_ptr()) } {
+            0 => Err(Error::last_os_error().into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::{config::Config, error::AnalyzerError, metrics::read_drives, storage::StorageAnalyzer, types::DriveAnalysis};
+    use std::io::ErrorKind;
+
+    const GB: u64 = 1024 * 1024 * 1024;
+
+    // a drive as the OS would report it, None for space means the drive went away
+    type MockDrive = (&'static str, DriveKind, Option<(u64, u64)>);
+
+    struct MockDrives(Vec<MockDrive>);
+
+    const LABELS: &[(&str, &str)] = &[("C:/", "Windows")];
+
+    impl DrivePlatform for MockDrives {
+        fn list_drives(&self) -> Vec<(String, DriveKind)> {
+            self.0.iter().map(|(drive, kind, _)| (drive.to_string(), *kind)).collect()
+        }
+
+        fn drive_space(&self, drive: &str) -> Result<(u64, u64)> {
+            self.0
+                .iter()
+                .find(|(d, _, _)| d.replace('\\', "/") == drive.replace('\\', "/"))
+                .and_then(|(_, _, space)| *space)
+                .ok_or_else(|| Error::from(ErrorKind::NotFound).into())
+        }
+
+        fn drive_label(&self, drive: &str) -> Option<String> {
+            LABELS.iter().find(|(d, _)| *d == drive).map(|(_, label)| label.to_string())
+        }
+    }
+
+    fn analyzer(drives: Vec<MockDrive>) -> StorageAnalyzer {
+        StorageAnalyzer::with_platform(Config::default(), Box::new(MockDrives(drives)))
+    }
+
+    #[test]
+    fn volume_labels_are_checked_before_they_reach_the_os() {
+        assert!(validate_volume_label("Backup 2024").is_ok());
+        assert!(validate_volume_label("").is_ok());
+        assert!(validate_volume_label(&"x".repeat(32)).is_ok());
+        assert!(matches!(validate_volume_label(&"x".repeat(33)), Err(AnalyzerError::InvalidArgument(_))));
+        assert!(matches!(validate_volume_label("photos/old"), Err(AnalyzerError::InvalidArgument(_))));
+        assert!(matches!(validate_volume_label("tab\there"), Err(AnalyzerError::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn only_fixed_drives_are_listed() {
+        let analyzer = analyzer(vec![
+            ("C:\\", DriveKind::Fixed, Some((500 * GB, 100 * GB))),
+            ("D:\\", DriveKind::Fixed, Some((2000 * GB, 1500 * GB))),
+            ("E:\\", DriveKind::Removable, Some((32 * GB, 30 * GB))),
+            ("Z:\\", DriveKind::Other, None),
+        ]);
+        assert_eq!(analyzer.drives, ["C:/", "D:/"]);
+        assert_eq!(analyzer.list_removable_drives(), ["E:/"]);
+
+        // a plugged in USB stick can get the full report without joining `drives`
+        assert!(analyzer.check_known_drive("E:/").is_ok());
+        match analyzer.check_known_drive("Z:/") {
+            Err(AnalyzerError::DriveNotFound { valid, .. }) => assert_eq!(valid, ["C:/", "D:/", "E:/"]),
+            other => panic!("expected DriveNotFound, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn refreshing_picks_up_new_drives_and_forgets_gone_ones() {
+        let mut analyzer = analyzer(vec![
+            ("C:\\", DriveKind::Fixed, Some((500 * GB, 100 * GB))),
+            ("D:\\", DriveKind::Fixed, Some((2000 * GB, 1500 * GB))),
+            ("E:\\", DriveKind::Removable, Some((32 * GB, 30 * GB))),
+        ]);
+        // as if C: and a USB stick were there at startup, X: was unplugged since and D: plugged in
+        analyzer.drives = vec!["C:/".to_string(), "E:/".to_string(), "X:/".to_string()];
+        let metrics_drives = analyzer.metrics_drives();
+
+        let (added, removed) = analyzer.refresh_drives();
+        assert_eq!(added, ["D:/"]);
+        assert_eq!(removed, ["X:/"]);
+        assert_eq!(analyzer.drives, ["C:/", "E:/", "D:/"]);
+        // the metrics server holds the handle from startup and sees the new list through it
+        assert_eq!(read_drives(&metrics_drives), analyzer.drives);
+        assert_eq!(analyzer.refresh_drives(), (vec![], vec![]));
+    }
+
+    #[test]
+    fn space_is_worked_out_from_the_byte_counts() {
+        let analyzer = analyzer(vec![("C:\\", DriveKind::Fixed, Some((400 * GB, 100 * GB)))]);
+        let space = analyzer.get_drive_space("C:/").unwrap();
+        assert_eq!(space, DriveAnalysis {
+            drive: "C:/".to_string(),
+            label: Some("Windows".to_string()),
+            total_size: 400.0,
+            used_space: 300.0,
+            free_space: 100.0,
+            free_space_percent: 25.0,
+        });
+        assert_eq!(space.name(), "C:/ (Windows)");
+    }
+
+    #[test]
+    fn zero_size_and_implausible_drives_are_unavailable() {
+        let analyzer = analyzer(vec![
+            ("C:\\", DriveKind::Fixed, Some((0, 0))),
+            ("D:\\", DriveKind::Fixed, Some((10 * GB, 20 * GB))),
+            ("E:\\", DriveKind::Removable, Some((0, 0))),
+        ]);
+        for drive in ["C:/", "D:/", "E:/"] {
+            match analyzer.get_drive_space(drive) {
+                Err(AnalyzerError::DriveUnavailable(name)) => assert_eq!(name, drive),
+                other => panic!("expected DriveUnavailable for {}, got {:?}", drive, other),
+            }
+        }
+        // an empty card reader isn't worth a warning and is listed without a size
+        assert!(analyzer.disk_warnings().is_empty());
+        assert!(analyzer.list_all_drives().iter().all(|(_, _, space)| space.is_none()));
+        assert!(analyzer.print_drive_space_overview("E:/").is_ok());
+    }
+
+    #[test]
+    fn missing_drives_are_an_error_and_left_out_of_the_warnings() {
+        let analyzer = analyzer(vec![
+            ("C:\\", DriveKind::Fixed, Some((100 * GB, 3 * GB))),
+            ("D:\\", DriveKind::Fixed, None),
+        ]);
+        assert!(matches!(analyzer.get_drive_space("D:/"), Err(AnalyzerError::Io(_))));
+        let warnings = analyzer.disk_warnings();
+        assert_eq!(warnings.len(), 1);
+        // the drive and its label come back apart
+        assert_eq!(warnings[0].drive, "C:/");
+        assert_eq!(warnings[0].label.as_deref(), Some("Windows"));
+        assert!(warnings[0].critical);
+    }
+}
@@ -0,0 +1,1115 @@
+// console rendering for the storage reports, the data comes from StorageAnalyzer and goes
+// anywhere that implements Write, the print_* methods just point these at stdout
+use super::{
+    config::Units,
+    constants::DAY_FORMAT,
+    platform::DriveKind,
+    types::*,
+    utils::*,
+};
+use chrono::Datelike;
+use super::style::Colorize;
+use std::{
+    io::{self, ErrorKind, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// What [`drive_report`] needs besides the report itself.
+#[derive(Debug, Clone, Default)]
+pub struct ReportLayout {
+    pub top_n: usize,
+    pub recent_days: i64,
+    pub old_days: i64,
+    // the roots when it was a user-data scan
+    pub user_data_roots: Option<Vec<String>>,
+    pub follows_links: bool,
+    pub gap: Option<ScanGap>,
+    // "(scan limited to depth 6)" when the scan didn't go all the way down
+    pub depth_note: Option<String>,
+    // set when the scan couldn't read a fair number of entries
+    pub unreadable_note: Option<String>,
+    // set when the scan timeout cut the scan short
+    pub timeout_note: Option<String>,
+}
+
+/// Every `analyze_drive` section, in the order the shell has always shown them.
+pub fn drive_report(out: &mut impl Write, units: Units, drive: &str, report: &DriveReport, layout: &ReportLayout) -> io::Result<()> {
+    let options = ListOptions::default();
+    let top_n = layout.top_n;
+    drive_header(out, drive, layout.user_data_roots.as_deref(), layout.follows_links)?;
+    drive_space(out, units, &report.space, layout.gap.as_ref())?;
+    folders(out, units, &report.folders[..report.folders.len().min(top_n)], &options, top_n)?;
+    type_distribution(out, units, &report.distribution, top_n, layout.recent_days)?;
+    top_files(out, units, "Largest Files", &options.apply(report.largest.clone(), top_n), &options, top_n)?;
+    let recent = format!("Recent Large Files (last {} days)", layout.recent_days);
+    top_files(out, units, &recent, &options.apply(report.recent.clone(), top_n), &options, top_n)?;
+    let old = format!("Old Large Files (>{} days old)", layout.old_days);
+    top_files(out, units, &old, &options.apply(report.old.clone(), top_n), &options, top_n)?;
+    if let Some(note) = &layout.depth_note {
+        writeln!(out, "\nEverything above comes from a depth-limited scan {}", note)?;
+    }
+    if let Some(note) = &layout.unreadable_note {
+        writeln!(out, "\nNote: {}", note)?;
+    }
+    if let Some(note) = &layout.timeout_note {
+        writeln!(out, "\nNote: {}", note)?;
+    }
+    Ok(())
+}
+
+pub fn drive_header(out: &mut impl Write, drive: &str, user_data_roots: Option<&[String]>, follows_links: bool) -> io::Result<()> {
+    writeln!(out, "\n=== Storage Distribution Analysis ===")?;
+    writeln!(out, "Date: {}", format_time(chrono::Utc::now()))?;
+    writeln!(out, "Drive: {}", drive)?;
+    if let Some(roots) = user_data_roots {
+        writeln!(out, "Scope: user-data scan ({})", roots.join(", "))?;
+    }
+    if follows_links {
+        writeln!(out, "Links: followed, sizes can overlap with the drives they point to")?;
+    }
+    Ok(())
+}
+
+pub fn drive_space(out: &mut impl Write, units: Units, space: &DriveAnalysis, gap: Option<&ScanGap>) -> io::Result<()> {
+    if space.drive.is_empty() {
+        writeln!(out, "\n--- Drive Space Overview ---")?;
+    } else {
+        writeln!(out, "\n--- Drive Space Overview: {} ---", space.name())?;
+    }
+    writeln!(out, "{}", space.shown(units))?;
+    match gap {
+        Some(gap) => scan_gap(out, units, gap),
+        None => Ok(()),
+    }
+}
+
+// a drive whose size came back as zero or less than its free space, instead of a NaN% overview
+pub fn drive_unavailable(out: &mut impl Write, drive: &str, label: Option<&str>) -> io::Result<()> {
+    match label {
+        Some(label) => writeln!(out, "\n--- Drive Space Overview: {} ({}) ---", drive, label)?,
+        None => writeln!(out, "\n--- Drive Space Overview: {} ---", drive)?,
+    }
+    writeln!(out, "Space unknown, the drive reports no usable size")?;
+    writeln!(out, "  card readers and optical drives do this with nothing inserted, otherwise the volume may be failing")
+}
+
+// how much of the used space the scan didn't see and the likely reasons
+pub fn scan_gap(out: &mut impl Write, units: Units, gap: &ScanGap) -> io::Result<()> {
+    let scanned_gb = bytes_to_gb(units, gap.scanned_bytes);
+    writeln!(out, "Scanned: {} in {} files", format_gb(units, scanned_gb), gap.file_count)?;
+    let gap_gb = gap.used_gb - scanned_gb;
+    if gap_gb <= 0.0 {
+        return Ok(());
+    }
+
+    writeln!(out, "Not seen by the scan: {}", format_gb(units, gap_gb))?;
+    if gap.user_data {
+        return writeln!(out, "  this was a user-data scan, everything outside the user folders was left out");
+    }
+    if !gap.system_dirs.is_empty() {
+        writeln!(out, "  part of it is in the skipped system dirs ({}), add --include-system to count them", gap.system_dirs.join(", "))?;
+    }
+    if gap.excluded > 0 {
+        writeln!(out, "  {} excluded entries", compact_count(gap.excluded))?;
+    }
+    writeln!(out, "  the rest is usually system files like pagefile.sys and hiberfil.sys, shadow copies and folders without access")
+}
+
+pub fn file_list(out: &mut impl Write, units: Units, files: &[FileInfo]) -> io::Result<()> {
+    for (i, file) in files.iter().enumerate() {
+        writeln!(out, "\n[{}] {}", i + 1, file.shown(units))?;
+    }
+    Ok(())
+}
+
+// `files` already went through ListOptions::apply, the header says what it did
+pub fn top_files(out: &mut impl Write, units: Units, title: &str, files: &[FileInfo], options: &ListOptions, top_n: usize) -> io::Result<()> {
+    if options.tsv {
+        writeln!(out, "path\tsize_bytes\tlast_modified\tlast_accessed")?;
+        for file in files {
+            writeln!(
+                out,
+                "{}\t{}\t{}\t{}",
+                file.full_path.display(),
+                file.size_bytes,
+                file.last_modified.map(|t| t.to_rfc3339()).unwrap_or_default(),
+                file.last_accessed.map(|t| t.to_rfc3339()).unwrap_or_default()
+            )?;
+        }
+        return Ok(());
+    }
+
+    writeln!(out, "\n--- {} ---", listing_header(units, title, options))?;
+    if let Some(min_bytes) = options.min_bytes
+        && files.len() < top_n
+        && options.offset == 0 {
+        writeln!(out, "only {} files exceed {}", files.len(), human_readable_size(units, min_bytes))?;
+    }
+    file_list(out, units, files)
+}
+
+// one page of a longer ranking, `offset` is where `page` starts in the `total` matches
+pub fn ranked_page(out: &mut impl Write, units: Units, title: &str, page: &[FileInfo], offset: usize, total: usize, options: &ListOptions) -> io::Result<()> {
+    writeln!(out, "\n--- {} ---", listing_header(units, title, options))?;
+    if page.is_empty() {
+        return writeln!(out, "nothing past the first {} of {} matches", offset.min(total), total);
+    }
+    file_list(out, units, page)?;
+    let shown = offset + page.len();
+    if shown < total {
+        writeln!(out, "\n{}-{} of {}, `more` shows the next {}", offset + 1, shown, total, page.len())
+    } else {
+        writeln!(out, "\n{}-{} of {}, that's all of them", offset + 1, shown, total)
+    }
+}
+
+// the title plus whatever the filters and the order narrowed it down to
+fn listing_header(units: Units, title: &str, options: &ListOptions) -> String {
+    let mut header = title.to_string();
+    if let Some(ext) = &options.ext {
+        header.push_str(&format!(", .{} only", ext));
+    }
+    if let Some(min_bytes) = options.min_bytes {
+        header.push_str(&format!(", over {}", human_readable_size(units, min_bytes)));
+    }
+    if options.order == SortOrder::Ascending {
+        header.push_str(match options.by {
+            SortKey::Size => ", smallest first",
+            SortKey::Path => ", A to Z",
+            _ => ", oldest first",
+        });
+    }
+    header
+}
+
+// `folders` is already filtered and cut to `top_n`
+pub fn folders(out: &mut impl Write, units: Units, folders: &[FolderSize], options: &ListOptions, top_n: usize) -> io::Result<()> {
+    if options.tsv {
+        writeln!(out, "path\tsize_bytes\tfiles")?;
+        for folder in folders {
+            writeln!(out, "{}\t{}\t{}", folder.folder.display(), folder.size_bytes, folder.file_count)?;
+        }
+        return Ok(());
+    }
+
+    let title = match options.order {
+        SortOrder::Descending => "Largest Folders",
+        SortOrder::Ascending => "Smallest Folders Over The Threshold",
+    };
+    match options.min_bytes {
+        Some(min_bytes) => writeln!(out, "\n--- {} (Top {}, over {}) ---", title, top_n, human_readable_size(units, min_bytes))?,
+        None => writeln!(out, "\n--- {} (Top {}) ---", title, top_n)?,
+    }
+    for (i, folder) in folders.iter().enumerate() {
+        writeln!(out, "\n[{}] {}", i + 1, folder.shown(units))?;
+        writeln!(out, "  Subfolders: {}", folder.subfolder_count)?;
+        writeln!(out, "  Average File: {}", human_readable_size(units, folder.average_file_bytes()))?;
+        if let Some(file) = &folder.largest_file {
+            writeln!(out, "  Largest File: {} ({})", file.full_path.display(), human_readable_size(units, file.size_bytes))?;
+        }
+    }
+    Ok(())
+}
+
+pub fn recent_large_folders(out: &mut impl Write, units: Units, folders: &[FolderSize], days: u32, min_gb: f64) -> io::Result<()> {
+    writeln!(out, "\n--- Folders Over {} Changed In The Last {} Days ---", format_gb(units, min_gb), days)?;
+    if folders.is_empty() {
+        return writeln!(out, "No folder that large had a file modified in that time");
+    }
+
+    for (i, folder) in folders.iter().enumerate() {
+        writeln!(out, "\n[{}] {}", i + 1, folder.shown(units))?;
+    }
+    Ok(())
+}
+
+pub fn folder_shares(out: &mut impl Write, units: Units, drive: &str, shares: &[(PathBuf, u64, f64)]) -> io::Result<()> {
+    writeln!(out, "\n--- Share Of Used Space On {} ---", drive)?;
+    if shares.is_empty() {
+        return writeln!(out, "No folders found");
+    }
+
+    writeln!(out, "{:>8} {:>12}  {:<22} Folder", "Percent", "Size", "")?;
+    for (folder, size_bytes, percent) in shares {
+        let filled = ((percent / 5.0).round() as usize).min(20);
+        writeln!(
+            out,
+            "{:>7.2}% {:>12}  [{}{}] {}",
+            percent,
+            human_readable_size(units, *size_bytes),
+            "#".repeat(filled),
+            " ".repeat(20 - filled),
+            folder.display()
+        )?;
+    }
+    let total: f64 = shares.iter().map(|(_, _, percent)| percent).sum();
+    writeln!(out, "\nThese {} folders hold {:.2}% of the used space", shares.len(), total)
+}
+
+// growth in red and shrinking in green, folders the older scan didn't have are marked New
+pub fn folder_growth(out: &mut impl Write, units: Units, drive: &str, since: Duration, deltas: &[FolderDelta]) -> io::Result<()> {
+    writeln!(out, "\n--- Folder Growth On {} Since The Scan {} Minutes Ago ---", drive, since.as_secs() / 60)?;
+    if deltas.is_empty() {
+        return writeln!(out, "No folder changed size");
+    }
+
+    writeln!(out, "{:>12} {:>12} {:>13}  Folder", "Before", "Now", "Change")?;
+    for (folder, old_bytes, new_bytes, delta) in deltas {
+        let before = old_bytes.map(|bytes| human_readable_size(units, bytes)).unwrap_or_else(|| "New".to_string());
+        let sign = if *delta < 0 { '-' } else { '+' };
+        let change = format!("{:>13}", format!("{}{}", sign, human_readable_size(units, delta.unsigned_abs())));
+        let change = if *delta < 0 { change.green() } else { change.red() };
+        writeln!(out, "{:>12} {:>12} {}  {}", before, human_readable_size(units, *new_bytes), change, folder.display())?;
+    }
+    Ok(())
+}
+
+// recent is how much of each was modified in the last `recent_days`, writing
+// going on right now stands out from an archive that hasn't changed in years
+pub fn type_distribution(out: &mut impl Write, units: Units, distribution: &TypeDistribution, top_n: usize, recent_days: i64) -> io::Result<()> {
+    writeln!(out, "\n--- File Type Distribution (Top {}) ---", top_n)?;
+    for (ext, size, count, recent) in distribution.iter().take(top_n) {
+        writeln!(
+            out,
+            "\n[>] {} \n  Count: {} \n  Size: {} \n  Recent ({}d): {}",
+            ext, count, format_gb(units, bytes_to_gb(units, *size)), recent_days, format_gb(units, bytes_to_gb(units, *recent))
+        )?;
+    }
+    Ok(())
+}
+
+// percentages are of the scanned bytes, not the used space of the drive
+pub fn category_distribution(out: &mut impl Write, units: Units, drive: &str, totals: &CategoryTotals) -> io::Result<()> {
+    writeln!(out, "\n--- Size By Category ---")?;
+    let total_bytes: u64 = totals.iter().map(|(_, _, bytes)| bytes).sum();
+    if total_bytes == 0 {
+        return writeln!(out, "No files scanned on {}", drive);
+    }
+
+    writeln!(out, "{:<10} {:>10} {:>14} {:>8}", "Category", "Files", "Size", "Share")?;
+    for (category, count, bytes) in totals {
+        let percent = *bytes as f64 / total_bytes as f64 * 100.0;
+        writeln!(out, "{:<10} {:>10} {:>14} {:>7.1}%", category.as_str(), count, human_readable_size(units, *bytes), percent)?;
+    }
+    Ok(())
+}
+
+// biggest folder first, each with its top 3 extensions
+pub fn type_dist_per_folder(out: &mut impl Write, units: Units, folders: &[(String, TypeDistribution)]) -> io::Result<()> {
+    writeln!(out, "\n--- File Type Distribution Per Folder (Top 3) ---")?;
+    for (folder, distribution) in folders {
+        writeln!(out, "\n[*] {}", folder)?;
+        for (ext, size, count, _) in distribution.iter().take(3) {
+            writeln!(out, "  [>] {} - Count: {}, Size: {}", ext, count, format_gb(units, bytes_to_gb(units, *size)))?;
+        }
+    }
+    Ok(())
+}
+
+// `folders` come frozen first, so the oldest are the head and the most active the tail reversed
+// with fewer than 2 * `shown` folders they're split in half, so no folder is in both lists
+pub fn folder_ages(out: &mut impl Write, drive: &str, folders: &[FolderAge], shown: usize) -> io::Result<()> {
+    writeln!(out, "\n--- Folders By Content Age ---")?;
+    if folders.is_empty() {
+        return writeln!(out, "No files with a modification time on {}", drive);
+    }
+
+    let (oldest, rest) = folders.split_at(shown.min(folders.len().div_ceil(2)));
+    writeln!(out, "\nLeast recently changed (archival candidates):")?;
+    folder_age_rows(out, oldest.iter())?;
+    if rest.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, "\nMost recently changed:")?;
+    folder_age_rows(out, rest.iter().rev().take(shown))
+}
+
+fn folder_age_rows<'a>(out: &mut impl Write, folders: impl Iterator<Item = &'a FolderAge>) -> io::Result<()> {
+    writeln!(out, "  {:<20} {:<20} Folder", "Newest file", "Oldest file")?;
+    for (folder, oldest, newest) in folders {
+        writeln!(out, "  {:<20} {:<20} {}", format_time(*newest), format_time(*oldest), folder.display())?;
+    }
+    Ok(())
+}
+
+// `totals` is (distinct extensions, files) on the whole drive, before min_count cut anything
+pub fn extension_census(out: &mut impl Write, units: Units, drive: &str, min_count: usize, census: &TypeDistribution, totals: (usize, usize)) -> io::Result<()> {
+    writeln!(out, "\n--- Extension Census ---")?;
+    if census.is_empty() {
+        return writeln!(out, "No extensions with at least {} files on {}", min_count, drive);
+    }
+
+    writeln!(out, "{:<24} {:>12} {:>14}", "Extension", "Files", "Size")?;
+    for (ext, bytes, count, _) in census {
+        writeln!(out, "{:<24} {:>12} {:>14}", ext, count, format_gb(units, bytes_to_gb(units, *bytes)))?;
+    }
+    writeln!(out, "\n{} distinct extensions, {} files", totals.0, totals.1)
+}
+
+pub fn compare_ext(out: &mut impl Write, units: Units, a: &ExtensionStats, b: &ExtensionStats) -> io::Result<()> {
+    writeln!(out, "\n--- .{} On {} vs {} ---", a.extension, a.drive, b.drive)?;
+    writeln!(out, "{:<8} {:>12} {:>12} {:>12}", "", a.drive, b.drive, "Delta")?;
+    writeln!(
+        out,
+        "{:<8} {:>12} {:>12} {:>+12}",
+        "Count", a.count, b.count, b.count as i64 - a.count as i64
+    )?;
+    writeln!(
+        out,
+        "{:<8} {:>12} {:>12} {:>+9.2} {}",
+        "Size",
+        format_gb(units, bytes_to_gb(units, a.size_bytes)),
+        format_gb(units, bytes_to_gb(units, b.size_bytes)),
+        (b.size_bytes as f64 - a.size_bytes as f64) / units.bytes_per_gb(),
+        gb_label(units)
+    )
+}
+
+// every drive the OS knows about, the ones without readable space (an empty CD drive) just get dashes
+pub fn drives(out: &mut impl Write, units: Units, drives: &[(String, DriveKind, Option<DriveAnalysis>)]) -> io::Result<()> {
+    writeln!(out, "\n--- Drives ---")?;
+    if drives.is_empty() {
+        return writeln!(out, "No drives detected");
+    }
+
+    writeln!(out, "{:<6} {:<10} {:<34} {:>12} {:>12}", "Drive", "Kind", "Label", "Total", "Free")?;
+    for (drive, kind, space) in drives {
+        let label = space.as_ref().and_then(|s| s.label.as_deref()).unwrap_or("-");
+        let kind = format!("{:?}", kind);
+        match space {
+            Some(space) => writeln!(
+                out,
+                "{:<6} {:<10} {:<34} {:>12} {:>12}",
+                drive,
+                kind,
+                label,
+                format_gb(units, space.total_size),
+                format_gb(units, space.free_space)
+            )?,
+            None => writeln!(out, "{:<6} {:<10} {:<34} {:>12} {:>12}", drive, kind, label, "-", "-")?,
+        }
+    }
+    Ok(())
+}
+
+// (drive, free percent, critical) for every drive under the low threshold
+pub fn disk_warnings(out: &mut impl Write, warnings: &[DiskWarning], low_free_space_percent: f64) -> io::Result<()> {
+    writeln!(out, "\n--- Disk Space Warnings ---")?;
+    if warnings.is_empty() {
+        return writeln!(out, "All drives have more than {:.0}% free space", low_free_space_percent);
+    }
+
+    for warning in warnings {
+        let level = if warning.critical { "CRITICAL" } else { "LOW" };
+        let label = warning.label.as_ref().map(|label| format!(" ({})", label)).unwrap_or_default();
+        writeln!(out, "[{}] {}{} has only {:.2}% free space", level, warning.drive, label, warning.free_space_percent)?;
+    }
+    Ok(())
+}
+
+pub fn cleanup_candidates(out: &mut impl Write, units: Units, candidates: &[CleanupCandidate]) -> io::Result<()> {
+    writeln!(out, "\n--- Cleanup Candidates ---")?;
+    if candidates.is_empty() {
+        return writeln!(out, "Nothing to clean up, nice");
+    }
+
+    for (i, candidate) in candidates.iter().enumerate() {
+        writeln!(out, "\n[{}] {}", i + 1, candidate.path.display())?;
+        writeln!(out, "  Size: {}", format_mb(units, bytes_to_mb(units, candidate.size_bytes)))?;
+        writeln!(out, "  Reason: {}", candidate.reason)?;
+        writeln!(out, "  Confidence: {:?}", candidate.confidence)?;
+    }
+
+    let total: u64 = candidates.iter().map(|c| c.size_bytes).sum();
+    writeln!(out, "\nReclaimable: {} / {} across {} items", format_mb(units, bytes_to_mb(units, total)), format_gb(units, bytes_to_gb(units, total)), candidates.len())
+}
+
+// one line per day with a bar scaled to the busiest one, weeks separated by a blank line
+pub fn files_by_day(out: &mut impl Write, units: Units, days: u32, activity: &[(String, usize, u64)]) -> io::Result<()> {
+    writeln!(out, "\n--- Files Modified Per Day (Last {} Days) ---", days)?;
+    if activity.is_empty() {
+        return writeln!(out, "No files were modified in that time");
+    }
+
+    let max_count = activity.iter().map(|(_, count, _)| *count).max().unwrap_or(1);
+    let mut last_week = None;
+    for (day, count, size_bytes) in activity {
+        let date = chrono::NaiveDate::parse_from_str(day, DAY_FORMAT).ok();
+        // blank line between weeks so it reads like a calendar
+        let week = date.map(|d| d.iso_week());
+        if last_week.is_some() && week != last_week {
+            writeln!(out)?;
+        }
+        last_week = week;
+
+        let bar_len = (*count * 30).div_ceil(max_count);
+        writeln!(
+            out,
+            "{} {} | {:<30} {} files, {}",
+            day,
+            date.map(|d| d.format("%a").to_string()).unwrap_or_default(),
+            "#".repeat(bar_len),
+            count,
+            format_mb(units, bytes_to_mb(units, *size_bytes))
+        )?;
+    }
+    Ok(())
+}
+
+pub fn fs_errors(out: &mut impl Write, drive: &str, errors: &[ScanError]) -> io::Result<()> {
+    writeln!(out, "\n--- Filesystem Errors ---")?;
+    if errors.is_empty() {
+        return writeln!(out, "No filesystem errors during the last scan of {}", drive);
+    }
+
+    writeln!(out, "{:<8} {:<40} Path", "Code", "Message")?;
+    for error in errors {
+        writeln!(out, "{:<8} {:<40} {}", error.code, format_os_error(error.code), error.path)?;
+    }
+    writeln!(out, "\n{} paths, running chkdsk {} /scan is a good next step", errors.len(), drive.trim_end_matches('/'))
+}
+
+pub fn cache_info(out: &mut impl Write, drive: &str, info: Option<&CacheInfo>) -> io::Result<()> {
+    writeln!(out, "\n--- Cache Info ---")?;
+    let Some(info) = info else {
+        return writeln!(out, "Nothing cached for {}, the next command on it scans it", drive);
+    };
+
+    writeln!(out, "Drive:        {}", drive)?;
+    writeln!(out, "Scanned at:   {}", format_time(info.scanned_at))?;
+    writeln!(out, "Files:        {}", info.file_count)?;
+    writeln!(out, "Folders:      {}", info.folder_count)?;
+    writeln!(out, "Excluded:     {}", info.excluded)?;
+    writeln!(out, "Read errors:  {}", info.errors)?;
+    writeln!(out, "Unreadable:   {}", info.unreadable)?;
+    writeln!(out, "Scanned with: {}", info.options.describe())?;
+    if info.timed_out {
+        writeln!(out, "\nThe scan timeout stopped this scan early, rescan for the full picture")?;
+    } else if info.is_partial() {
+        writeln!(out, "\nThis scan left parts of the drive out, totals from it won't add up to the drive")?;
+    }
+    Ok(())
+}
+
+// counts for every kind, the most common first, then the paths that were kept
+pub fn unreadable(out: &mut impl Write, drive: &str, unreadable: &Unreadable) -> io::Result<()> {
+    writeln!(out, "\n--- Scan Errors ---")?;
+    if unreadable.total() == 0 {
+        return writeln!(out, "The last scan of {} could read everything it walked into", drive);
+    }
+
+    let mut counts = unreadable.counts.clone();
+    counts.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+    for (kind, count) in &counts {
+        writeln!(out, "  {:<20} {}", format!("{:?}", kind), count)?;
+    }
+    writeln!(out)?;
+    for (path, kind) in &unreadable.paths {
+        writeln!(out, "  [{:?}] {}", kind, path)?;
+    }
+    if unreadable.paths.len() < unreadable.total() {
+        writeln!(out, "  .. and {} more", unreadable.total() - unreadable.paths.len())?;
+    }
+    writeln!(
+        out,
+        "\n{} items unreadable, sizes above leave out whatever is inside them. \
+         PermissionDenied usually goes away when run as administrator",
+        unreadable.total()
+    )
+}
+
+// grouped by error kind, the most common kind first
+pub fn inaccessible_files(out: &mut impl Write, drive: &str, inaccessible: &[(String, ErrorKind)]) -> io::Result<()> {
+    writeln!(out, "\n--- Inaccessible Files ---")?;
+    if inaccessible.is_empty() {
+        return writeln!(out, "Everything on {} is readable", drive);
+    }
+
+    let mut by_kind: Vec<(ErrorKind, Vec<&str>)> = Vec::new();
+    for (path, kind) in inaccessible {
+        match by_kind.iter_mut().find(|(k, _)| k == kind) {
+            Some((_, paths)) => paths.push(path),
+            None => by_kind.push((*kind, vec![path])),
+        }
+    }
+    by_kind.sort_by_key(|(_, paths)| std::cmp::Reverse(paths.len()));
+
+    for (kind, paths) in &by_kind {
+        writeln!(out, "\n[{:?}] {} paths", kind, paths.len())?;
+        for path in paths {
+            writeln!(out, "  {}", path)?;
+        }
+    }
+    writeln!(
+        out,
+        "\n{} paths in total. PermissionDenied usually needs admin rights, \
+         other errors are often files locked by another program",
+        inaccessible.len()
+    )
+}
+
+// each cycle already reads "link -> target"
+pub fn symlink_cycles(out: &mut impl Write, drive: &str, cycles: &[String]) -> io::Result<()> {
+    writeln!(out, "\n--- Symlink Cycles ---")?;
+    if cycles.is_empty() {
+        return writeln!(out, "No link on {} leads back into itself", drive);
+    }
+
+    for cycle in cycles {
+        writeln!(out, "  {}", cycle)?;
+    }
+    writeln!(out, "\n{} cycles, deleting the link (not what it points to) breaks the loop", cycles.len())
+}
+
+// only the first 50 are listed, the total counts all of them
+pub fn files_matching(out: &mut impl Write, units: Units, pattern: &str, files: &[FileInfo]) -> io::Result<()> {
+    writeln!(out, "\n--- Files Matching '{}' ---", pattern)?;
+    if files.is_empty() {
+        return writeln!(out, "No matching files");
+    }
+
+    file_list(out, units, &files[..files.len().min(50)])?;
+    let total: u64 = files.iter().map(|f| f.size_bytes).sum();
+    writeln!(out, "\n{} matching files, {} total", files.len(), format_mb(units, bytes_to_mb(units, total)))
+}
+
+pub fn installer_cache(out: &mut impl Write, units: Units, files: &[FileInfo]) -> io::Result<()> {
+    writeln!(out, "\n--- Leftover Installer Packages ---")?;
+    if files.is_empty() {
+        return writeln!(out, "No leftover installers found");
+    }
+
+    file_list(out, units, files)?;
+    let total: u64 = files.iter().map(|f| f.size_bytes).sum();
+    writeln!(out, "\nTotal: {} files, {} / {}", files.len(), format_mb(units, bytes_to_mb(units, total)), format_gb(units, bytes_to_gb(units, total)))
+}
+
+pub fn unsynced_files(out: &mut impl Write, units: Units, files: &[FileInfo], roots: &[String], min_mb: f64) -> io::Result<()> {
+    writeln!(out, "\n--- Large Files Outside OneDrive (>= {}) ---", format_mb(units, min_mb))?;
+    if roots.is_empty() {
+        writeln!(out, "No OneDrive folder found, nothing on this drive is being backed up by it")?;
+    } else {
+        writeln!(out, "OneDrive: {}", roots.join(", "))?;
+    }
+    if files.is_empty() {
+        return writeln!(out, "Every large file is inside OneDrive");
+    }
+
+    file_list(out, units, files)?;
+    let total: u64 = files.iter().map(|f| f.size_bytes).sum();
+    writeln!(out, "\nNot backed up: {} files, {}", files.len(), format_gb(units, bytes_to_gb(units, total)))
+}
+
+// the biggest `shown` of them, the totals count every one
+pub fn executables(out: &mut impl Write, units: Units, files: &[FileInfo], shown: usize) -> io::Result<()> {
+    writeln!(out, "\n--- Executable Files (Top {}) ---", shown)?;
+    if files.is_empty() {
+        return writeln!(out, "No executable files found");
+    }
+
+    writeln!(out, "{:>12}  Path", "Size")?;
+    for file in files.iter().take(shown) {
+        writeln!(out, "{:>12}  {}", human_readable_size(units, file.size_bytes), file.full_path.display())?;
+    }
+    let total: u64 = files.iter().map(|f| f.size_bytes).sum();
+    writeln!(out, "\n{} executable files, {} in total", files.len(), human_readable_size(units, total))
+}
+
+pub fn temp_dirs(out: &mut impl Write, units: Units, dirs: &[FolderSize]) -> io::Result<()> {
+    writeln!(out, "\n--- Temp Directories ---")?;
+    writeln!(out, "{:>12} {:>10}  Directory", "Size", "Files")?;
+    for dir in dirs {
+        writeln!(out, "{:>12} {:>10}  {}", format_mb(units, bytes_to_mb(units, dir.size_bytes)), dir.file_count, dir.folder.display())?;
+    }
+    let total: u64 = dirs.iter().map(|d| d.size_bytes).sum();
+    writeln!(out, "\nTotal: {} in {} directories", format_gb(units, bytes_to_gb(units, total)), dirs.len())
+}
+
+pub fn downloads_total(out: &mut impl Write, units: Units, size_bytes: u64, count: usize) -> io::Result<()> {
+    writeln!(out, "\nDownloads folder: {}, {} files", format_gb(units, bytes_to_gb(units, size_bytes)), count)
+}
+
+pub fn thread_timing(out: &mut impl Write, threads: usize, elapsed: Duration) -> io::Result<()> {
+    writeln!(out, "{:>4} threads: {:.2?}", threads, elapsed)
+}
+
+pub fn refresh_drives(out: &mut impl Write, added: &[String], removed: &[String], drives: &[String]) -> io::Result<()> {
+    if added.is_empty() && removed.is_empty() {
+        writeln!(out, "No drives were plugged in or removed")?;
+    }
+    for drive in added {
+        writeln!(out, "+ {}", drive)?;
+    }
+    for drive in removed {
+        writeln!(out, "- {} (its cached scan was dropped)", drive)?;
+    }
+    writeln!(out, "Drives: {}", drives.join(", "))
+}
+
+pub fn no_files_of_type(out: &mut impl Write, ext: &str, drive: &str) -> io::Result<()> {
+    writeln!(out, "No .{} files on {}", normalize_extension(ext), drive)
+}
+
+// the notes go right under the title, before the summary lines
+pub fn drive_summary(out: &mut impl Write, drive: &str, notes: &[String], lines: &[String]) -> io::Result<()> {
+    writeln!(out, "\n=== {} Summary ({}) ===", drive, format_time(chrono::Utc::now()))?;
+    for line in notes.iter().chain(lines) {
+        writeln!(out, "{}", line)?;
+    }
+    Ok(())
+}
+
+// (account, files, bytes) among the `sample` largest files
+pub fn top_creators(out: &mut impl Write, units: Units, drive: &str, sample: usize, creators: &[(String, usize, u64)]) -> io::Result<()> {
+    writeln!(out, "\n--- Top File Owners (largest {} files) ---", sample)?;
+    if creators.is_empty() {
+        return writeln!(out, "Couldn't read the owner of any file on {}", drive);
+    }
+    writeln!(out, "{:<40} {:>8} {:>14}", "Account", "Files", "Size")?;
+    for (name, count, size) in creators {
+        writeln!(out, "{:<40} {:>8} {:>14}", name, count, format_gb(units, bytes_to_gb(units, *size)))?;
+    }
+    Ok(())
+}
+
+// the folders Everyone can get into are highlighted and numbered for #N
+pub fn acl_report(out: &mut impl Write, drive: &str, summaries: &[(String, AclSummary)]) -> io::Result<()> {
+    writeln!(out, "\n--- Folder Access Control ---")?;
+    if summaries.is_empty() {
+        return writeln!(out, "Couldn't read the permissions of any folder on {}", drive);
+    }
+
+    writeln!(out, "{:>6} {:>9}  Folder", "ACEs", "Inherits")?;
+    let mut exposed = 0;
+    for (folder, summary) in summaries {
+        let line = format!(
+            "{:>6} {:>9}  {}",
+            summary.ace_count,
+            if summary.inherits_parent { "yes" } else { "no" },
+            folder
+        );
+        if summary.has_everyone_access {
+            exposed += 1;
+            writeln!(out, "{} (Everyone has access)", line.red())?;
+        } else {
+            writeln!(out, "{}", line)?;
+        }
+    }
+    if exposed > 0 {
+        writeln!(
+            out,
+            "\n{} folders give Everyone access, check whether that's intended (#1..#{} refer to them)",
+            exposed, exposed
+        )
+    } else {
+        writeln!(out, "\nNo folder gives Everyone access")
+    }
+}
+
+/// One scan-batch target: what it was, how its scan went and the files and bytes it cached.
+pub type BatchRow<'a> = (&'a str, Result<ScanStatus, String>, Option<(usize, u64)>);
+
+pub fn scan_batch(out: &mut impl Write, units: Units, results: &[BatchRow<'_>]) -> io::Result<()> {
+    writeln!(out, "\n--- Batch Scan ---")?;
+    if results.is_empty() {
+        return writeln!(out, "Nothing to scan");
+    }
+
+    writeln!(out, "{:<32} {:>10} {:>14}  Result", "Drive", "Files", "Size")?;
+    let mut failed = 0;
+    for (target, result, totals) in results {
+        match (result, totals) {
+            (Ok(status), Some((count, bytes))) => writeln!(
+                out,
+                "{:<32} {:>10} {:>14}  {}",
+                target, count, format_gb(units, bytes_to_gb(units, *bytes)), batch_outcome(*status)
+            )?,
+            (Ok(status), None) => writeln!(out, "{:<32} {:>10} {:>14}  {} (folders only)", target, "-", "-", batch_outcome(*status))?,
+            (Err(e), _) => {
+                failed += 1;
+                writeln!(out, "{:<32} {:>10} {:>14}  {}", target, "-", "-", e)?
+            }
+        }
+    }
+    writeln!(out, "\n{} scanned, {} failed", results.len() - failed, failed)?;
+    if results.iter().any(|(_, result, _)| matches!(result, Ok(ScanStatus::Partial))) {
+        writeln!(out, "partial: stopped by the scan timeout, only what was reached is cached")?;
+    }
+    Ok(())
+}
+
+fn batch_outcome(status: ScanStatus) -> &'static str {
+    match status {
+        ScanStatus::Complete => "ok",
+        ScanStatus::Partial => "partial",
+    }
+}
+
+// (file, bytes on disk), only `top_n` are listed but the totals count every one
+pub fn compressed_files(out: &mut impl Write, units: Units, drive: &str, compressed: &[(FileInfo, u64)], top_n: usize) -> io::Result<()> {
+    writeln!(out, "\n--- Compressed Files ---")?;
+    if compressed.is_empty() {
+        return writeln!(out, "No NTFS-compressed files on {}", drive);
+    }
+
+    for (i, (file, physical)) in compressed.iter().take(top_n).enumerate() {
+        writeln!(out, "\n[{}] Path: {}", i + 1, file.full_path.display())?;
+        writeln!(out, "    Logical Size: {}", format_mb(units, bytes_to_mb(units, file.size_bytes)))?;
+        writeln!(out, "    Compressed Size: {}", human_readable_size(units, *physical))?;
+    }
+    if compressed.len() > top_n {
+        writeln!(out, "\n... and {} more", compressed.len() - top_n)?;
+    }
+
+    let logical: u64 = compressed.iter().map(|(file, _)| file.size_bytes).sum();
+    let physical: u64 = compressed.iter().map(|(_, physical)| physical).sum();
+    writeln!(
+        out,
+        "\n{} compressed files, {} logical, {} on disk ({} saved by compression)",
+        compressed.len(),
+        format_gb(units, bytes_to_gb(units, logical)),
+        format_gb(units, bytes_to_gb(units, physical)),
+        format_gb(units, bytes_to_gb(units, logical.saturating_sub(physical)))
+    )
+}
+
+// (file, bytes on disk) for the sparse files worth mentioning
+pub fn sparse_files(out: &mut impl Write, units: Units, drive: &str, sparse: &[(FileInfo, u64)]) -> io::Result<()> {
+    writeln!(out, "\n--- Sparse Files ---")?;
+    if sparse.is_empty() {
+        return writeln!(out, "No sparse files using less than half their size on {}", drive);
+    }
+
+    for (i, (file, physical)) in sparse.iter().enumerate() {
+        writeln!(out, "\n[{}] Path: {}", i + 1, file.full_path.display())?;
+        writeln!(out, "    Logical Size: {}", format_mb(units, bytes_to_mb(units, file.size_bytes)))?;
+        writeln!(out, "    On Disk: {}", human_readable_size(units, *physical))?;
+    }
+
+    let logical: u64 = sparse.iter().map(|(file, _)| file.size_bytes).sum();
+    let physical: u64 = sparse.iter().map(|(_, physical)| physical).sum();
+    writeln!(
+        out,
+        "\n{} sparse files, {} logical but only {} on disk ({} saved)",
+        sparse.len(),
+        format_gb(units, bytes_to_gb(units, logical)),
+        format_gb(units, bytes_to_gb(units, physical)),
+        format_gb(units, bytes_to_gb(units, logical.saturating_sub(physical)))
+    )
+}
+
+pub fn compression_candidates(out: &mut impl Write, units: Units, candidates: &[CompressionCandidate]) -> io::Result<()> {
+    writeln!(out, "\n--- NTFS Compression Candidates ---")?;
+    if candidates.is_empty() {
+        return writeln!(out, "No good compression candidates found");
+    }
+
+    for (i, candidate) in candidates.iter().enumerate() {
+        writeln!(out, "\n[{}] Path: {}", i + 1, candidate.file.full_path.display())?;
+        writeln!(out, "    Size: {}", format_mb(units, bytes_to_mb(units, candidate.file.size_bytes)))?;
+        writeln!(
+            out,
+            "    Estimated Savings: {} ({:.0}% smaller)",
+            format_mb(units, bytes_to_mb(units, candidate.estimated_savings_bytes)),
+            (1.0 - candidate.estimated_ratio) * 100.0
+        )?;
+    }
+
+    let total: u64 = candidates.iter().map(|c| c.estimated_savings_bytes).sum();
+    writeln!(
+        out,
+        "\nEstimated total savings: {} / {} (advisory, nothing was changed)",
+        format_mb(units, bytes_to_mb(units, total)), format_gb(units, bytes_to_gb(units, total))
+    )
+}
+
+// (file on a, its copy on b), the first `shown` are listed and numbered, files under `min_bytes` weren't compared
+pub fn cross_drive_duplicates(
+    out: &mut impl Write,
+    units: Units,
+    drive_a: &str,
+    drive_b: &str,
+    min_bytes: u64,
+    pairs: &[(FileInfo, FileInfo)],
+    shown: usize,
+) -> io::Result<()> {
+    writeln!(out, "\n--- Files On Both {} And {} ---", drive_a, drive_b)?;
+    let smallest = format_mb(units, bytes_to_mb(units, min_bytes));
+    if pairs.is_empty() {
+        return writeln!(out, "Nothing of {} or more on {} has a copy on {}", smallest, drive_a, drive_b);
+    }
+    writeln!(out, "(files under {} weren't compared)", smallest)?;
+
+    for (i, (file, copy)) in pairs.iter().take(shown).enumerate() {
+        writeln!(out, "\n[{}] {} ({})", i + 1, file.full_path.display(), format_mb(units, bytes_to_mb(units, file.size_bytes)))?;
+        writeln!(out, "     same as {}", copy.full_path.display())?;
+    }
+
+    let savings: u64 = pairs.iter().map(|(file, _)| file.size_bytes).sum();
+    writeln!(
+        out,
+        "\n{} files, {} / {} freed on either drive by dropping its copies",
+        pairs.len(), format_mb(units, bytes_to_mb(units, savings)), format_gb(units, bytes_to_gb(units, savings))
+    )
+}
+
+// the first `shown` groups are listed, every file in them numbered for #N
+pub fn duplicates(out: &mut impl Write, units: Units, groups: &[Vec<FileInfo>], shown: usize) -> io::Result<()> {
+    writeln!(out, "\n--- Duplicate Files ---")?;
+    if groups.is_empty() {
+        return writeln!(out, "No duplicates found");
+    }
+
+    let mut number = 0;
+    for group in groups.iter().take(shown) {
+        writeln!(out, "\n{} copies of {}:", group.len(), format_mb(units, bytes_to_mb(units, group[0].size_bytes)))?;
+        for file in group {
+            number += 1;
+            writeln!(out, "  [{}] {}", number, file.full_path.display())?;
+        }
+    }
+
+    let wasted: u64 = groups
+        .iter()
+        .map(|group| group[0].size_bytes * (group.len() - 1) as u64)
+        .sum();
+    writeln!(
+        out,
+        "\n{} groups, {} / {} reclaimable by keeping one copy of each",
+        groups.len(), format_mb(units, bytes_to_mb(units, wasted)), format_gb(units, bytes_to_gb(units, wasted))
+    )
+}
+
+// (extension, used bytes, limit bytes) for every rule, the violated ones in red
+pub fn ext_quotas(out: &mut impl Write, units: Units, usage: &[(String, u64, u64)], violations: &[(String, u64, u64)]) -> io::Result<()> {
+    writeln!(out, "\n--- Extension Quotas ---")?;
+    for (ext, used, limit) in usage {
+        let line = format!(".{:<10} {:>14} / {}", ext, format_gb(units, bytes_to_gb(units, *used)), format_gb(units, bytes_to_gb(units, *limit)));
+        if violations.iter().any(|(violated, _, _)| violated == ext) {
+            writeln!(out, "{} (over by {})", line.red(), format_gb(units, bytes_to_gb(units, used - limit)))?;
+        } else {
+            writeln!(out, "{}", line.green())?;
+        }
+    }
+    writeln!(out, "\n{} of {} quotas exceeded", violations.len(), usage.len())
+}
+
+// folders created in the last year are highlighted
+pub fn folder_timeline(out: &mut impl Write, drive: &str, timeline: &[(String, chrono::DateTime<chrono::Utc>)]) -> io::Result<()> {
+    writeln!(out, "\n--- Folder Creation Timeline ---")?;
+    if timeline.is_empty() {
+        return writeln!(out, "No folders found on {}", drive);
+    }
+
+    let last_year = chrono::Utc::now() - chrono::Duration::days(365);
+    for (folder, created) in timeline {
+        let line = format!("{}  {}", format_time(*created), folder);
+        if *created > last_year {
+            writeln!(out, "{}", line.bright_white())?;
+        } else {
+            writeln!(out, "{}", line)?;
+        }
+    }
+    let recent = timeline.iter().filter(|(_, created)| *created > last_year).count();
+    writeln!(out, "\n{} folders, {} of them created in the last year (highlighted)", timeline.len(), recent)
+}
+
+// (file, what its contents actually are) among the `sample` largest files
+pub fn extension_mismatches(out: &mut impl Write, units: Units, drive: &str, sample: usize, mismatches: &[(FileInfo, String)]) -> io::Result<()> {
+    writeln!(out, "\n--- Extension Mismatches (largest {} files checked) ---", sample)?;
+    if mismatches.is_empty() {
+        return writeln!(out, "Every recognized file on {} matches its extension", drive);
+    }
+
+    for (i, (file, actual)) in mismatches.iter().enumerate() {
+        let declared = file.full_path.extension().map(|e| e.to_string_lossy().to_string()).unwrap_or_default();
+        writeln!(out, "\n[{}] Path: {}", i + 1, file.full_path.display())?;
+        writeln!(out, "    Declared: .{}, Actually: {}", declared, actual)?;
+        writeln!(out, "    Size: {}", format_mb(units, bytes_to_mb(units, file.size_bytes)))?;
+    }
+    writeln!(out, "\n{} files don't match their extension", mismatches.len())
+}
+
+// the tiers build on each other, so each one's projection is a running total
+pub fn reclaim_estimate(out: &mut impl Write, units: Units, space: &DriveAnalysis, sources: &[ReclaimSource]) -> io::Result<()> {
+    writeln!(out, "\n--- Reclaim Estimate (dry run, nothing is deleted) ---")?;
+    writeln!(
+        out,
+        "Currently free: {} of {} ({:.2}%)",
+        format_gb(units, space.free_space), format_gb(units, space.total_size), space.free_space_percent
+    )?;
+
+    let mut reclaimed_gb = 0.0;
+    for (tier, title) in [
+        (ReclaimTier::Safe, "Safe"),
+        (ReclaimTier::ProbablySafe, "Probably safe"),
+        (ReclaimTier::Aggressive, "Aggressive"),
+    ] {
+        writeln!(out, "\n[{}]", title)?;
+        for source in sources.iter().filter(|s| s.tier == tier) {
+            reclaimed_gb += bytes_to_gb(units, source.size_bytes);
+            writeln!(
+                out,
+                "  {:<30} {:>14}  {:>8} items  (see {})",
+                source.name, format_mb(units, bytes_to_mb(units, source.size_bytes)), source.items, source.report
+            )?;
+        }
+
+        let projected_free = space.free_space + reclaimed_gb;
+        writeln!(
+            out,
+            "  => {} reclaimable so far, projected free space {} ({:.2}%)",
+            format_gb(units, reclaimed_gb), format_gb(units, projected_free), projected_free / space.total_size * 100.0
+        )?;
+    }
+    Ok(())
+}
+
+pub fn snapshot_saved(out: &mut impl Write, name: &str, drives: &[String], path: &Path) -> io::Result<()> {
+    writeln!(out, "Saved {} as snapshot {} ({})", drives.join(", "), name, path.display())
+}
+
+pub fn snapshot_loaded(out: &mut impl Write, name: &str, drives: &[String]) -> io::Result<()> {
+    writeln!(out, "Loaded snapshot {}, the cache of {} is now what it saved", name, drives.join(", "))
+}
+
+// (name, when it was saved, files across its drives), newest first
+pub fn snapshots(out: &mut impl Write, snapshots: &[(String, chrono::DateTime<chrono::Utc>, usize)]) -> io::Result<()> {
+    if snapshots.is_empty() {
+        return writeln!(out, "No snapshots yet, snapshot save <name> makes one");
+    }
+
+    writeln!(out, "{:<20} {:<20} Files", "Name", "Saved")?;
+    for (name, saved_at, files) in snapshots {
+        writeln!(out, "{:<20} {:<20} {}", name, format_time(*saved_at), files)?;
+    }
+    Ok(())
+}
+
+// (process, bytes per second) most first, only `shown` of them are listed
+pub fn top_disk_writers(out: &mut impl Write, units: Units, writers: &[(String, f64)], sample_secs: u64, shown: usize) -> io::Result<()> {
+    writeln!(out, "\n--- Top Disk Writers ---")?;
+    if writers.is_empty() {
+        return writeln!(out, "No process wrote anything in the last {}s", sample_secs);
+    }
+
+    writeln!(out, "{:<32} {:>14} {:>14}", "Process", "Written", "Per second")?;
+    for (process, rate) in writers.iter().take(shown) {
+        let written = (rate * sample_secs as f64) as u64;
+        writeln!(out, "{:<32} {:>14} {:>14}", process, human_readable_size(units, written), format!("{}/s", human_readable_size(units, *rate as u64)))?;
+    }
+    writeln!(out, "\nWrites to any drive, the network and pipes all count, not only this disk")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::test_util::file;
+    use chrono::{TimeZone, Utc};
+
+    fn render(print: impl FnOnce(&mut Vec<u8>) -> io::Result<()>) -> String {
+        let mut out = Vec::new();
+        print(&mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn folders_as_tsv() {
+        let listed = [FolderSize { folder: "C:/Games".into(), size_bytes: 2_048, file_count: 3, ..FolderSize::default() }];
+        let options = ListOptions { tsv: true, ..ListOptions::default() };
+        assert_eq!(
+            render(|out| folders(out, Units::Binary, &listed, &options, 10)),
+            "path\tsize_bytes\tfiles\nC:/Games\t2048\t3\n"
+        );
+    }
+
+    #[test]
+    fn top_files_says_when_fewer_pass_the_size_filter() {
+        let options = ListOptions { min_bytes: Some(1024), ..ListOptions::default() };
+        let files = options.apply(vec![file("C:/big.iso", 4096), file("C:/small.txt", 10)], 5);
+        assert_eq!(files.len(), 1);
+
+        let shown = render(|out| top_files(out, Units::Binary, "Largest Files", &files, &options, 5));
+        assert!(shown.starts_with("\n--- Largest Files, over 1.00 KiB ---\nonly 1 files exceed 1.00 KiB\n"));
+        assert!(shown.contains("[1] Path: C:/big.iso"));
+        assert!(!shown.contains("small.txt"));
+    }
+
+    #[test]
+    fn unavailable_drives_explain_instead_of_showing_a_percentage() {
+        let shown = render(|out| drive_unavailable(out, "E:/", None));
+        assert!(shown.starts_with("\n--- Drive Space Overview: E:/ ---\nSpace unknown"));
+        assert!(!shown.contains('%'));
+    }
+
+    #[test]
+    fn a_folder_is_never_both_among_the_oldest_and_the_newest() {
+        let at = |day| Utc.with_ymd_and_hms(2024, 1, day, 0, 0, 0).unwrap();
+        let folders: Vec<FolderAge> = (1..=3).map(|day| (PathBuf::from(format!("C:/f{}", day)), at(day), at(day))).collect();
+        let shown = render(|out| folder_ages(out, "C:/", &folders, 10));
+        for folder in ["C:/f1", "C:/f2", "C:/f3"] {
+            assert_eq!(shown.matches(folder).count(), 1, "{}", shown);
+        }
+        let newest = shown.split("Most recently changed:").nth(1).unwrap();
+        assert!(newest.contains("C:/f3") && !newest.contains("C:/f2"));
+    }
+
+    #[test]
+    fn the_census_totals_count_what_min_count_left_out() {
+        let census = vec![("tmp".to_string(), 4096, 500, 0)];
+        let shown = render(|out| extension_census(out, Units::Binary, "C:/", 100, &census, (12, 740)));
+        assert!(shown.ends_with("\n12 distinct extensions, 740 files\n"), "{}", shown);
+    }
+
+    #[test]
+    fn symlink_cycles_say_how_to_break_them() {
+        assert_eq!(
+            render(|out| symlink_cycles(out, "C:/", &[])),
+            "\n--- Symlink Cycles ---\nNo link on C:/ leads back into itself\n"
+        );
+        let shown = render(|out| symlink_cycles(out, "C:/", &["C:/a/loop -> C:/a".to_string()]));
+        assert!(shown.contains("\n  C:/a/loop -> C:/a\n\n1 cycles, deleting the link"), "{}", shown);
+    }
+
+    #[test]
+    fn disk_warnings_mark_critical_drives() {
+        let warning = |drive: &str, label: Option<&str>, free_space_percent, critical| DiskWarning {
+            drive: drive.to_string(),
+            label: label.map(str::to_string),
+            free_space_percent,
+            critical,
+        };
+        let warnings = [warning("C:/", Some("Windows"), 3.5, true), warning("D:/", None, 12.0, false)];
+        assert_eq!(
+            render(|out| disk_warnings(out, &warnings, 15.0)),
+            "\n--- Disk Space Warnings ---\n[CRITICAL] C:/ (Windows) has only 3.50% free space\n[LOW] D:/ has only 12.00% free space\n"
+        );
+    }
+
+    #[test]
+    fn duplicates_are_numbered_across_the_groups_that_are_shown() {
+        let groups = [
+            vec![file("C:/a.bin", 2048), file("C:/b.bin", 2048)],
+            vec![file("C:/c.bin", 1024), file("C:/d.bin", 1024)],
+        ];
+        let shown = render(|out| duplicates(out, Units::Binary, &groups, 1));
+        assert!(shown.contains("  [1] C:/a.bin\n  [2] C:/b.bin\n"));
+        assert!(!shown.contains("c.bin"));
+        // the total still counts the group that wasn't listed
+        assert!(shown.contains("\n2 groups, "));
+    }
+
+    #[test]
+    fn batch_rows_say_which_scans_were_cut_short() {
+        let rows: [BatchRow<'_>; 3] = [
+            ("C:/", Ok(ScanStatus::Complete), Some((10, 1024))),
+            ("D:/", Ok(ScanStatus::Partial), Some((4, 512))),
+            ("Z:/", Err("root not found".to_string()), None),
+        ];
+        let shown = render(|out| scan_batch(out, Units::Binary, &rows));
+        assert!(shown.lines().any(|line| line.starts_with("C:/") && line.ends_with("  ok")));
+        assert!(shown.lines().any(|line| line.starts_with("D:/") && line.ends_with("  partial")));
+        assert!(shown.contains("\n2 scanned, 1 failed\npartial: "));
+    }
+}
@@ -0,0 +1,188 @@
+use super::constants::LIVE_UI_REFRESH_MS;
+use crossterm::{
+    cursor::{self, MoveTo},
+    queue,
+    terminal::{Clear, ClearType},
+};
+use std::{
+    io::{self, IsTerminal, Write},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc::{self, RecvTimeoutError, Sender},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+// set while scan_and_cache walks a root, Ctrl+C only cancels a scan when one is running
+static SCANNING: AtomicBool = AtomicBool::new(false);
+static CANCEL_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+// asks the running scan to stop, false when there's no scan to stop
+pub fn cancel_running_scan() -> bool {
+    if !SCANNING.load(Ordering::SeqCst) {
+        return false;
+    }
+    CANCEL_REQUESTED.store(true, Ordering::SeqCst);
+    true
+}
+
+pub(crate) fn scan_cancelled() -> bool {
+    CANCEL_REQUESTED.load(Ordering::Relaxed)
+}
+
+// marks a scan as running until it's dropped, so an early return can't leave the flag set
+pub(crate) struct ScanGuard;
+
+impl ScanGuard {
+    pub(crate) fn start() -> ScanGuard {
+        CANCEL_REQUESTED.store(false, Ordering::SeqCst);
+        SCANNING.store(true, Ordering::SeqCst);
+        ScanGuard
+    }
+}
+
+impl Drop for ScanGuard {
+    fn drop(&mut self) {
+        SCANNING.store(false, Ordering::SeqCst);
+        CANCEL_REQUESTED.store(false, Ordering::SeqCst);
+    }
+}
+
+// cancels the running scan once `limit` has passed, the walk winds down the same way as on Ctrl+C
+pub(crate) struct ScanDeadline {
+    stop: Option<Sender<()>>,
+    handle: Option<JoinHandle<bool>>,
+}
+
+impl ScanDeadline {
+    pub(crate) fn start(limit: Duration) -> ScanDeadline {
+        ScanDeadline::setting(&CANCEL_REQUESTED, limit)
+    }
+
+    // sets `flag` instead, so the clock can be tested without cancelling anyone's scan
+    fn setting(flag: &'static AtomicBool, limit: Duration) -> ScanDeadline {
+        let (stop, stopped) = mpsc::channel::<()>();
+        let handle = thread::spawn(move || {
+            let timed_out = matches!(stopped.recv_timeout(limit), Err(RecvTimeoutError::Timeout));
+            if timed_out {
+                flag.store(true, Ordering::SeqCst);
+            }
+            timed_out
+        });
+        ScanDeadline { stop: Some(stop), handle: Some(handle) }
+    }
+
+    // stops the clock, true when it ran out before the scan was done
+    pub(crate) fn finish(mut self) -> bool {
+        self.stop_clock()
+    }
+
+    fn stop_clock(&mut self) -> bool {
+        drop(self.stop.take());
+        self.handle.take().is_some_and(|handle| handle.join().unwrap_or(false))
+    }
+}
+
+impl Drop for ScanDeadline {
+    fn drop(&mut self) {
+        self.stop_clock();
+    }
+}
+
+// counters the scan bumps as it goes, read by the live table
+#[derive(Default)]
+pub struct ScanProgress {
+    pub files: AtomicUsize,
+    pub folders: AtomicUsize,
+    pub errors: AtomicUsize,
+}
+
+impl ScanProgress {
+    fn line(&self, elapsed: Duration) -> String {
+        let files = self.files.load(Ordering::Relaxed);
+        let speed = files as f64 / elapsed.as_secs_f64().max(0.001);
+        format!(
+            "Files scanned: {} | Folders: {} | Errors: {} | Speed: {:.0} files/s | Elapsed: {:.1}s",
+            files,
+            self.folders.load(Ordering::Relaxed),
+            self.errors.load(Ordering::Relaxed),
+            speed,
+            elapsed.as_secs_f64()
+        )
+    }
+}
+
+// redraws one line in place while a scan runs, dropping it moves past the final counts
+pub struct LiveUi {
+    stop: Option<Sender<()>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl LiveUi {
+    // fails when stdout isn't a terminal, the scan then just runs without the table
+    // no raw mode, it would keep Ctrl+C from reaching the handler that cancels the scan
+    pub fn start(progress: Arc<ScanProgress>) -> io::Result<LiveUi> {
+        if !io::stdout().is_terminal() {
+            return Err(io::Error::new(io::ErrorKind::Unsupported, "stdout isn't a terminal"));
+        }
+        let (_, row) = cursor::position()?;
+
+        let (stop, stopped) = mpsc::channel();
+        let started = Instant::now();
+        let handle = thread::spawn(move || {
+            let mut stdout = io::stdout();
+            let refresh = Duration::from_millis(LIVE_UI_REFRESH_MS);
+            let mut done = false;
+            loop {
+                let _ = queue!(stdout, MoveTo(0, row), Clear(ClearType::CurrentLine));
+                let _ = write!(stdout, "{}", progress.line(started.elapsed()));
+                let _ = stdout.flush();
+                // the draw after the stop leaves the final counts on screen
+                if done {
+                    break;
+                }
+                done = !matches!(stopped.recv_timeout(refresh), Err(RecvTimeoutError::Timeout));
+            }
+        });
+        Ok(LiveUi { stop: Some(stop), handle: Some(handle) })
+    }
+}
+
+impl Drop for LiveUi {
+    fn drop(&mut self) {
+        drop(self.stop.take());
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+        println!();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_deadline_only_goes_off_when_the_scan_outlasts_it() {
+        static FINISHED_IN_TIME: AtomicBool = AtomicBool::new(false);
+        let deadline = ScanDeadline::setting(&FINISHED_IN_TIME, Duration::from_secs(60));
+        let started = Instant::now();
+        assert!(!deadline.finish());
+        assert!(!FINISHED_IN_TIME.load(Ordering::SeqCst));
+        // finishing stops the clock right away instead of waiting out the limit
+        assert!(started.elapsed() < Duration::from_secs(10));
+
+        static RAN_OUT: AtomicBool = AtomicBool::new(false);
+        let deadline = ScanDeadline::setting(&RAN_OUT, Duration::from_millis(1));
+        thread::sleep(Duration::from_millis(50));
+        assert!(RAN_OUT.load(Ordering::SeqCst));
+        assert!(deadline.finish());
+
+        // dropped without finishing, the clock still stops
+        static DROPPED: AtomicBool = AtomicBool::new(false);
+        drop(ScanDeadline::setting(&DROPPED, Duration::from_secs(60)));
+        assert!(!DROPPED.load(Ordering::SeqCst));
+    }
+}
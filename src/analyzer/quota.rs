@@ -0,0 +1,52 @@
+use super::{
+    error::{AnalyzerError, Result},
+    constants::*,
+    printers,
+    storage::StorageAnalyzer,
+    types::*,
+    utils::{gb_to_bytes, normalize_extension}
+};
+use std::{collections::HashMap, io, path::Path};
+
+// the entries of a quota_usage result that are over their limit
+fn over_limit(usage: &[(String, u64, u64)]) -> Vec<(String, u64, u64)> {
+    usage.iter().filter(|(_, used, limit)| used > limit).cloned().collect()
+}
+
+impl StorageAnalyzer {
+    // (extension, used bytes, limit bytes) for every rule, in the order they were given
+    pub fn quota_usage(&mut self, drive: &str, rules: &[QuotaRule]) -> Result<Vec<(String, u64, u64)>> {
+        self.collect_and_cache_files(drive)?;
+
+        let mut used_bytes: HashMap<String, u64> = HashMap::new();
+        if let Some(files) = self.cached_files(drive) {
+            for file in files {
+                let ext = file.full_path
+                    .extension()
+                    .map(|e| e.to_string_lossy().to_lowercase())
+                    .unwrap_or_default();
+                *used_bytes.entry(ext).or_default() += file.size_bytes;
+            }
+        }
+
+        Ok(rules
+            .iter()
+            .map(|rule| {
+                let ext = normalize_extension(&rule.extension);
+                let used = used_bytes.get(&ext).copied().unwrap_or(0);
+                (ext, used, gb_to_bytes(self.units(), rule.limit_gb))
+            })
+            .collect())
+    }
+
+    // only the rules that are over their limit
+    pub fn check_ext_quotas(&mut self, drive: &str, rules: &[QuotaRule]) -> Result<Vec<(String, u64, u64)>> {
+        Ok(over_limit(&self.quota_usage(drive, rules)?))
+    }
+
+    pub fn print_ext_quotas(&mut self, drive: &str, rules: &[QuotaRule]) -> Result<()> {
+        let usage = self.quota_usage(drive, rules)?;
+        let violations = over_limit(&usage);
+        Ok(printers::ext_quotas(&mut self.out(), self.units(), &usage, &violations)?)
+    }
+}
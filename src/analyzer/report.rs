@@ -0,0 +1,280 @@
+use super::{
+    config::Units,
+    error::Result,
+    printers,
+    storage::StorageAnalyzer,
+    types::{DriveReport, SortOrder, TypeDistribution, Unreadable},
+    utils::{bytes_to_gb, bytes_to_mb, csv_field, format_gb, format_mb, format_time, human_readable_size, normalize_path},
+};
+use chrono::Utc;
+use serde_json::json;
+use std::{fmt::Write as _, fs, io::{self, Error, ErrorKind}, path::Path};
+
+impl StorageAnalyzer {
+    /// Every `analyze_drive` section as data.
+    pub fn drive_report(&mut self, drive: &str) -> Result<DriveReport> {
+        // one walk up front, otherwise the folders come from the uncached fallback and the
+        // distribution scans the drive all over again
+        self.collect_and_cache_files(drive)?;
+        Ok(DriveReport {
+            space: self.get_drive_space(drive)?,
+            folders: self.get_largest_folders(drive, SortOrder::Descending)?,
+            distribution: self.get_file_type_distribution(drive)?,
+            largest: self.get_largest_files(drive, SortOrder::Descending)?,
+            recent: self.get_recent_large_files(drive)?,
+            old: self.get_old_large_files(drive)?,
+        })
+    }
+
+    // the headline number of each analyze_drive section, one line each
+    pub fn drive_summary(&mut self, drive: &str) -> Result<Vec<String>> {
+        let units = self.units();
+        let report = self.drive_report(drive)?;
+        let days = &self.config().days;
+        let mut lines = vec![format!(
+            "Usage: {:.1}% of {} used, {} free",
+            100.0 - report.space.free_space_percent,
+            format_gb(units, report.space.total_size),
+            format_gb(units, report.space.free_space)
+        )];
+
+        lines.push(match report.folders.first() {
+            Some(folder) => format!("Biggest folder: {} ({})", folder.folder.display(), format_gb(units, bytes_to_gb(units, folder.size_bytes))),
+            None => "Biggest folder: none found".to_string(),
+        });
+
+        let scanned: u64 = report.distribution.iter().map(|(_, size, _, _)| size).sum();
+        lines.push(match report.distribution.first() {
+            Some((ext, size, count, _)) if scanned > 0 => format!(
+                "Top file type: {} ({}, {:.1}% of scanned bytes, {} files)",
+                ext, format_gb(units, bytes_to_gb(units, *size)), *size as f64 / scanned as f64 * 100.0, count
+            ),
+            _ => "Top file type: none found".to_string(),
+        });
+
+        lines.push(match report.largest.first() {
+            Some(file) => format!("Largest file: {} ({})", file.full_path.display(), format_mb(units, bytes_to_mb(units, file.size_bytes))),
+            None => "Largest file: none found".to_string(),
+        });
+
+        let recent: u64 = report.recent.iter().map(|f| f.size_bytes).sum();
+        lines.push(format!(
+            "Recent growth: {} in {} large files (last {} days)",
+            format_gb(units, bytes_to_gb(units, recent)), report.recent.len(), days.recent_window_days
+        ));
+        let old: u64 = report.old.iter().map(|f| f.size_bytes).sum();
+        lines.push(format!(
+            "Old files: {} in {} large files (>{} days old)",
+            format_gb(units, bytes_to_gb(units, old)), report.old.len(), days.old_window_days
+        ));
+        Ok(lines)
+    }
+
+    pub fn print_drive_summary(&mut self, drive: &str) -> Result<()> {
+        let lines = self.drive_summary(drive)?;
+        let notes: Vec<String> = [
+            self.depth_note(drive),
+            self.scan_options_note(drive),
+            self.unreadable_note(drive),
+            self.timeout_note(drive),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        Ok(printers::drive_summary(&mut self.out(), drive, &notes, &lines)?)
+    }
+
+    // same sections as analyze_drive, rendered as a standalone HTML page
+    pub fn generate_html_report(&mut self, drive: &str) -> Result<String> {
+        let units = self.units();
+        let DriveReport { space, folders, distribution, largest, recent, old } = self.drive_report(drive)?;
+
+        let top_n = self.config().display.top_n;
+        let old_title = format!("Old Large Files (&gt;{} days old)", self.config().days.old_window_days);
+
+        let mut html = String::new();
+        let _ = write!(
+            html,
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>Storage Report - {drive}</title>\n\
+             <style>body{{font-family:sans-serif;margin:2em}}table{{border-collapse:collapse}}\
+             td,th{{border:1px solid #ccc;padding:4px 8px;text-align:left}}</style>\n\
+             </head>\n<body>\n<h1>Storage Distribution Analysis</h1>\n\
+             <p>Drive: {drive}<br>Date: {date}</p>\n",
+            drive = escape_html(drive),
+            date = format_time(Utc::now()),
+        );
+        if let Some(note) = self.depth_note(drive) {
+            let _ = writeln!(html, "<p>{}</p>", escape_html(&note));
+        }
+        if let Some(note) = self.scan_options_note(drive) {
+            let _ = writeln!(html, "<p>{}</p>", escape_html(&note));
+        }
+        if let Some(note) = self.unreadable_note(drive) {
+            let _ = writeln!(html, "<p>{}</p>", escape_html(&note));
+        }
+        if let Some(note) = self.timeout_note(drive) {
+            let _ = writeln!(html, "<p>{}</p>", escape_html(&note));
+        }
+        if self.is_follow_links_scan(drive) {
+            html.push_str("<p>Scanned with symlinks and junctions followed, sizes can overlap with other drives.</p>\n");
+        }
+
+        html.push_str("<h2>Drive Space Overview</h2>\n<table>\n");
+        let _ = writeln!(html, "<tr><th>Total Size</th><td>{}</td></tr>", format_gb(units, space.total_size));
+        let _ = writeln!(html, "<tr><th>Used Space</th><td>{}</td></tr>", format_gb(units, space.used_space));
+        let _ = writeln!(
+            html,
+            "<tr><th>Free Space</th><td>{} ({:.2}%)</td></tr>",
+            format_gb(units, space.free_space), space.free_space_percent
+        );
+        html.push_str("</table>\n");
+
+        html.push_str("<h2>Largest Folders</h2>\n<table>\n<tr><th>Folder</th><th>Size</th><th>Files</th></tr>\n");
+        for folder in folders.iter().take(top_n) {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+                escape_html(&folder.folder.to_string_lossy()), format_gb(units, bytes_to_gb(units, folder.size_bytes)), folder.file_count
+            );
+        }
+        html.push_str("</table>\n");
+
+        html.push_str("<h2>File Type Distribution</h2>\n<table>\n<tr><th>Extension</th><th>Count</th><th>Size</th><th>Recent</th></tr>\n");
+        for (ext, size, count, recent) in distribution.iter().take(top_n) {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                escape_html(ext), count, format_gb(units, bytes_to_gb(units, *size)), format_gb(units, bytes_to_gb(units, *recent))
+            );
+        }
+        html.push_str("</table>\n");
+
+        for (title, files) in [
+            ("Largest Files", &largest),
+            ("Recent Large Files", &recent),
+            (old_title.as_str(), &old),
+        ] {
+            let _ = write!(
+                html,
+                "<h2>{}</h2>\n<table>\n<tr><th>Path</th><th>Size</th><th>Last Modified</th></tr>\n",
+                title
+            );
+            for file in files.iter().take(top_n) {
+                let _ = writeln!(
+                    html,
+                    "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+                    escape_html(&file.full_path.to_string_lossy()),
+                    format_mb(units, bytes_to_mb(units, file.size_bytes)),
+                    file.last_modified.map(format_time).unwrap_or_else(|| "Unknown".to_string())
+                );
+            }
+            html.push_str("</table>\n");
+        }
+
+        html.push_str("</body>\n</html>\n");
+        Ok(html)
+    }
+}
+
+impl StorageAnalyzer {
+    /// Walks `drive` and writes every file to `writer` as it's found, one [`FileInfo`] JSON
+    /// object per line (NDJSON). Nothing is cached, so memory stays flat on any size of drive.
+    ///
+    /// A reader that hangs up early (`| head`) just ends the stream.
+    ///
+    /// ```
+    /// use rusty_analyser::StorageAnalyzer;
+    /// use std::fs;
+    ///
+    /// let dir = std::env::temp_dir().join("rusty-analyser-doctest-ndjson");
+    /// fs::create_dir_all(&dir)?;
+    /// fs::write(dir.join("a.txt"), "0123456789")?;
+    ///
+    /// let analyzer = StorageAnalyzer::new();
+    /// let mut out = Vec::new();
+    /// analyzer.stream_files_as_json(&dir.to_string_lossy(), &mut out)?;
+    ///
+    /// let lines: Vec<serde_json::Value> = String::from_utf8_lossy(&out)
+    ///     .lines()
+    ///     .map(|line| serde_json::from_str(line).unwrap())
+    ///     .collect();
+    /// assert_eq!(lines.len(), 1);
+    /// assert_eq!(lines[0]["size_bytes"], 10);
+    ///
+    /// fs::remove_dir_all(&dir)?;
+    /// # Ok::<(), rusty_analyser::AnalyzerError>(())
+    /// ```
+    ///
+    /// [`FileInfo`]: super::types::FileInfo
+    pub fn stream_files_as_json(&self, drive: &str, writer: &mut impl io::Write) -> Result<()> {
+        let drive = normalize_path(drive);
+        self.checked_scan_roots(&drive)?;
+        for file in self.file_stream(&drive) {
+            let line = serde_json::to_writer(&mut *writer, &file)
+                .map_err(io::Error::from)
+                .and_then(|_| writer.write_all(b"\n"));
+            match line {
+                Ok(()) => {}
+                Err(e) if e.kind() == ErrorKind::BrokenPipe => return Ok(()),
+                Err(e) => return Err(e.into()),
+            }
+        }
+        match writer.flush() {
+            Err(e) if e.kind() != ErrorKind::BrokenPipe => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    // the analyze_drive sections as one JSON object, for scripts and CI
+    pub fn analyze_drive_to_json(&mut self, drive: &str) -> Result<String> {
+        self.check_known_drive(drive)?;
+
+        let DriveReport { space, folders, distribution, largest, recent, old } = self.drive_report(drive)?;
+        let distribution: Vec<_> = distribution
+            .into_iter()
+            .map(|(extension, total_bytes, count, recent_bytes)| {
+                json!({
+                    "extension": extension,
+                    "total_bytes": total_bytes,
+                    "size": human_readable_size(Units::Binary, total_bytes),
+                    "count": count,
+                    "recent_bytes": recent_bytes,
+                })
+            })
+            .collect();
+
+        let report = json!({
+            "drive": drive,
+            "scope": if self.is_user_data_scan(drive) { "user-data" } else { "full" },
+            "follow_links": self.is_follow_links_scan(drive),
+            "max_depth": self.scan_depth(drive),
+            "unreadable": self.unreadable(drive).map_or(0, Unreadable::total),
+            "generated_at": Utc::now(),
+            "drive_space": space,
+            "largest_folders": folders,
+            "file_type_distribution": distribution,
+            "largest_files": largest,
+            "recent_large_files": recent,
+            "old_large_files": old,
+        });
+        Ok(serde_json::to_string_pretty(&report).map_err(Error::other)?)
+    }
+}
+
+// the ext-census list as extension,count,total_bytes,size rows
+pub fn export_extension_census(census: &TypeDistribution, path: &Path, units: Units) -> io::Result<()> {
+    let mut csv = String::from("extension,count,total_bytes,size\n");
+    for (ext, total_bytes, count, _) in census {
+        let _ = writeln!(csv, "{},{},{},{}", csv_field(ext), count, total_bytes, csv_field(&human_readable_size(units, *total_bytes)));
+    }
+    fs::write(path, csv)
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
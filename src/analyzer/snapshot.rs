@@ -0,0 +1,360 @@
+use super::{
+    config::{Config, ScanOptions},
+    constants::SNAPSHOT_DIR_NAME,
+    error::{AnalyzerError, Result},
+    printers,
+    storage::StorageAnalyzer,
+    types::{FileInfo, FolderSize},
+};
+use chrono::{DateTime, Utc};
+use log::warn;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{
+    cmp::Reverse,
+    fs,
+    io::{Error, ErrorKind},
+    path::{Path, PathBuf},
+};
+
+/// The envelope layout written today, bumped whenever it or a wrapped type changes shape.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// What every file the analyzer writes for itself to read back later is wrapped in.
+///
+/// Files from older versions are migrated on load, files from newer ones are refused
+/// with [`AnalyzerError::UnsupportedSchema`] instead of being half understood.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Envelope<T> {
+    pub schema_version: u32,
+    pub created_at: DateTime<Utc>,
+    // the Rusty-Analyser version that wrote it, "unknown" for files from before the envelope
+    pub tool_version: String,
+    pub drive: String,
+    pub data: T,
+}
+
+impl<T> Envelope<T> {
+    pub fn new(drive: &str, data: T) -> Self {
+        Envelope {
+            schema_version: SCHEMA_VERSION,
+            created_at: Utc::now(),
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            drive: drive.to_string(),
+            data,
+        }
+    }
+}
+
+impl<T: Serialize> Envelope<T> {
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self).map_err(Error::other)?)
+    }
+}
+
+impl<T: DeserializeOwned> Envelope<T> {
+    pub fn from_json(json: &str) -> Result<Self> {
+        let mut value: Value = serde_json::from_str(json)
+            .map_err(|e| AnalyzerError::InvalidArgument(format!("not a Rusty-Analyser file: {}", e)))?;
+        let version = match value.get("schema_version") {
+            Some(version) => version
+                .as_u64()
+                .and_then(|v| u32::try_from(v).ok())
+                .ok_or_else(|| AnalyzerError::InvalidArgument("schema_version has to be a whole number".to_string()))?,
+            None => 0,
+        };
+        if version > SCHEMA_VERSION {
+            return Err(AnalyzerError::UnsupportedSchema { found: version, supported: SCHEMA_VERSION });
+        }
+        for from in version..SCHEMA_VERSION {
+            value = migrate(value, from);
+        }
+        serde_json::from_value(value).map_err(|e| {
+            AnalyzerError::InvalidArgument(format!("file doesn't match schema {}: {}", SCHEMA_VERSION, e))
+        })
+    }
+}
+
+/// One drive's cache the way `snapshot save` writes it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DriveSnapshot {
+    pub drive: String,
+    pub scanned_at: DateTime<Utc>,
+    #[serde(default)]
+    pub options: ScanOptions,
+    // the scan timeout cut it short, snapshots from before this was saved read as complete
+    #[serde(default)]
+    pub timed_out: bool,
+    pub files: Vec<FileInfo>,
+    pub folders: Vec<FolderSize>,
+}
+
+/// (name, when it was saved, files across all of its drives)
+pub type SnapshotEntry = (String, DateTime<Utc>, usize);
+
+// the name ends up as a file name, so nothing that could leave the snapshot folder
+fn check_snapshot_name(name: &str) -> Result<()> {
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(AnalyzerError::InvalidArgument(format!(
+            "'{}' can't be a snapshot name, use letters, digits, - and _", name
+        )));
+    }
+    Ok(())
+}
+
+fn snapshot_path(name: &str) -> Result<PathBuf> {
+    check_snapshot_name(name)?;
+    Ok(snapshot_dir()?.join(format!("{}.json", name)))
+}
+
+fn snapshot_dir() -> Result<PathBuf> {
+    let dir = Config::data_dir()?.join(SNAPSHOT_DIR_NAME);
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+fn read_snapshot(path: &Path) -> Result<Envelope<Vec<DriveSnapshot>>> {
+    Envelope::from_json(&fs::read_to_string(path)?)
+}
+
+impl StorageAnalyzer {
+    /// Writes the cache of every scanned drive to `<data dir>/snapshots/<name>.json`,
+    /// replacing a snapshot of the same name. Fails when nothing has been scanned yet.
+    pub fn save_snapshot(&self, name: &str) -> Result<PathBuf> {
+        let path = snapshot_path(name)?;
+        let drives = self.cached_drives();
+        if drives.is_empty() {
+            return Err(AnalyzerError::InvalidArgument("nothing is cached yet, scan a drive first".to_string()));
+        }
+
+        let snapshots: Vec<DriveSnapshot> = drives
+            .iter()
+            .filter_map(|drive| {
+                let info = self.cache_info(drive)?;
+                Some(DriveSnapshot {
+                    drive: drive.clone(),
+                    scanned_at: info.scanned_at,
+                    options: info.options,
+                    timed_out: info.timed_out,
+                    files: self.cached_files(drive).unwrap_or_default().to_vec(),
+                    folders: self.cached_folders(drive).unwrap_or_default().to_vec(),
+                })
+            })
+            .collect();
+        fs::write(&path, Envelope::new(&drives.join(", "), snapshots).to_json()?)?;
+        Ok(path)
+    }
+
+    /// Replaces the cache of every drive in the snapshot `name` with what it saved, drives
+    /// that aren't in it keep their cache. Returns the drives that were restored.
+    ///
+    /// What a restored drive's cache replaced is kept as its previous scan, so folder-growth
+    /// compares the disk now against the snapshot after a rescan.
+    pub fn load_snapshot(&mut self, name: &str) -> Result<Vec<String>> {
+        let path = snapshot_path(name)?;
+        let envelope = match read_snapshot(&path) {
+            Err(AnalyzerError::Io(e)) if e.kind() == ErrorKind::NotFound => {
+                return Err(AnalyzerError::InvalidArgument(format!("no snapshot called '{}', see snapshot list", name)));
+            }
+            result => result?,
+        };
+
+        let mut restored = Vec::new();
+        for snapshot in envelope.data {
+            restored.push(snapshot.drive.clone());
+            self.restore_scan(snapshot);
+        }
+        Ok(restored)
+    }
+
+    /// Every saved snapshot, newest first. Files that don't read as one are skipped with a warning.
+    pub fn list_snapshots() -> Result<Vec<SnapshotEntry>> {
+        let mut snapshots = Vec::new();
+        for entry in fs::read_dir(snapshot_dir()?)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let name = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+            match read_snapshot(&path) {
+                Ok(envelope) => {
+                    let files = envelope.data.iter().map(|drive| drive.files.len()).sum();
+                    snapshots.push((name, envelope.created_at, files));
+                }
+                Err(e) => warn!("Skipping snapshot {}: {}", path.display(), e),
+            }
+        }
+        snapshots.sort_by_key(|&(_, saved_at, _)| Reverse(saved_at));
+        Ok(snapshots)
+    }
+
+    pub fn print_save_snapshot(&self, name: &str) -> Result<()> {
+        let path = self.save_snapshot(name)?;
+        Ok(printers::snapshot_saved(&mut self.out(), name, &self.cached_drives(), &path)?)
+    }
+
+    pub fn print_load_snapshot(&mut self, name: &str) -> Result<()> {
+        let drives = self.load_snapshot(name)?;
+        Ok(printers::snapshot_loaded(&mut self.out(), name, &drives)?)
+    }
+
+    pub fn print_snapshots(&self) -> Result<()> {
+        let snapshots = Self::list_snapshots()?;
+        Ok(printers::snapshots(&mut self.out(), &snapshots)?)
+    }
+}
+
+// takes a file from schema `from` to `from + 1`, one step per version so old files go up the whole chain
+fn migrate(value: Value, from: u32) -> Value {
+    match from {
+        // before the envelope files were just the data, when and by what is unknown
+        0 => json!({
+            "schema_version": 1,
+            "created_at": DateTime::<Utc>::UNIX_EPOCH,
+            "tool_version": "unknown",
+            "drive": "",
+            "data": value,
+        }),
+        _ => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::{test_util::{file, folder}, types::*};
+    use chrono::TimeZone;
+    use std::fmt::Debug;
+
+    fn round_trip<T: Serialize + DeserializeOwned + PartialEq + Debug>(value: &T) {
+        let json = serde_json::to_string(value).unwrap();
+        let back: T = serde_json::from_str(&json).unwrap();
+        assert_eq!(&back, value, "{}", json);
+    }
+
+    fn dated(path: &str) -> FileInfo {
+        FileInfo {
+            last_modified: Some(Utc.with_ymd_and_hms(2024, 2, 29, 23, 59, 59).unwrap()),
+            last_accessed: Some(Utc::now()),
+            ..file(path, 4_096)
+        }
+    }
+
+    #[test]
+    fn files_and_folders_round_trip() {
+        round_trip(&file("C:/Users/José/Música/canción.mp3", 7_340_032));
+        round_trip(&file("D:/日本語/ファイル.txt", 0));
+        round_trip(&file("C:/Users/Łukasz/Pulpit/😀.png", u64::MAX));
+        round_trip(&dated("C:/Temp/ümlaut.log"));
+        round_trip(&FolderSize {
+            folder: "C:/Users/Zoë/Документы".into(),
+            folder_lossy: false,
+            size_bytes: 123_456_789,
+            file_count: 42,
+            subfolder_count: 3,
+            largest_file: Some(dated("C:/Users/Zoë/Документы/отчёт.pdf")),
+        });
+        // snapshots from before subfolder_count and largest_file still load
+        let old: FolderSize = serde_json::from_str(r#"{"folder":"C:/a","size_bytes":10,"file_count":1}"#).unwrap();
+        assert_eq!(old, folder("C:/a", 10));
+    }
+
+    #[test]
+    fn result_types_round_trip() {
+        round_trip(&DriveAnalysis {
+            drive: "C:/".to_string(),
+            label: Some("Système".to_string()),
+            total_size: 476.9,
+            used_space: 401.25,
+            free_space: 75.65,
+            free_space_percent: 15.86,
+        });
+        // snapshots from before drive and label still load
+        let old: DriveAnalysis =
+            serde_json::from_str(r#"{"total_size":1.0,"used_space":0.5,"free_space":0.5,"free_space_percent":50.0}"#).unwrap();
+        assert_eq!((old.drive.as_str(), old.label), ("", None));
+        round_trip(&CleanupCandidate {
+            path: "C:/Users/Ana/AppData/Local/Temp/résumé.tmp".into(),
+            path_lossy: false,
+            size_bytes: 2_048,
+            reason: "temp file".to_string(),
+            confidence: Confidence::High,
+        });
+        round_trip(&CompressionCandidate { file: dated("C:/logs/día.log"), estimated_ratio: 0.25, estimated_savings_bytes: 3_072 });
+        round_trip(&ExtensionStats { drive: "C:/".to_string(), extension: "mp4".to_string(), count: 3, size_bytes: 9_000 });
+        round_trip(&ScanError { path: "C:/broken/ø".to_string(), code: 1392 });
+        round_trip(&AclSummary { has_everyone_access: true, ace_count: 4, inherits_parent: false });
+        for category in FileCategory::ALL {
+            round_trip(&category);
+        }
+    }
+
+    #[test]
+    fn names_that_arent_unicode_are_flagged() {
+        use std::{ffi::OsString, os::windows::ffi::OsStringExt};
+        // "C:/a" followed by an unpaired high surrogate
+        let name = OsString::from_wide(&[0x43, 0x3A, 0x2F, 0x61, 0xD800]);
+        let json = serde_json::to_value(FileInfo { full_path: name.into(), ..file("", 1) }).unwrap();
+        assert_eq!(json["full_path"], "C:/a\u{FFFD}");
+        assert_eq!(json["full_path_lossy"], true);
+        // the name read back is only the replacement, saving it again keeps the flag
+        let back: FileInfo = serde_json::from_value(json).unwrap();
+        assert!(back.full_path_lossy);
+        assert_eq!(serde_json::to_value(back).unwrap()["full_path_lossy"], true);
+
+        let json = serde_json::to_value(file("C:/ok.txt", 1)).unwrap();
+        assert!(json.get("full_path_lossy").is_none());
+    }
+
+    #[test]
+    fn envelope_round_trips() {
+        let envelope = Envelope::new("C:/", vec![file("C:/ñ.bin", 1), dated("C:/å.bin")]);
+        let back = Envelope::<Vec<FileInfo>>::from_json(&envelope.to_json().unwrap()).unwrap();
+        assert_eq!(back, envelope);
+        assert_eq!(back.schema_version, SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn bare_data_from_before_the_envelope_is_migrated() {
+        let legacy = serde_json::to_string(&vec![file("C:/old.txt", 10)]).unwrap();
+        let envelope = Envelope::<Vec<FileInfo>>::from_json(&legacy).unwrap();
+        assert_eq!(envelope.data, vec![file("C:/old.txt", 10)]);
+        assert_eq!(envelope.tool_version, "unknown");
+    }
+
+    #[test]
+    fn drive_snapshots_round_trip() {
+        let snapshot = DriveSnapshot {
+            drive: "D:/".to_string(),
+            scanned_at: Utc.with_ymd_and_hms(2024, 6, 1, 12, 0, 0).unwrap(),
+            options: ScanOptions { max_depth: Some(4), ..ScanOptions::default() },
+            timed_out: true,
+            files: vec![dated("D:/Fotos/año.jpg")],
+            folders: vec![folder("D:/Fotos", 4_096)],
+        };
+        let envelope = Envelope::new("D:/", vec![snapshot]);
+        let back = Envelope::<Vec<DriveSnapshot>>::from_json(&envelope.to_json().unwrap()).unwrap();
+        assert_eq!(back, envelope);
+    }
+
+    #[test]
+    fn snapshot_names_stay_inside_the_snapshot_folder() {
+        for name in ["", "../escape", "a/b", "C:", "with space"] {
+            assert!(matches!(check_snapshot_name(name), Err(AnalyzerError::InvalidArgument(_))), "{}", name);
+        }
+        assert!(check_snapshot_name("before-cleanup_2").is_ok());
+    }
+
+    #[test]
+    fn newer_schemas_are_refused() {
+        let future = json!({
+            "schema_version": SCHEMA_VERSION + 1,
+            "created_at": Utc::now(),
+            "tool_version": "9.0.0",
+            "drive": "C:/",
+            "data": [],
+        });
+        let err = Envelope::<Vec<FileInfo>>::from_json(&future.to_string()).unwrap_err();
+        assert!(matches!(err, AnalyzerError::UnsupportedSchema { found, .. } if found == SCHEMA_VERSION + 1));
+    }
+}
@@ -1,322 +1,2075 @@
 use super::{
+    error::{AnalyzerError, Result},
+    cleanup,
     constants::*,
+    config::{Config, ScanOptions, Units},
+    exclude::{scan_glob_set, ExcludeList},
+    progress::{scan_cancelled, LiveUi, ScanDeadline, ScanGuard, ScanProgress},
+    metrics::*,
+    output::{Out, OutputCapture},
+    platform::{validate_volume_label, DriveKind, DrivePlatform, WinApiDrives},
+    printers::{self, ReportLayout},
+    snapshot::DriveSnapshot,
     utils::*,
     types::* 
 };
-use chrono::{DateTime, Duration, NaiveDateTime, TimeZone, Utc};
-use rayon::{prelude::*, ThreadPoolBuilder};
+use chrono::{DateTime, Datelike, Duration, TimeZone, Utc};
+use rayon::{prelude::*, ThreadPool, ThreadPoolBuilder};
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
+    env,
     ffi::{OsStr, OsString},
-    io::{self, Error},
+    fs,
+    io::{self, Error, ErrorKind},
     os::windows::ffi::{OsStrExt, OsStringExt},
-    path::Path,
+    path::{Path, PathBuf},
+    thread,
     time::{
-        SystemTime, UNIX_EPOCH},
+        Instant, SystemTime, UNIX_EPOCH},
     sync::{
-        Arc, Mutex}
-};
-use walkdir::WalkDir;
-use winapi::um::{
-    fileapi::{GetDiskFreeSpaceExW, GetDriveTypeW, GetLogicalDriveStringsW},
-    winbase::DRIVE_FIXED,
+        atomic::Ordering, Arc, Mutex, PoisonError, RwLock}
 };
+use globset::{GlobBuilder, GlobMatcher, GlobSet};
+use log::{info, log, warn, Level};
+use walkdir::{DirEntry, WalkDir};
+
+
+// what one walk of a drive found, kept apart from the caches until the walk is known to be complete
+pub(crate) struct DriveScan {
+    files: Vec<FileInfo>,
+    folders: Vec<FolderSize>,
+    excluded: usize,
+    system_dirs: Vec<String>,
+    scan_errors: Vec<ScanError>,
+    unreadable: Unreadable,
+    links: usize,
+    max_depth: Option<usize>,
+}
+
+// a link to a directory whose target was already walked, skipping it keeps loops and several
+// junctions to the same place from being counted more than once
+fn is_repeat_link(visited: &mut HashSet<PathBuf>, entry: &DirEntry) -> bool {
+    if !entry.path_is_symlink() || !entry.file_type().is_dir() {
+        return false;
+    }
+    match fs::canonicalize(entry.path()) {
+        Ok(target) => !visited.insert(target),
+        // a dangling link has nothing to walk into
+        Err(_) => true,
+    }
+}
+
+fn sort_by_size<T: HasSize + Send>(items: &mut [T], order: SortOrder) {
+    items.par_sort_unstable_by(|a, b| {
+        let ordering = BySize(a).cmp(&BySize(b));
+        match order {
+            SortOrder::Descending => ordering.reverse(),
+            SortOrder::Ascending => ordering,
+        }
+    });
+}
+
+// every category with its file count and bytes, biggest first, empty ones included so the table is always whole
+fn category_totals(files: &[FileInfo]) -> CategoryTotals {
+    let mut totals: CategoryTotals = FileCategory::ALL.iter().map(|&category| (category, 0, 0)).collect();
+    for file in files {
+        let category = FileCategory::of_path(&file.full_path);
+        if let Some((_, count, bytes)) = totals.iter_mut().find(|(c, _, _)| *c == category) {
+            *count += 1;
+            *bytes += file.size_bytes;
+        }
+    }
+    totals.sort_by_key(|(_, _, bytes)| std::cmp::Reverse(*bytes));
+    totals
+}
 
+fn ranking_title(key: SortKey) -> &'static str {
+    match key {
+        SortKey::Size => "Largest Files",
+        SortKey::Modified => "Files By Last Modified",
+        SortKey::Accessed => "Files By Last Accessed",
+        SortKey::Created => "Files By Creation Time",
+        SortKey::Path => "Files By Path",
+    }
+}
+
+// the first `n` of `items` under `compare`, in order, without sorting everything behind them
+fn first_n_by<T>(mut items: Vec<T>, n: usize, compare: impl Fn(&T, &T) -> std::cmp::Ordering) -> Vec<T> {
+    if n == 0 {
+        return Vec::new();
+    }
+    if n < items.len() {
+        items.select_nth_unstable_by(n - 1, &compare);
+        items.truncate(n);
+    }
+    items.sort_unstable_by(compare);
+    items
+}
+
+// (path, bytes, percent of `used_bytes`) biggest first
+fn space_shares(mut folders: Vec<FolderSize>, used_bytes: u64) -> Vec<(PathBuf, u64, f64)> {
+    sort_by_size(&mut folders, SortOrder::Descending);
+    folders
+        .into_iter()
+        .map(|folder| {
+            let percent = folder.size_bytes as f64 / used_bytes.max(1) as f64 * 100.0;
+            (folder.folder, folder.size_bytes, percent)
+        })
+        .collect()
+}
+
+// the first folder below `drive` that `path` is in, the drive itself for files at its root
+fn top_level_folder(drive: &str, path: &Path) -> PathBuf {
+    path.strip_prefix(drive)
+        .ok()
+        .filter(|rel| rel.components().count() > 1)
+        .and_then(|rel| rel.components().next())
+        .map(|first| Path::new(drive).join(first))
+        .unwrap_or_else(|| PathBuf::from(drive))
+}
 
+// what a cached scan left out, a cache made one way can't answer for another
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ScanMode {
+    no_exclude: bool,
+    include_system: bool,
+    user_data: bool,
+    follow_links: bool,
+    min_file_bytes: Option<u64>,
+}
+
+/// Scans drives or folders and answers questions about what takes up the space.
+///
+/// Every query scans its drive the first time and reuses that scan after, so asking for
+/// several reports about the same drive only walks it once. `drives` holds the fixed drives
+/// found at startup (or by the last [`refresh_drives`](Self::refresh_drives)), but any folder path
+/// works wherever a drive is expected.
 pub struct StorageAnalyzer {
     pub drives: Vec<String>,
     file_cache: HashMap<String, Vec<FileInfo>>,
-    folder_cache: HashMap<String, Vec<FolderSize>>
+    folder_cache: HashMap<String, Vec<FolderSize>>,
+    metrics: MetricsRegistry,
+    metrics_drives: SharedDrives,
+    // what the print_* methods write through, see out()
+    output: OutputCapture,
+    // KiB/MiB/GiB (1024) when set, KB/MB/GB (1000) otherwise, for everything this analyzer prints
+    use_iec: bool,
+    // bumped whenever a cache changes, so a listing kept from before can tell it's stale
+    cache_generation: u64,
+    last_listing: Vec<PathBuf>,
+    // single pass queries walk the disk directly instead of filling the cache
+    use_streaming: bool,
+    config: Config,
+    excludes: ExcludeList,
+    // the `[scan] exclude` globs from the config
+    scan_excludes: GlobSet,
+    // set for a single command by --no-exclude, nothing is skipped while it's on
+    no_exclude: bool,
+    // set for a single command by --include-system
+    include_system: bool,
+    // folders starting with a dot (~/.cache and the like) are left out of folder listings without it
+    include_hidden: bool,
+    // set for a single command by --user-data
+    user_data: bool,
+    // set for a single command by --follow-links or a profile, [scan] follow_links decides while it's None
+    follow_links: Option<bool>,
+    // how many symlinks and junctions each drive's last scan walked into
+    links_followed: HashMap<String, usize>,
+    // set for a single command by --max-depth, otherwise [scan] max_depth is used
+    max_depth: Option<usize>,
+    // set for a single command by a profile's min_file_size, smaller files never make it into the cache
+    min_file_bytes: Option<u64>,
+    // what the builder was given, every command's options fall back to these
+    base_options: ScanOptions,
+    // the current command's options after falling back to base_options
+    options: ScanOptions,
+    // what each drive's cached scan ran with, config defaults filled in
+    scan_options: HashMap<String, ScanOptions>,
+    // when each drive's cache was filled, for [scan] cache_ttl_minutes, and the wall clock time for cache_info
+    cached_at: HashMap<String, (Instant, DateTime<Utc>)>,
+    // the folder sizes of the scan each drive's cache replaced, when that one was done and what it
+    // left out, for folder-growth
+    previous_folders: HashMap<String, (Instant, Option<ScanMode>, Vec<FolderSize>)>,
+    // depth each drive's cache was limited to, drives scanned all the way down aren't in here
+    scan_depths: HashMap<String, usize>,
+    // asked before a depth-limited cache is thrown away for a deeper scan, without it the rescan just happens
+    rescan_prompt: Option<fn(&str) -> bool>,
+    // how the scan each drive's cache came from was done
+    scan_modes: HashMap<String, ScanMode>,
+    // system dirs left out of each drive's last scan
+    skipped_system_dirs: HashMap<String, Vec<String>>,
+    // scans run here instead of the global pool once tune-threads picked a count
+    thread_pool: Option<Arc<ThreadPool>>,
+    // scan progress is only logged at debug level, for when the output is meant for machine readable use
+    quiet: bool,
+    // redraw a live counter table during scans instead of only printing when they're done
+    live_ui: bool,
+    // refuses every command that deletes or moves files, for auditing a drive that isn't yours
+    read_only: bool,
+    // how many entries the exclude list kept out of each drive's cache
+    excluded_counts: HashMap<String, usize>,
+    scan_errors: HashMap<String, Vec<ScanError>>,
+    // everything each drive's last scan had to skip because it couldn't be read
+    unreadable: HashMap<String, Unreadable>,
+    // scans give up after this long and cache what they got, for network drives that never finish
+    scan_timeout: Option<std::time::Duration>,
+    // drives whose cached scan the timeout cut short
+    timed_out_scans: HashSet<String>,
+    // drive listing and free space, the winapi unless a test swapped it out
+    platform: Box<dyn DrivePlatform>,
 }
 
-impl StorageAnalyzer {
-    pub fn new() -> Self {
-        let drives = Self::list_drives();
-        StorageAnalyzer {
-            drives,
-            file_cache: HashMap::new(),
-            folder_cache: HashMap::new(),
+impl Default for StorageAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StorageAnalyzer {
+    /// An analyzer with [`Config::default`] and nothing else changed, nothing is scanned yet,
+    /// that happens on the first query for each drive.
+    ///
+    /// [`StorageAnalyzer::builder`] is the way to start from a loaded config or change anything.
+    pub fn new() -> Self {
+        Self::with_config(Config::default())
+    }
+
+    /// Like [`new`](Self::new) with `config` in place of the defaults, none of the builder's
+    /// checks are run on it.
+    pub fn with_config(config: Config) -> Self {
+        Self::with_platform(config, Box::new(WinApiDrives))
+    }
+
+    /// Like [`with_config`](Self::with_config), with drives and their space coming from `platform` instead of the OS.
+    pub fn with_platform(config: Config, platform: Box<dyn DrivePlatform>) -> Self {
+        set_time_display(&config.display.date_format, config.display.utc);
+        if let Some(threads) = config.scan.threads {
+            // only fails if rayon was already started, which means the default pool is in use
+            let _ = ThreadPoolBuilder::new().num_threads(threads).build_global();
+        }
+        // broken patterns shouldn't stop the program, they just mean nothing is excluded
+        let excludes = ExcludeList::new(&config.exclude).unwrap_or_else(|e| {
+            warn!("Ignoring exclude list: {}", e);
+            ExcludeList::default()
+        });
+        let scan_excludes = scan_glob_set(&config.scan.exclude).unwrap_or_else(|e| {
+            warn!("Ignoring scan exclusions: {}", e);
+            GlobSet::empty()
+        });
+
+        let drives = Self::drives_of_kind(platform.as_ref(), DriveKind::Fixed);
+        let use_iec = config.display.units == Units::Binary;
+        StorageAnalyzer {
+            metrics_drives: Arc::new(RwLock::new(drives.clone())),
+            drives,
+            file_cache: HashMap::new(),
+            folder_cache: HashMap::new(),
+            metrics: MetricsRegistry::default(),
+            output: OutputCapture::default(),
+            use_iec,
+            cache_generation: 0,
+            last_listing: Vec::new(),
+            use_streaming: false,
+            config,
+            excludes,
+            scan_excludes,
+            no_exclude: false,
+            include_system: false,
+            include_hidden: false,
+            user_data: false,
+            follow_links: None,
+            links_followed: HashMap::new(),
+            max_depth: None,
+            min_file_bytes: None,
+            base_options: ScanOptions::default(),
+            options: ScanOptions::default(),
+            scan_options: HashMap::new(),
+            cached_at: HashMap::new(),
+            previous_folders: HashMap::new(),
+            scan_depths: HashMap::new(),
+            rescan_prompt: None,
+            thread_pool: None,
+            scan_modes: HashMap::new(),
+            skipped_system_dirs: HashMap::new(),
+            quiet: false,
+            live_ui: false,
+            read_only: false,
+            excluded_counts: HashMap::new(),
+            scan_errors: HashMap::new(),
+            unreadable: HashMap::new(),
+            scan_timeout: None,
+            timed_out_scans: HashSet::new(),
+            platform,
+        }
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn config_mut(&mut self) -> &mut Config {
+        &mut self.config
+    }
+
+    // swaps in a freshly loaded config and rebuilds everything that came from the old one
+    // scan.threads is the exception, the thread pool can't be resized once it's running
+    pub fn apply_config(&mut self, config: Config) -> Result<()> {
+        let excludes = ExcludeList::new(&config.exclude)?;
+        let scan_excludes = scan_glob_set(&config.scan.exclude)?;
+        self.set_units(config.display.units);
+        set_time_display(&config.display.date_format, config.display.utc);
+        // scans done with the old globs could be missing files, or have ones that are now excluded
+        if config.scan.exclude != self.config.scan.exclude {
+            let drives: Vec<String> = self.file_cache.keys().chain(self.folder_cache.keys()).cloned().collect();
+            for drive in drives {
+                self.drop_cached_scan(&drive);
+            }
+        }
+        self.scan_excludes = scan_excludes;
+        self.config = config;
+        self.set_excludes(excludes);
+        Ok(())
+    }
+
+    pub fn set_use_streaming(&mut self, enabled: bool) {
+        self.use_streaming = enabled;
+    }
+
+    // switches between IEC (KiB, 1024) and SI (KB, 1000) for the rest of the session
+    // cached sizes are plain bytes, so only the display changes
+    pub fn set_units(&mut self, new_units: Units) {
+        self.config.display.units = new_units;
+        self.use_iec = new_units == Units::Binary;
+    }
+
+    /// The units sizes are shown in, hand these to the `utils` size formatters.
+    pub fn units(&self) -> Units {
+        if self.use_iec { Units::Binary } else { Units::Decimal }
+    }
+
+    // the global pool can only be sized once, so this gives scans a pool of their own
+    pub fn set_thread_count(&mut self, threads: usize) -> Result<()> {
+        let pool = ThreadPoolBuilder::new().num_threads(threads).build().map_err(Error::other)?;
+        self.thread_pool = Some(Arc::new(pool));
+        self.config.scan.threads = Some(threads);
+        Ok(())
+    }
+
+    // rescans the drive once per thread count, prints how long each took and keeps the fastest
+    // the first run also warms the OS file cache, so it tends to look slower than it is
+    pub fn auto_tune_threads(&mut self, drive: &str) -> Result<usize> {
+        let drive = normalize_path(drive);
+        let cores = thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+        let mut counts = vec![1, 2, 4, 8, cores, cores * 2];
+        counts.sort_unstable();
+        counts.dedup();
+
+        let quiet = self.quiet;
+        self.quiet = true;
+        let mut results = Vec::new();
+        for &threads in &counts {
+            self.set_thread_count(threads)?;
+            self.drop_cached_scan(&drive);
+            let start = Instant::now();
+            if let Err(e) = self.collect_and_cache_files(&drive) {
+                self.quiet = quiet;
+                return Err(e);
+            }
+            let elapsed = start.elapsed();
+            if let Err(e) = printers::thread_timing(&mut self.out(), threads, elapsed) {
+                self.quiet = quiet;
+                return Err(e.into());
+            }
+            results.push((threads, elapsed));
+        }
+        self.quiet = quiet;
+
+        let (best, _) = results.into_iter().min_by_key(|(_, elapsed)| *elapsed).unwrap_or((cores, Default::default()));
+        self.set_thread_count(best)?;
+        Ok(best)
+    }
+
+    // the options for the next command, already merged from its flags and the active profile
+    pub fn set_scan_options(&mut self, options: &ScanOptions) {
+        let options = options.clone().or(&self.base_options);
+        self.no_exclude = options.no_exclude.unwrap_or(false);
+        self.include_system = options.include_system.unwrap_or(false);
+        self.user_data = options.user_data.unwrap_or(false);
+        self.follow_links = options.follow_links;
+        self.max_depth = options.max_depth;
+        // profiles were checked when the config loaded, so a size that doesn't parse can't get here
+        self.min_file_bytes = options.min_file_size.as_deref().and_then(|size| parse_size(size, self.units()).ok());
+        self.options = options;
+    }
+
+    // every option spelled out, the way a scan started now would run
+    fn options_in_effect(&self) -> ScanOptions {
+        ScanOptions {
+            max_depth: self.effective_max_depth(),
+            min_file_size: self.options.min_file_size.clone(),
+            follow_links: Some(self.follows_links()),
+            include_system: Some(self.include_system),
+            user_data: Some(self.user_data),
+            no_exclude: Some(self.no_exclude),
+        }
+    }
+
+    pub(crate) fn set_base_options(&mut self, options: ScanOptions) {
+        self.base_options = options;
+        self.set_scan_options(&ScanOptions::default());
+    }
+
+    pub fn set_rescan_prompt(&mut self, prompt: fn(&str) -> bool) {
+        self.rescan_prompt = Some(prompt);
+    }
+
+    fn effective_max_depth(&self) -> Option<usize> {
+        self.max_depth.or(self.config.scan.max_depth)
+    }
+
+    pub fn scan_depth(&self, drive: &str) -> Option<usize> {
+        self.scan_depths.get(&normalize_path(drive)).copied()
+    }
+
+    // "(scan limited to depth 6)" when the cached scan of `drive` didn't go all the way down
+    pub fn depth_note(&self, drive: &str) -> Option<String> {
+        self.scan_depth(drive).map(|depth| format!("(scan limited to depth {})", depth))
+    }
+
+    // "scanned with max_depth = 6, follow_links = false, .." for the cached scan of `drive`
+    pub fn scan_options_note(&self, drive: &str) -> Option<String> {
+        self.scan_options.get(&normalize_path(drive)).map(|options| format!("scanned with {}", options.describe()))
+    }
+
+    // forgets the cached scan of `drive`, its folder sizes are kept as the previous scan
+    fn drop_cached_scan(&mut self, drive: &str) {
+        self.cache_generation += 1;
+        self.timed_out_scans.remove(drive);
+        self.file_cache.remove(drive);
+        if let Some(folders) = self.folder_cache.remove(drive) {
+            let scanned_at = self.cached_at.get(drive).map_or_else(Instant::now, |(at, _)| *at);
+            let mode = self.scan_modes.get(drive).copied();
+            self.previous_folders.insert(drive.to_string(), (scanned_at, mode, folders));
+        }
+    }
+
+    // drops whatever was cached for `drive` and scans it again with the current options
+    pub fn rescan(&mut self, drive: &str) -> Result<()> {
+        let drive = normalize_path(drive);
+        self.drop_cached_scan(&drive);
+        self.collect_and_cache_files(&drive)?;
+        Ok(())
+    }
+
+    fn follows_links(&self) -> bool {
+        self.follow_links.unwrap_or(self.config.scan.follow_links)
+    }
+
+    fn scan_mode(&self) -> ScanMode {
+        ScanMode {
+            no_exclude: self.no_exclude,
+            include_system: self.include_system,
+            user_data: self.user_data,
+            follow_links: self.follows_links(),
+            min_file_bytes: self.min_file_bytes,
+        }
+    }
+
+    // whether the cached scan of `drive` walked into links, its sizes can overlap with other drives
+    pub fn is_follow_links_scan(&self, drive: &str) -> bool {
+        self.scan_modes.get(&normalize_path(drive)).is_some_and(|mode| mode.follow_links)
+    }
+
+    // whether the cached scan of `drive` only covered user data
+    pub fn is_user_data_scan(&self, drive: &str) -> bool {
+        self.scan_modes.get(&normalize_path(drive)).is_some_and(|mode| mode.user_data)
+    }
+
+    // the current user's profile and [scan] user_data_roots, whichever are on `drive`
+    fn user_data_roots(&self, drive: &str) -> Vec<String> {
+        let drive = comparable_path(drive);
+        let roots = env::var("USERPROFILE")
+            .ok()
+            .into_iter()
+            .chain(self.config.scan.user_data_roots.iter().cloned())
+            .map(|root| normalize_path(&root))
+            .filter(|root| is_under(&comparable_path(root), &drive))
+            .collect();
+        outermost_roots(roots)
+    }
+
+    // where walks of `drive` start, the drive itself unless --user-data was given
+    pub(crate) fn scan_roots(&self, drive: &str) -> Vec<String> {
+        if self.user_data {
+            self.user_data_roots(drive)
+        } else {
+            vec![drive.to_string()]
+        }
+    }
+
+    // only straight below a drive root, a Windows folder inside a scanned folder is just a folder
+    pub(crate) fn is_system_dir(&self, entry: &DirEntry) -> bool {
+        self.config.scan.skip_system_dirs
+            && !self.include_system
+            && !self.user_data
+            && entry.depth() == 1
+            && entry.path().parent().is_some_and(|root| root.parent().is_none())
+            && entry.file_type().is_dir()
+            && SYSTEM_DIRS.iter().any(|dir| entry.file_name().eq_ignore_ascii_case(dir))
+    }
+
+    // dot folders are scanned and cached like any other, they only drop out when folders are listed
+    fn is_hidden_folder(&self, path: &Path) -> bool {
+        !self.include_hidden && path.file_name().and_then(|name| name.to_str()).is_some_and(|name| name.starts_with('.'))
+    }
+
+    // whether a walk should leave this entry (and everything below it) out
+    pub(crate) fn is_skipped(&self, path: &Path) -> bool {
+        !self.no_exclude
+            && (self.excludes.matches(path)
+                || self.scan_excludes.is_match(normalize_path(&path.to_string_lossy())))
+    }
+
+    fn file_info_from_entry(entry: &DirEntry) -> walkdir::Result<FileInfo> {
+        let metadata = entry.metadata()?;
+        Ok(FileInfo {
+            full_path: entry.path().to_path_buf(),
+            full_path_lossy: false,
+            size_bytes: metadata.len(),
+            last_modified: metadata.modified().ok().map(DateTime::<Utc>::from),
+            last_accessed: metadata.accessed().ok().map(DateTime::<Utc>::from),
+            created: metadata.created().ok().map(DateTime::<Utc>::from),
+        })
+    }
+
+    // walks the drive lazily, nothing is cached and each file is only looked at once
+    pub fn file_stream<'a>(&'a self, drive: &str) -> impl Iterator<Item = FileInfo> + 'a {
+        self.scan_roots(drive).into_iter().flat_map(move |root| {
+            WalkDir::new(root)
+                .into_iter()
+                .filter_entry(move |e| !self.is_system_dir(e) && !self.is_skipped(e.path()))
+                .filter_map(Result::ok)
+                .filter(|e| e.file_type().is_file())
+                .filter_map(|entry| Self::file_info_from_entry(&entry).ok())
+        })
+    }
+
+    // stdout for the reports, copied into the capture while one is running
+    pub(crate) fn out(&self) -> Out<'_> {
+        Out::new(&self.output)
+    }
+
+    /// Starts keeping a copy of everything the reports print, until [`finish_capture`](Self::finish_capture).
+    pub fn start_capture(&self) {
+        self.output.start();
+    }
+
+    /// What the reports printed since [`start_capture`](Self::start_capture), as plain text.
+    pub fn finish_capture(&self) -> String {
+        self.output.finish()
+    }
+
+    /// Changes every time a cached scan is stored, dropped or patched by a delete or move, a
+    /// listing saved alongside it is out of date once this differs.
+    pub fn cache_generation(&self) -> u64 {
+        self.cache_generation
+    }
+
+    // handle for the metrics endpoint, updated after every scan
+    pub fn metrics_registry(&self) -> MetricsRegistry {
+        Arc::clone(&self.metrics)
+    }
+
+    // the drive list for the metrics endpoint, it follows refresh_drives while the server runs
+    pub fn metrics_drives(&self) -> SharedDrives {
+        Arc::clone(&self.metrics_drives)
+    }
+
+    pub(crate) fn publish_drives(&self) {
+        *self.metrics_drives.write().unwrap_or_else(PoisonError::into_inner) = self.drives.clone();
+    }
+
+    // normalized roots of the drives the platform reports as `kind`
+    fn drives_of_kind(platform: &dyn DrivePlatform, kind: DriveKind) -> Vec<String> {
+        platform
+            .list_drives()
+            .into_iter()
+            .filter(|(_, k)| *k == kind)
+            .map(|(drive, _)| normalize_path(&drive))
+            .collect()
+    }
+
+    // USB sticks, card readers and the like, they aren't in `drives` but can be analyzed all the same
+    pub fn list_removable_drives(&self) -> Vec<String> {
+        Self::drives_of_kind(self.platform.as_ref(), DriveKind::Removable)
+    }
+
+    // the full drive reports take a fixed drive or a removable one that's plugged in right now
+    pub(crate) fn check_known_drive(&self, drive: &str) -> Result<()> {
+        if self.drives.iter().any(|d| d == drive) {
+            return Ok(());
+        }
+        let removable = self.list_removable_drives();
+        if removable.iter().any(|d| d == drive) {
+            return Ok(());
+        }
+        let mut valid = self.drives.clone();
+        valid.extend(removable);
+        Err(AnalyzerError::DriveNotFound { drive: drive.to_string(), valid })
+    }
+
+    /// Lists the drives again so disks plugged in after startup show up, returns the (added,
+    /// removed) drives. New fixed drives join `drives`, removable ones added by hand stay as long
+    /// as they're still plugged in, and whatever was cached for a drive that's gone is dropped.
+    pub fn refresh_drives(&mut self) -> (Vec<String>, Vec<String>) {
+        let present: Vec<String> = self.platform.list_drives().iter().map(|(drive, _)| normalize_path(drive)).collect();
+        let (kept, removed): (Vec<String>, Vec<String>) = self.drives.drain(..).partition(|d| present.contains(d));
+        let added: Vec<String> = Self::drives_of_kind(self.platform.as_ref(), DriveKind::Fixed)
+            .into_iter()
+            .filter(|d| !kept.contains(d))
+            .collect();
+        self.drives = kept;
+        self.drives.extend(added.iter().cloned());
+
+        for drive in &removed {
+            self.forget_drive(drive);
+        }
+        self.publish_drives();
+        if !added.is_empty() || !removed.is_empty() {
+            info!("Drives: {}", self.drives.join(", "));
+        }
+        (added, removed)
+    }
+
+    // everything cached for `drive` or a folder on it, for when the drive isn't there anymore
+    fn forget_drive(&mut self, drive: &str) {
+        let on_drive = |key: &String| Path::new(key).starts_with(drive);
+        self.cache_generation += 1;
+        self.file_cache.retain(|key, _| !on_drive(key));
+        self.folder_cache.retain(|key, _| !on_drive(key));
+        self.cached_at.retain(|key, _| !on_drive(key));
+        self.previous_folders.retain(|key, _| !on_drive(key));
+        self.scan_depths.retain(|key, _| !on_drive(key));
+        self.scan_modes.retain(|key, _| !on_drive(key));
+        self.scan_options.retain(|key, _| !on_drive(key));
+        self.skipped_system_dirs.retain(|key, _| !on_drive(key));
+        self.excluded_counts.retain(|key, _| !on_drive(key));
+        self.scan_errors.retain(|key, _| !on_drive(key));
+        self.unreadable.retain(|key, _| !on_drive(key));
+        self.links_followed.retain(|key, _| !on_drive(key));
+        self.timed_out_scans.retain(|key| !on_drive(key));
+        lock_registry(&self.metrics).retain(|key, _| !on_drive(key));
+    }
+
+    pub fn print_refresh_drives(&mut self) -> Result<()> {
+        let (added, removed) = self.refresh_drives();
+        Ok(printers::refresh_drives(&mut self.out(), &added, &removed, &self.drives)?)
+    }
+
+    /// Total, used and free space of `drive` in GB, straight from the OS, nothing is scanned.
+    ///
+    /// A drive reporting a size of zero or more free space than it holds is
+    /// [`AnalyzerError::DriveUnavailable`], there's no percentage worth showing for it.
+    pub fn get_drive_space(&self, drive: &str) -> Result<DriveAnalysis> {
+        let (total_bytes, free_bytes) = self.drive_space_bytes(drive)?;
+        if total_bytes == 0 || free_bytes > total_bytes {
+            return Err(AnalyzerError::DriveUnavailable(normalize_path(drive)));
+        }
+        let label = self.platform.drive_label(drive);
+        Ok(DriveAnalysis::from_bytes(&normalize_path(drive), label, total_bytes, free_bytes, self.units()))
+    }
+
+    /// Every drive the OS reports, of any kind, with its space when it could be read (an empty
+    /// card reader or CD drive has none).
+    pub fn list_all_drives(&self) -> Vec<(String, DriveKind, Option<DriveAnalysis>)> {
+        self.platform
+            .list_drives()
+            .into_iter()
+            .map(|(drive, kind)| {
+                let space = self.get_drive_space(&drive).ok();
+                (normalize_path(&drive), kind, space)
+            })
+            .collect()
+    }
+
+    pub fn print_drives(&self) -> Result<()> {
+        Ok(printers::drives(&mut self.out(), self.units(), &self.list_all_drives())?)
+    }
+
+    /// The volume label of `drive`, `None` when it has none or it couldn't be read.
+    pub fn drive_label(&self, drive: &str) -> Option<String> {
+        self.platform.drive_label(drive)
+    }
+
+    /// Renames the volume of `drive` after checking `label` is one NTFS accepts.
+    pub fn set_drive_label(&self, drive: &str, label: &str) -> Result<()> {
+        validate_volume_label(label)?;
+        self.platform.set_drive_label(drive, label)?;
+        info!("{} is now labelled \"{}\"", drive, label);
+        Ok(())
+    }
+
+    /// Raw (total, free) byte counts of `drive`.
+    pub fn drive_space_bytes(&self, drive: &str) -> Result<(u64, u64)> {
+        self.platform.drive_space(drive)
+    }
+
+    /// Every file from the cached scan of `drive`, `None` when it hasn't been scanned.
+    ///
+    /// This is the disk as it was when the scan ran, nothing is checked again, see
+    /// [`cache_info`](Self::cache_info) for when that was. The slice borrows the analyzer, so a
+    /// shared one has to stay locked for as long as it's in use.
+    pub fn cached_files(&self, drive: &str) -> Option<&[FileInfo]> {
+        self.file_cache.get(&normalize_path(drive)).map(|files| files.as_slice())
+    }
+
+    /// Every drive or folder with a cached scan, sorted.
+    pub fn cached_drives(&self) -> Vec<String> {
+        let mut drives: Vec<String> = self.file_cache.keys().chain(self.folder_cache.keys()).cloned().collect();
+        drives.sort();
+        drives.dedup();
+        drives
+    }
+
+    /// Every folder from the cached scan of `drive` with its total size, `None` when it hasn't
+    /// been scanned. The same snapshot as [`cached_files`](Self::cached_files).
+    pub fn cached_folders(&self, drive: &str) -> Option<&[FolderSize]> {
+        self.folder_cache.get(&normalize_path(drive)).map(|folders| folders.as_slice())
+    }
+
+    /// When `drive` was scanned, how much is cached and what the scan left out, `None` when it
+    /// hasn't been scanned.
+    pub fn cache_info(&self, drive: &str) -> Option<CacheInfo> {
+        let drive = normalize_path(drive);
+        if !self.file_cache.contains_key(&drive) && !self.folder_cache.contains_key(&drive) {
+            return None;
+        }
+        let (_, scanned_at) = self.cached_at.get(&drive)?;
+        let mode = self.scan_modes.get(&drive);
+        Some(CacheInfo {
+            scanned_at: *scanned_at,
+            file_count: self.file_cache.get(&drive).map_or(0, Vec::len),
+            folder_count: self.folder_cache.get(&drive).map_or(0, Vec::len),
+            max_depth: self.scan_depths.get(&drive).copied(),
+            min_file_bytes: mode.and_then(|mode| mode.min_file_bytes),
+            user_data: mode.is_some_and(|mode| mode.user_data),
+            follows_links: mode.is_some_and(|mode| mode.follow_links),
+            excluded: self.excluded_counts.get(&drive).copied().unwrap_or(0),
+            errors: self.scan_errors.get(&drive).map_or(0, Vec::len),
+            unreadable: self.unreadable.get(&drive).map_or(0, Unreadable::total),
+            timed_out: self.timed_out_scans.contains(&drive),
+            options: self.scan_options.get(&drive).cloned().unwrap_or_default(),
+        })
+    }
+
+    pub fn print_cache_info(&self, drive: &str) -> Result<()> {
+        let drive = normalize_path(drive);
+        Ok(printers::cache_info(&mut self.out(), &drive, self.cache_info(&drive).as_ref())?)
+    }
+
+    // for queries right after collect_and_cache_files, a cached folder-only scan has no file list
+    pub(crate) fn scanned_files(&self, drive: &str) -> Result<&[FileInfo]> {
+        self.cached_files(drive).ok_or_else(|| AnalyzerError::CacheMissing(drive.to_string()))
+    }
+
+    // paths from the last printed report, in the order they were numbered
+    pub fn last_listing(&self) -> &[PathBuf] {
+        &self.last_listing
+    }
+
+    pub(crate) fn set_last_listing(&mut self, paths: Vec<PathBuf>) {
+        self.last_listing = paths;
+    }
+
+    /// Turns off the scan progress messages on stderr.
+    pub fn set_quiet(&mut self, quiet: bool) {
+        self.quiet = quiet;
+    }
+
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+    }
+
+    pub fn read_only(&self) -> bool {
+        self.read_only
+    }
+
+    pub fn set_live_ui(&mut self, live_ui: bool) {
+        self.live_ui = live_ui;
+    }
+
+    pub fn live_ui(&self) -> bool {
+        self.live_ui
+    }
+
+    /// Gives up on scans that take longer than `seconds`, whatever was walked by then is cached
+    /// and [`collect_and_cache_files`](Self::collect_and_cache_files) returns [`ScanStatus::Partial`].
+    pub fn with_scan_timeout(mut self, seconds: u64) -> Self {
+        self.set_scan_timeout(Some(seconds));
+        self
+    }
+
+    // None (or 0) lets scans run as long as they need
+    pub fn set_scan_timeout(&mut self, seconds: Option<u64>) {
+        self.set_scan_timeout_duration(seconds.map(std::time::Duration::from_secs));
+    }
+
+    /// [`set_scan_timeout`](Self::set_scan_timeout) for a limit that isn't whole seconds.
+    pub fn set_scan_timeout_duration(&mut self, limit: Option<std::time::Duration>) {
+        self.scan_timeout = limit.filter(|limit| !limit.is_zero());
+    }
+
+    pub fn scan_timeout(&self) -> Option<std::time::Duration> {
+        self.scan_timeout
+    }
+
+    // None keeps caches until they're cleared, Some(0) rescans for every query
+    pub fn set_cache_ttl(&mut self, minutes: Option<u64>) {
+        self.config.scan.cache_ttl_minutes = minutes;
+    }
+
+    pub fn cache_ttl(&self) -> Option<u64> {
+        self.config.scan.cache_ttl_minutes
+    }
+
+    fn is_cache_expired(&self, drive: &str) -> bool {
+        let (Some(ttl), Some(cached_at)) = (self.cache_ttl(), self.cached_at.get(drive)) else {
+            return false;
+        };
+        let expired = cached_at.0.elapsed() >= std::time::Duration::from_secs(ttl.saturating_mul(60));
+        if expired && ttl > 0 {
+            self.status(Level::Info, &format!("Cached scan of {} is over {} minutes old, rescanning..", drive, ttl));
+        }
+        expired
+    }
+
+    pub fn set_include_hidden(&mut self, include_hidden: bool) {
+        self.include_hidden = include_hidden;
+    }
+
+    pub fn include_hidden(&self) -> bool {
+        self.include_hidden
+    }
+
+    // stderr, so whatever a query returns or prints on stdout stays clean
+    // progress of the scans, a quiet analyzer only logs it at debug level
+    pub(crate) fn status(&self, level: Level, message: &str) {
+        let level = if self.quiet { Level::Debug } else { level };
+        log!(level, "{}", message);
+    }
+
+    pub fn excludes(&self) -> &ExcludeList {
+        &self.excludes
+    }
+
+    // swaps in a new exclude list and drops anything it now excludes from scans done before
+    // returns how many cached files were dropped
+    pub fn set_excludes(&mut self, excludes: ExcludeList) -> usize {
+        self.excludes = excludes;
+        let excludes = &self.excludes;
+        let mut pruned = 0;
+        for (drive, files) in self.file_cache.iter_mut() {
+            let (removed, kept): (Vec<FileInfo>, Vec<FileInfo>) = files
+                .drain(..)
+                .partition(|file| excludes.is_excluded(&file.full_path));
+            *files = kept;
+            if removed.is_empty() {
+                continue;
+            }
+
+            self.cache_generation += 1;
+            if let Some(folders) = self.folder_cache.get_mut(drive) {
+                folders.retain(|folder| !excludes.is_excluded(&folder.folder));
+                for folder in folders.iter_mut() {
+                    let dir = comparable_path(&folder.folder);
+                    let under: Vec<&FileInfo> = removed
+                        .iter()
+                        .filter(|file| is_under(&comparable_path(&file.full_path), &dir))
+                        .collect();
+                    let bytes: u64 = under.iter().map(|file| file.size_bytes).sum();
+                    folder.size_bytes = folder.size_bytes.saturating_sub(bytes);
+                    folder.file_count = folder.file_count.saturating_sub(under.len());
+                }
+            }
+            *self.excluded_counts.entry(drive.clone()).or_default() += removed.len();
+            pruned += removed.len();
+        }
+        self.last_listing.retain(|p| !excludes.is_excluded(p));
+        pruned
+    }
+
+    // drops a deleted or moved path (and anything below it) from the caches
+    // folder sizes above it are reduced so the reports stay accurate
+    pub fn forget_path(&mut self, path: &Path) {
+        self.cache_generation += 1;
+        let target = comparable_path(path);
+        let under_target = |p: &Path| {
+            let p = comparable_path(p);
+            p == target || p.starts_with(&format!("{}/", target))
+        };
+
+        let mut removed_bytes = 0;
+        let mut removed_files = 0;
+        for files in self.file_cache.values_mut() {
+            files.retain(|file| {
+                let keep = !under_target(&file.full_path);
+                if !keep {
+                    removed_bytes += file.size_bytes;
+                    removed_files += 1;
+                }
+                keep
+            });
+        }
+
+        for folders in self.folder_cache.values_mut() {
+            folders.retain(|folder| !under_target(&folder.folder));
+            for folder in folders.iter_mut() {
+                if target.starts_with(&format!("{}/", comparable_path(&folder.folder))) {
+                    folder.size_bytes = folder.size_bytes.saturating_sub(removed_bytes);
+                    folder.file_count = folder.file_count.saturating_sub(removed_files);
+                }
+            }
+        }
+
+        self.last_listing.retain(|p| !under_target(p));
+    }
+
+    // adds a file or folder that just appeared (e.g. moved in) to whichever cached drive it is on
+    pub fn record_path(&mut self, path: &Path) {
+        let target = comparable_path(path);
+        let drive = match self
+            .file_cache
+            .keys()
+            .find(|drive| is_under(&target, &comparable_path(drive)))
+        {
+            Some(drive) => drive.clone(),
+            None => return, // that drive was never scanned, nothing to keep in sync
+        };
+        self.cache_generation += 1;
+
+        let new_files: Vec<FileInfo> = WalkDir::new(path)
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter(|e| e.file_type().is_file())
+            .filter_map(|entry| Self::file_info_from_entry(&entry).ok())
+            .collect();
+        let added_bytes: u64 = new_files.iter().map(|f| f.size_bytes).sum();
+        let added_files = new_files.len();
+
+        if let Some(folders) = self.folder_cache.get_mut(&drive) {
+            for folder in folders.iter_mut() {
+                if target.starts_with(&format!("{}/", comparable_path(&folder.folder))) {
+                    folder.size_bytes += added_bytes;
+                    folder.file_count += added_files;
+                }
+            }
+        }
+        if let Some(files) = self.file_cache.get_mut(&drive) {
+            files.extend(new_files);
+        }
+    }
+
+    // access denied, sharing violations and vanished files are normal on a live system, only
+    // invalid data or an OS code that means corruption (ERROR_FILE_CORRUPT, ...) is worth a look
+    fn is_fs_error(error: &io::Error) -> bool {
+        error.raw_os_error().is_some_and(|code| {
+            code != 0 && (matches!(error.kind(), ErrorKind::InvalidData | ErrorKind::Other) || FS_CORRUPTION_CODES.contains(&code))
+        })
+    }
+
+    fn scan_error(error: &walkdir::Error) -> Option<ScanError> {
+        let io_error = error.io_error().filter(|e| Self::is_fs_error(e))?;
+        let code = io_error.raw_os_error()?;
+        Some(ScanError {
+            path: error.path().map(|p| p.to_string_lossy().to_string()).unwrap_or_default(),
+            code,
+        })
+    }
+
+    /// What the cached scan of `drive` couldn't read, `None` when it hasn't been scanned.
+    pub fn unreadable(&self, drive: &str) -> Option<&Unreadable> {
+        self.unreadable.get(&normalize_path(drive))
+    }
+
+    // for reports built from a scan that missed enough to skew them
+    pub fn unreadable_note(&self, drive: &str) -> Option<String> {
+        let total = self.unreadable(drive).map_or(0, Unreadable::total);
+        (total >= UNREADABLE_NOTE_MIN)
+            .then(|| format!("{} items couldn't be read during the scan and aren't counted (see scan-errors)", total))
+    }
+
+    pub fn print_scan_errors(&mut self, drive: &str) -> Result<()> {
+        self.collect_and_cache_files(drive)?;
+        let unreadable = self.unreadable(drive).cloned().unwrap_or_default();
+        Ok(printers::unreadable(&mut self.out(), drive, &unreadable)?)
+    }
+
+    pub fn print_fs_errors(&mut self, drive: &str) -> Result<()> {
+        self.collect_and_cache_files(drive)?;
+        let errors = self.scan_errors.get(&normalize_path(drive)).map(Vec::as_slice).unwrap_or_default();
+        Ok(printers::fs_errors(&mut self.out(), drive, errors)?)
+    }
+
+    // walks the drive fresh (the cache only has files that could be read) and
+    // tries to stat everything, keeping whatever fails
+    pub fn find_inaccessible_files(&self, drive: &str) -> Result<Vec<(String, ErrorKind)>> {
+        let mut inaccessible: Vec<(String, ErrorKind)> = self
+            .scan_roots(drive)
+            .into_iter()
+            .flat_map(|root| WalkDir::new(root).into_iter().filter_entry(|e| !self.is_system_dir(e) && !self.is_skipped(e.path())))
+            .par_bridge()
+            .filter_map(|entry| match entry {
+                Ok(entry) => fs::metadata(entry.path())
+                    .err()
+                    .map(|e| (entry.path().to_string_lossy().to_string(), e.kind())),
+                // directories that couldn't even be listed
+                Err(e) => {
+                    let path = e.path()?.to_string_lossy().to_string();
+                    Some((path, e.io_error().map(|e| e.kind()).unwrap_or(ErrorKind::Other)))
+                }
+            })
+            .collect();
+        inaccessible.par_sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        Ok(inaccessible)
+    }
+
+    pub fn print_inaccessible_files(&self, drive: &str) -> Result<()> {
+        self.status(Level::Info, &format!("Checking {}, this walks the whole drive..", drive));
+        let inaccessible = self.find_inaccessible_files(drive)?;
+        Ok(printers::inaccessible_files(&mut self.out(), drive, &inaccessible)?)
+    }
+
+    // runs `f` on the tune-threads pool when there is one
+    pub(crate) fn in_scan_pool<R: Send>(&self, f: impl FnOnce() -> R + Send) -> R {
+        match &self.thread_pool {
+            Some(pool) => pool.install(f),
+            None => f(),
+        }
+    }
+
+    // (file count, bytes) of the cached file scan of `drive`
+    pub(crate) fn cached_file_totals(&self, drive: &str) -> Option<(usize, u64)> {
+        let files = self.file_cache.get(&normalize_path(drive))?;
+        Some((files.len(), files.iter().map(|f| f.size_bytes).sum()))
+    }
+
+    /// Same as [`collect_and_cache_files`](Self::collect_and_cache_files) with `options` in
+    /// place of whatever the last command set, unset fields fall back to the builder's. The
+    /// options only last for this call, the ones set before are back afterwards.
+    ///
+    /// Fails with [`AnalyzerError::InvalidArgument`] when `options` don't pass
+    /// [`ScanOptions::validate`]. A cache made with other options is scanned again.
+    pub fn collect_and_cache_files_with(&mut self, drive: &str, options: &ScanOptions) -> Result<ScanStatus> {
+        options.validate().map_err(AnalyzerError::InvalidArgument)?;
+        let previous = self.options.clone();
+        self.set_scan_options(options);
+        let status = self.collect_and_cache_files(drive);
+        self.set_scan_options(&previous);
+        status
+    }
+
+    /// Scans `drive` unless there's a cached scan that fits the current scan options, `Partial`
+    /// when the scan timeout cut the scan (or the cached one) short.
+    pub fn collect_and_cache_files(&mut self, drive: &str) -> Result<ScanStatus> {
+        match self.thread_pool.clone() {
+            Some(pool) => pool.install(|| self.scan_and_cache(drive)),
+            None => self.scan_and_cache(drive),
+        }
+    }
+
+    fn scan_and_cache(&mut self, drive: &str) -> Result<ScanStatus> {
+        let drive = normalize_path(drive);
+        let drive = drive.as_str();
+        if self.has_usable_cache(drive) {
+            return Ok(self.cached_scan_status(drive));
+        }
+
+        let roots = self.checked_scan_roots(drive)?;
+        if self.user_data {
+            self.status(Level::Info, &format!("No cache found, scanning user data only ({})..", roots.join(", ")));
+        } else {
+            self.status(Level::Info, "No cache found, scanning..");
+        }
+
+        let scanning = ScanGuard::start();
+        let deadline = self.scan_timeout.map(ScanDeadline::start);
+        let scan = self.walk_roots(&roots, self.live_ui && !self.quiet);
+        let timed_out = deadline.is_some_and(ScanDeadline::finish);
+        // nothing gets cached from a cancelled scan, a half walked drive would look smaller than it is
+        // a timed out one is kept anyway, it's all a drive that slow is going to give
+        if scan_cancelled() && !timed_out {
+            return Err(AnalyzerError::Cancelled);
+        }
+        drop(scanning);
+        self.store_scan(drive, scan);
+        if !timed_out {
+            return Ok(ScanStatus::Complete);
+        }
+        self.mark_timed_out(drive);
+        Ok(ScanStatus::Partial)
+    }
+
+    // whether the cached scan of `drive` ran into the scan timeout
+    pub(crate) fn cached_scan_status(&self, drive: &str) -> ScanStatus {
+        match self.timed_out_scans.contains(drive) {
+            true => ScanStatus::Partial,
+            false => ScanStatus::Complete,
+        }
+    }
+
+    // remembers that the scan just stored for `drive` was cut short by the timeout
+    pub(crate) fn mark_timed_out(&mut self, drive: &str) {
+        let secs = self.scan_timeout.map_or(0.0, |timeout| timeout.as_secs_f64());
+        self.status(Level::Warn, &format!("Scan of {} stopped after the {}s timeout, results only cover what was reached", drive, secs));
+        self.timed_out_scans.insert(drive.to_string());
+    }
+
+    // for reports built from a scan the timeout cut short
+    pub fn timeout_note(&self, drive: &str) -> Option<String> {
+        self.timed_out_scans
+            .contains(&normalize_path(drive))
+            .then(|| "the scan stopped at the scan timeout, only what it reached is counted".to_string())
+    }
+
+    // drops a cache that no longer fits the current options, true when what's left can be used
+    pub(crate) fn has_usable_cache(&mut self, drive: &str) -> bool {
+        // a cache from a scan with different exclusions would give the wrong answer
+        if self.scan_modes.get(drive).is_some_and(|mode| *mode != self.scan_mode()) || self.is_cache_expired(drive) {
+            self.drop_cached_scan(drive);
+        }
+        // a shallower cache can't answer a deeper question, a deeper one is fine for a shallower one
+        if let Some(&cached) = self.scan_depths.get(drive)
+            && self.effective_max_depth().is_none_or(|wanted| wanted > cached)
+            && (self.file_cache.contains_key(drive) || self.folder_cache.contains_key(drive)) {
+            let question = format!("the cached scan of {} only goes {} levels deep, rescan? [y/N]", drive, cached);
+            if self.rescan_prompt.is_none_or(|prompt| prompt(&question)) {
+                self.drop_cached_scan(drive);
+            }
+        }
+        let scope = if self.user_data { "user-data" } else { "file" };
+        let note = self.depth_note(drive).map(|note| format!(" {}", note)).unwrap_or_default();
+        if self.file_cache.contains_key(drive) {
+            self.status(Level::Debug, &format!("Cached {} scan found{}! Proceeding..", scope, note));
+            true
+        } else if self.folder_cache.contains_key(drive) { 
+            self.status(Level::Debug, &format!("Cached folder scan found{}! Proceeding..", note));
+            true
+        } else {
+            false
+        }
+    }
+
+    // the roots a scan of `drive` would walk, an error if there are none or one can't be listed
+    pub(crate) fn checked_scan_roots(&self, drive: &str) -> Result<Vec<String>> {
+        let roots = self.scan_roots(drive);
+        if roots.is_empty() {
+            return Err(AnalyzerError::InvalidArgument(
+                format!("no user data on {}, add folders to [scan] user_data_roots", drive),
+            ));
+        }
+        // a root that can't be listed would otherwise just come back as an empty scan
+        for root in &roots {
+            match fs::read_dir(root) {
+                Ok(_) => {}
+                Err(e) if e.kind() == ErrorKind::NotFound => return Err(AnalyzerError::RootNotFound(root.clone())),
+                Err(e) if e.kind() == ErrorKind::PermissionDenied => return Err(AnalyzerError::AccessDenied(root.clone())),
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(roots)
+    }
+
+    // walks `roots` without touching the caches, so several drives can be walked at once
+    // the caller holds the ScanGuard and checks scan_cancelled() before storing anything
+    pub(crate) fn walk_roots(&self, roots: &[String], live_ui: bool) -> DriveScan {
+        let mut files = Vec::new();
+        let mut folders = Vec::new();
+
+        // excluded directories are skipped whole instead of walked and filtered
+        let mut excluded = 0;
+        let mut system_dirs = Vec::new();
+        let mut scan_errors = Vec::new();
+        let mut unreadable = Unreadable::default();
+        let progress = Arc::new(ScanProgress::default());
+        let live_ui = match live_ui {
+            true => LiveUi::start(Arc::clone(&progress)).ok(),
+            false => None,
+        };
+        let follow_links = self.follows_links();
+        let max_depth = self.effective_max_depth();
+        let min_file_bytes = self.min_file_bytes;
+        let mut visited = HashSet::new();
+        let mut links = 0;
+        for root in roots {
+            // can use WalkDir with max depth to avoid scanning deeply nested directories
+            let walker = WalkDir::new(root)
+                .follow_links(follow_links)
+                .max_depth(max_depth.unwrap_or(usize::MAX))
+                .into_iter()
+                .filter_entry(|e| {
+                    // a cancelled scan prunes everything left so the walk winds down quickly
+                    if scan_cancelled() {
+                        return false;
+                    }
+                    if follow_links && e.path_is_symlink() && e.file_type().is_dir() {
+                        if is_repeat_link(&mut visited, e) {
+                            return false;
+                        }
+                        links += 1;
+                    }
+                    if self.is_system_dir(e) {
+                        system_dirs.push(e.path().to_string_lossy().to_string());
+                        return false;
+                    }
+                    let skip = e.depth() > 0 && self.is_skipped(e.path());
+                    if skip {
+                        excluded += 1;
+                    }
+                    !skip
+                })
+                .filter_map(|entry| match entry {
+                    Ok(entry) => Some(entry),
+                    // skip errors instead of crashing, but remember the ones that point at corruption
+                    Err(e) => {
+                        progress.errors.fetch_add(1, Ordering::Relaxed);
+                        scan_errors.extend(Self::scan_error(&e));
+                        // loops have no io error, the link handling already deals with those
+                        if let Some(io_error) = e.io_error() {
+                            let path = e.path().map(|p| p.to_string_lossy().to_string()).unwrap_or_default();
+                            unreadable.record(path, io_error.kind());
+                        }
+                        None
+                    }
+                })
+                .filter(|e| {
+                    if e.file_type().is_dir() {
+                        progress.folders.fetch_add(1, Ordering::Relaxed);
+                    }
+                    e.file_type().is_file() // Process only files
+                });
+
+            // process in parallel using Rayon
+            let root_files: Vec<walkdir::Result<FileInfo>> = walker
+                .par_bridge() // Enables parallel iteration
+                // files the walk already handed over aren't read any more once the scan is cancelled
+                .filter(|_| !scan_cancelled())
+                .map(|entry| {
+                    progress.files.fetch_add(1, Ordering::Relaxed);
+                    Self::file_info_from_entry(&entry)
+                })
+                .filter(|file| file.as_ref().map_or(true, |file| min_file_bytes.is_none_or(|min| file.size_bytes >= min)))
+                .collect(); // Collect all results in one go (which seems stupid I know)
+            for file in root_files {
+                match file {
+                    Ok(file) => files.push(file),
+                    // listed but gone or locked by the time its size was read
+                    Err(e) => {
+                        progress.errors.fetch_add(1, Ordering::Relaxed);
+                        let path = e.path().map(|p| p.to_string_lossy().to_string()).unwrap_or_default();
+                        unreadable.record(path, e.io_error().map_or(ErrorKind::Other, |e| e.kind()));
+                    }
+                }
+            }
+            if scan_cancelled() {
+                break;
+            }
+
+            // Cache folder sizes
+            let mut visited_folders = HashSet::new();
+            let root_folders: Vec<FolderSize> = WalkDir::new(root)
+                .follow_links(follow_links)
+                .min_depth(1)
+                .max_depth(max_depth.map_or(3, |depth| depth.min(3)))
+                .into_iter()
+                .filter_entry(|e| {
+                    !self.is_system_dir(e) && !self.is_skipped(e.path()) && !is_repeat_link(&mut visited_folders, e)
+                })
+                .filter_map(Result::ok)
+                .filter(|e| e.file_type().is_dir())
+                .filter_map(|entry| {
+                    // the folder's own walk only gets the levels the limit has left
+                    let remaining = max_depth.map(|depth| depth - entry.depth());
+                    self.folder_size_within(entry.path(), remaining, self.follows_links()).ok()
+                })
+                .collect();
+            folders.extend(root_folders);
+        }
+        drop(live_ui);
+
+        DriveScan { files, folders, excluded, system_dirs, scan_errors, unreadable, links, max_depth }
+    }
+
+    // fills every cache of `drive` from a finished walk
+    pub(crate) fn store_scan(&mut self, drive: &str, scan: DriveScan) {
+        let DriveScan { files, folders, excluded, system_dirs, scan_errors, unreadable, links, max_depth } = scan;
+        self.cache_generation += 1;
+        let note = max_depth.map(|depth| format!(" (scan limited to depth {})", depth)).unwrap_or_default();
+        if excluded > 0 {
+            self.status(Level::Info, &format!("Scanning complete{}.. ({} entries skipped by exclusions)", note, excluded));
+        } else {
+            self.status(Level::Info, &format!("Scanning complete{}..", note));
+        }
+        match max_depth {
+            Some(depth) => self.scan_depths.insert(drive.to_string(), depth),
+            None => self.scan_depths.remove(drive),
+        };
+        if !system_dirs.is_empty() {
+            self.status(Level::Info, &format!("system dirs skipped: {}, unknown size (add --include-system to scan them)", system_dirs.len()));
+        }
+        if links > 0 {
+            self.status(Level::Info, &format!("Followed {} symlinks and junctions, sizes may overlap with other drives", links));
+        }
+        self.excluded_counts.insert(drive.to_string(), excluded);
+        self.links_followed.insert(drive.to_string(), links);
+        self.skipped_system_dirs.insert(drive.to_string(), system_dirs);
+        self.scan_modes.insert(drive.to_string(), self.scan_mode());
+        self.scan_options.insert(drive.to_string(), self.options_in_effect());
+        if !scan_errors.is_empty() {
+            self.status(Level::Warn, &format!("{} paths had filesystem errors, see show-fs-errors", scan_errors.len()));
+        }
+        self.scan_errors.insert(drive.to_string(), scan_errors);
+        if unreadable.total() > 0 {
+            self.status(Level::Warn, &format!("{} items unreadable, run scan-errors {} for details", unreadable.total(), drive));
+        }
+        self.unreadable.insert(drive.to_string(), unreadable);
+        self.timed_out_scans.remove(drive);
+        let metrics = ScanMetrics::from_files(&files);
+        // you might ask why do these separately, well.. you never asked
+        self.cached_at.insert(drive.to_string(), (Instant::now(), Utc::now()));
+        self.file_cache.insert(drive.to_string(), files);
+        self.folder_cache.insert(drive.to_string(), folders);
+        lock_registry(&self.metrics).insert(drive.to_string(), metrics);
+        self.status(Level::Debug, "Caching files and folders..");
+    }
+
+    // puts a scan saved earlier back as the cache of its drive, the ttl counts from now and not from `scanned_at`
+    pub(crate) fn restore_scan(&mut self, snapshot: DriveSnapshot) {
+        let DriveSnapshot { drive, scanned_at, options, timed_out, files, folders } = snapshot;
+        let drive = normalize_path(&drive);
+        self.drop_cached_scan(&drive);
+        if timed_out {
+            self.timed_out_scans.insert(drive.clone());
+        }
+        match options.max_depth {
+            Some(depth) => self.scan_depths.insert(drive.clone(), depth),
+            None => self.scan_depths.remove(&drive),
+        };
+        self.scan_modes.insert(drive.clone(), ScanMode {
+            no_exclude: options.no_exclude.unwrap_or(false),
+            include_system: options.include_system.unwrap_or(false),
+            user_data: options.user_data.unwrap_or(false),
+            follow_links: options.follow_links.unwrap_or(false),
+            min_file_bytes: options.min_file_size.as_deref().and_then(|size| parse_size(size, self.units()).ok()),
+        });
+        self.scan_options.insert(drive.clone(), options);
+        // what the scan skipped or couldn't read wasn't saved with it
+        self.excluded_counts.remove(&drive);
+        self.scan_errors.remove(&drive);
+        self.unreadable.remove(&drive);
+        self.links_followed.remove(&drive);
+        self.skipped_system_dirs.remove(&drive);
+        let metrics = ScanMetrics::from_files(&files);
+        self.cached_at.insert(drive.clone(), (Instant::now(), scanned_at));
+        self.file_cache.insert(drive.clone(), files);
+        self.folder_cache.insert(drive.clone(), folders);
+        lock_registry(&self.metrics).insert(drive, metrics);
+    }
+
+    // groups files by extension and returns (extension, bytes, count, recent bytes) sorted by size,
+    // recent meaning modified after `recent_since`
+    // extensions at or under `min_bytes` are left out, None keeps every one
+    fn group_by_extension(files: &[FileInfo], min_bytes: Option<u64>, recent_since: DateTime<Utc>) -> TypeDistribution {
+        let file_types: HashMap<String, FileTypeStats> = files
+            .par_iter()
+            .fold(
+                || HashMap::new(),
+                |mut acc, file_info| {
+                    let ext = file_info.full_path
+                        .extension()
+                        .map(|e| e.to_string_lossy().to_lowercase())
+                        .unwrap_or_else(|| "(No Extension)".to_string());
+
+                    let stats: &mut FileTypeStats = acc.entry(ext).or_default();
+                    stats.total_bytes += file_info.size_bytes;
+                    stats.count += 1;
+                    if file_info.last_modified.is_some_and(|modified| modified > recent_since) {
+                        stats.recent_bytes += file_info.size_bytes;
+                    }
+                    acc
+                },
+            )
+            .reduce(
+                || HashMap::new(),
+                |mut acc1, acc2| {
+                    for (ext, stats2) in acc2 {
+                        let stats1 = acc1.entry(ext).or_default();
+                        stats1.total_bytes += stats2.total_bytes;
+                        stats1.count += stats2.count;
+                        stats1.recent_bytes += stats2.recent_bytes;
+                    }
+                    acc1
+                },
+            );
+
+        let mut distribution: Vec<_> = file_types
+            .into_iter()
+            .map(|(ext, stats)| (ext, stats.total_bytes, stats.count, stats.recent_bytes))
+            .filter(|&(_, size, _, _)| min_bytes.is_none_or(|min| size > min))
+            .collect();
+
+        distribution.par_sort_unstable_by(|a, b| b.1.cmp(&a.1));
+        distribution
+    }
+
+    // where "recent" starts for the type distributions
+    fn recent_cutoff(&self) -> DateTime<Utc> {
+        Utc::now() - Duration::days(self.config.days.recent_window_days)
+    }
+
+    /// (extension, bytes, file count, recent bytes) for every extension over
+    /// `thresholds.min_file_type_size_gb`, biggest first.
+    pub fn get_file_type_distribution(&mut self, drive: &str) -> Result<TypeDistribution> {
+        self.collect_and_cache_files(drive)?;
+
+        let min_bytes = gb_to_bytes(self.units(), self.config.thresholds.min_file_type_size_gb);
+        match self.file_cache.get(&normalize_path(drive)) {
+            Some(files) => Ok(Self::group_by_extension(files, Some(min_bytes), self.recent_cutoff())),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// File count and bytes of each [`FileCategory`] on `drive`, biggest first. Unlike
+    /// [`get_file_type_distribution`](Self::get_file_type_distribution) nothing is left out for
+    /// being small, so the categories add up to the whole scan.
+    pub fn size_by_category(&mut self, drive: &str) -> Result<CategoryTotals> {
+        self.collect_and_cache_files(drive)?;
+        Ok(category_totals(self.scanned_files(&normalize_path(drive))?))
+    }
+
+    /// Every extension with at least `min_count` files, no size cutoff, most common first.
+    ///
+    /// ```
+    /// use rusty_analyser::StorageAnalyzer;
+    /// use std::fs;
+    ///
+    /// let dir = std::env::temp_dir().join("rusty-analyser-doctest-census");
+    /// fs::create_dir_all(&dir)?;
+    /// fs::write(dir.join("a.txt"), "0123456789")?;
+    /// fs::write(dir.join("b.txt"), "01234567890123456789")?;
+    /// fs::write(dir.join("notes.log"), "01234")?;
+    ///
+    /// let mut analyzer = StorageAnalyzer::new();
+    /// analyzer.set_quiet(true);
+    /// let census = analyzer.extension_census(&dir.to_string_lossy(), 1)?;
+    ///
+    /// // all of it was written just now, so all of it counts as recent
+    /// assert_eq!(census, vec![("txt".to_string(), 30, 2, 30), ("log".to_string(), 5, 1, 5)]);
+    ///
+    /// fs::remove_dir_all(&dir)?;
+    /// # Ok::<(), rusty_analyser::AnalyzerError>(())
+    /// ```
+    pub fn extension_census(&mut self, drive: &str, min_count: usize) -> Result<TypeDistribution> {
+        self.collect_and_cache_files(drive)?;
+        let mut census = match self.file_cache.get(&normalize_path(drive)) {
+            Some(files) => Self::group_by_extension(files, None, self.recent_cutoff()),
+            None => Vec::new(),
+        };
+        census.retain(|&(_, _, count, _)| count >= min_count);
+        census.par_sort_unstable_by(|a, b| b.2.cmp(&a.2).then_with(|| a.0.cmp(&b.0)));
+        Ok(census)
+    }
+
+    pub fn print_extension_census(&mut self, drive: &str, min_count: usize) -> Result<TypeDistribution> {
+        // the totals cover every extension, not just the ones that made the cutoff
+        let mut census = self.extension_census(drive, 1)?;
+        let (distinct, files) = (census.len(), census.iter().map(|&(_, _, count, _)| count).sum());
+        census.retain(|&(_, _, count, _)| count >= min_count);
+        printers::extension_census(&mut self.out(), self.units(), drive, min_count, &census, (distinct, files))?;
+        Ok(census)
+    }
+
+    // how many files with extension `ext` are on `drive` and how many bytes they take
+    pub fn extension_stats(&mut self, drive: &str, ext: &str) -> Result<ExtensionStats> {
+        self.collect_and_cache_files(drive)?;
+        let ext = normalize_extension(ext);
+        let (count, size_bytes) = self
+            .file_cache
+            .get(&normalize_path(drive))
+            .map(|files| {
+                files
+                    .iter()
+                    .filter(|file| {
+                        file.full_path
+                            .extension()
+                            .map(|e| e.to_string_lossy().to_lowercase() == ext)
+                            .unwrap_or(false)
+                    })
+                    .fold((0, 0), |(count, size), file| (count + 1, size + file.size_bytes))
+            })
+            .unwrap_or((0, 0));
+
+        Ok(ExtensionStats { drive: drive.to_string(), extension: ext, count, size_bytes })
+    }
+
+    // both drives go through the cache, so only a cold drive gets scanned
+    pub fn compare_ext_across_drives(&mut self, ext: &str, drive_a: &str, drive_b: &str) -> Result<(ExtensionStats, ExtensionStats)> {
+        Ok((self.extension_stats(drive_a, ext)?, self.extension_stats(drive_b, ext)?))
+    }
+
+    pub fn print_compare_ext(&mut self, ext: &str, drive_a: &str, drive_b: &str) -> Result<()> {
+        let (a, b) = self.compare_ext_across_drives(ext, drive_a, drive_b)?;
+        Ok(printers::compare_ext(&mut self.out(), self.units(), &a, &b)?)
+    }
+
+    // same as get_file_type_distribution, but split up by top-level folder (depth 1)
+    // files sitting directly in the drive root are grouped under the drive itself
+    pub fn type_dist_per_folder(&mut self, drive: &str) -> Result<HashMap<String, TypeDistribution>> {
+        self.collect_and_cache_files(drive)?;
+
+        let files = match self.file_cache.get(&normalize_path(drive)) {
+            Some(files) => files,
+            None => return Ok(HashMap::new()),
+        };
+
+        let mut partitions: HashMap<String, Vec<FileInfo>> = HashMap::new();
+        for file in files {
+            let top_folder = top_level_folder(drive, &file.full_path).to_string_lossy().to_string();
+            partitions.entry(top_folder).or_default().push(file.clone());
+        }
+
+        let min_bytes = gb_to_bytes(self.units(), self.config.thresholds.min_file_type_size_gb);
+        let recent_since = self.recent_cutoff();
+        Ok(partitions
+            .into_iter()
+            .map(|(folder, files)| (folder, Self::group_by_extension(&files, Some(min_bytes), recent_since)))
+            .collect())
+    }
+
+    /// (folder, oldest, newest) modification time of the files in each top-level folder of
+    /// `drive`, the ones whose newest file is the oldest (the folders nothing touched in years)
+    /// first. Files at the root of the drive count as the drive itself.
+    pub fn folders_by_content_age(&mut self, drive: &str) -> Result<Vec<FolderAge>> {
+        self.collect_and_cache_files(drive)?;
+
+        let mut ages: HashMap<PathBuf, (DateTime<Utc>, DateTime<Utc>)> = HashMap::new();
+        for file in self.scanned_files(&normalize_path(drive))? {
+            let Some(modified) = file.last_modified else { continue };
+            let (oldest, newest) = ages.entry(top_level_folder(drive, &file.full_path)).or_insert((modified, modified));
+            *oldest = (*oldest).min(modified);
+            *newest = (*newest).max(modified);
         }
+
+        let mut folders: Vec<_> = ages.into_iter().map(|(folder, (oldest, newest))| (folder, oldest, newest)).collect();
+        folders.sort_by(|a, b| a.2.cmp(&b.2).then_with(|| a.0.cmp(&b.0)));
+        Ok(folders)
     }
 
-    // Windows-specific implementation to list fixed drives
-    // filters for physical drives only, skips USB/network drives
-    #[cfg(target_os = "windows")]
-    fn list_drives() -> Vec<String> {
-        let mut buffer = [0u16; 256];
-        let len = unsafe { GetLogicalDriveStringsW(buffer.len() as u32, buffer.as_mut_ptr()) };
+    /// Every scanned file on `drive`, sorted by size in `order`.
+    pub fn get_largest_files(&mut self, drive: &str, order: SortOrder) -> Result<Vec<FileInfo>> {
+        self.collect_and_cache_files(drive)?;
 
-        if len == 0 {
-            return Vec::new();
+        if let Some(files) = self.file_cache.get(&normalize_path(drive)) {
+            let mut result = files.clone();
+            sort_by_size(&mut result, order);
+            Ok(result)
+        } else {
+            Ok(Vec::new())
         }
+    }
 
-        buffer[..len as usize]
-            .split(|&c| c == 0)
-            .filter_map(|slice| {
-                (!slice.is_empty())
-                    .then(|| {
-                        let drive = OsString::from_wide(slice);
-                        let drive_type = unsafe { GetDriveTypeW(slice.as_ptr()) };
-                        (drive_type == DRIVE_FIXED).then(|| drive.to_string_lossy().into_owned())
-                    })
-                    .flatten()
-            })
-            .collect()
+    /// `limit` files on `drive` ranked by `key` in `order` (files missing that timestamp come
+    /// last either way), starting `offset` places down. Only the first `offset + limit` get
+    /// sorted and only the ones returned are copied out of the cache.
+    pub fn rank_files(&mut self, drive: &str, key: SortKey, order: SortOrder, offset: usize, limit: usize) -> Result<Vec<FileInfo>> {
+        self.rank_files_where(drive, key, order, offset, limit, |_| true)
+    }
+
+    /// Every file on `drive` that gets past the filters in `options`, ranked the way it asks,
+    /// for going through the result a page at a time without ranking it again.
+    pub fn ranked_files(&mut self, drive: &str, options: &ListOptions) -> Result<Vec<FileInfo>> {
+        self.rank_files_where(drive, options.by, options.order, 0, usize::MAX, |file| options.keeps(file))
     }
 
-    // placeholder for non-Windows platforms, no bloody idea what to do
-    #[cfg(not(target_os = "windows"))]
-    fn list_drives() -> Vec<String> {
-        Vec::new()
+    fn rank_files_where(
+        &mut self,
+        drive: &str,
+        key: SortKey,
+        order: SortOrder,
+        offset: usize,
+        limit: usize,
+        keep: impl Fn(&FileInfo) -> bool,
+    ) -> Result<Vec<FileInfo>> {
+        self.collect_and_cache_files(drive)?;
+        let files: Vec<&FileInfo> = self.scanned_files(&normalize_path(drive))?.iter().filter(|file| keep(file)).collect();
+        let ranked = first_n_by(files, offset.saturating_add(limit), |a, b| key.compare(order, a, b));
+        Ok(ranked.into_iter().skip(offset).cloned().collect())
     }
 
-    // uses Windows API to get drive space information
-    fn get_drive_space(&self, drive: &str) -> io::Result<DriveAnalysis> {
-        use winapi::um::winnt::ULARGE_INTEGER;
-        let mut free_bytes_available: ULARGE_INTEGER = unsafe { std::mem::zeroed() };
-        let mut total_bytes: ULARGE_INTEGER = unsafe { std::mem::zeroed() };
-        let mut total_free_bytes: ULARGE_INTEGER = unsafe { std::mem::zeroed() };
+    /// The `limit` largest files on `drive` with extension `ext` (with or without the dot,
+    /// any case), biggest first.
+    pub fn top_files_by_ext(&mut self, drive: &str, ext: &str, limit: usize) -> Result<Vec<FileInfo>> {
+        self.collect_and_cache_files(drive)?;
+        let ext = normalize_extension(ext);
+        let mut files: Vec<FileInfo> = self
+            .scanned_files(drive)?
+            .iter()
+            .filter(|file| {
+                file.full_path
+                    .extension()
+                    .is_some_and(|e| normalize_extension(&e.to_string_lossy()) == ext)
+            })
+            .cloned()
+            .collect();
+        sort_by_size(&mut files, SortOrder::Descending);
+        files.truncate(limit);
+        Ok(files)
+    }
 
-        // convert drive path to wide string for Windows API
-        let wide_drive: Vec<u16> = OsStr::new(drive).encode_wide().chain(Some(0)).collect();
+    pub fn print_top_files_by_ext(&mut self, drive: &str, ext: &str, limit: Option<usize>) -> Result<()> {
+        let limit = limit.unwrap_or(self.config.display.top_n);
+        let files = self.top_files_by_ext(drive, ext, limit)?;
+        if files.is_empty() {
+            return Ok(printers::no_files_of_type(&mut self.out(), ext, drive)?);
+        }
+        let title = format!("Largest .{} Files", normalize_extension(ext));
+        Ok(self.print_top_files(&title, files, &ListOptions { count: Some(limit), ..ListOptions::default() })?)
+    }
+    
+    // main analysis function that calls all the other functions for a full scan
+    pub fn analyze_drive(&mut self, drive: &str) -> Result<()> {
+        let drive = normalize_path(drive);
+        let drive = drive.as_str();
+        self.check_known_drive(drive)?;
 
-        let success = unsafe {
-            GetDiskFreeSpaceExW(
-                wide_drive.as_ptr(),
-                &mut free_bytes_available as *mut _ as *mut _,
-                &mut total_bytes as *mut _ as *mut _,
-                &mut total_free_bytes as *mut _ as *mut _,
-            )
+        let report = self.drive_report(drive)?;
+        let layout = ReportLayout {
+            top_n: self.config.display.top_n,
+            recent_days: self.config.days.recent_window_days,
+            old_days: self.config.days.old_window_days,
+            // what the cached scan was made with, the switches may have changed since
+            user_data_roots: self.is_user_data_scan(drive).then(|| self.user_data_roots(drive)),
+            follows_links: self.is_follow_links_scan(drive),
+            gap: self.scan_gap(drive, report.space.used_space),
+            depth_note: self.depth_note(drive),
+            unreadable_note: self.unreadable_note(drive),
+            timeout_note: self.timeout_note(drive),
         };
+        self.last_listing = report.old.iter().take(layout.top_n).map(|f| f.full_path.clone()).collect();
+        Ok(printers::drive_report(&mut self.out(), self.units(), drive, &report, &layout)?)
+    }
+
+    // the usual suspects for the Downloads folder: what types pile up, the biggest files and what's been sitting there
+    pub fn analyze_downloads(&mut self) -> Result<()> {
+        let downloads = home_dir()
+            .map(|home| home.join("Downloads"))
+            .filter(|path| path.is_dir())
+            .ok_or_else(|| AnalyzerError::RootNotFound("a Downloads folder in your profile".to_string()))?;
+        let path = normalize_path(&downloads.to_string_lossy());
 
-        if success == 0 {
-            return Err(Error::last_os_error());
+        self.collect_and_cache_files(&path)?;
+        let (size_bytes, count) = self
+            .cached_files(&path)
+            .map(|files| (files.iter().map(|f| f.size_bytes).sum::<u64>(), files.len()))
+            .unwrap_or_default();
+        printers::downloads_total(&mut self.out(), self.units(), size_bytes, count)?;
+
+        self.print_file_type_distribution(&path)?;
+        self.print_largest_files(&path, &ListOptions::default())?;
+        self.print_old_large_files(&path, Some(DOWNLOADS_OLD_DAYS), &ListOptions::default())?;
+        Ok(())
+    }
+
+    // one line summary of the cached scan, e.g. "C:/ scan complete: 1.2M files, 387 GiB"
+    pub fn scan_summary(&self, drive: &str) -> Option<String> {
+        let units = self.units();
+        let files = self.file_cache.get(&normalize_path(drive))?;
+        let total_gb = bytes_to_gb(units, files.iter().map(|f| f.size_bytes).sum());
+        let scope = if self.is_user_data_scan(drive) { "user-data scan" } else { "scan" };
+        let mut summary = format!("{} {} complete: {} files, {:.0} {}", drive, scope, compact_count(files.len()), total_gb, gb_label(units));
+        if let Some(note) = self.depth_note(drive) {
+            summary.push_str(&format!(" {}", note));
+        }
+        match self.excluded_counts.get(&normalize_path(drive)) {
+            Some(&excluded) if excluded > 0 => summary.push_str(&format!(", {} excluded", compact_count(excluded))),
+            _ => {}
+        }
+        match self.links_followed.get(&normalize_path(drive)) {
+            Some(&links) if links > 0 => summary.push_str(&format!(", {} links followed (sizes may overlap)", links)),
+            _ => {}
+        }
+        match self.skipped_system_dirs.get(&normalize_path(drive)) {
+            Some(dirs) if !dirs.is_empty() => {
+                summary.push_str(&format!(", system dirs skipped: {}, unknown size", dirs.len()))
+            }
+            _ => {}
+        }
+        Some(summary)
+    }
+
+    // checks free space on every fixed drive and returns the ones below the low space
+    // threshold, drives that report no size (an empty card reader and the like) can't run
+    // out of space and are skipped
+    pub fn disk_warnings(&self) -> Vec<DiskWarning> {
+        let thresholds = &self.config.thresholds;
+        self.drives
+            .iter()
+            .filter_map(|drive| {
+                let analysis = self.get_drive_space(drive).ok()?;
+                (analysis.free_space_percent < thresholds.low_free_space_percent).then(|| DiskWarning {
+                    critical: analysis.free_space_percent < thresholds.critical_free_space_percent,
+                    free_space_percent: analysis.free_space_percent,
+                    drive: analysis.drive,
+                    label: analysis.label,
+                })
+            })
+            .collect()
+    }
+
+    pub fn print_disk_warnings(&self) -> Result<()> {
+        let warnings = self.disk_warnings();
+        printers::disk_warnings(&mut self.out(), &warnings, self.config.thresholds.low_free_space_percent)?;
+
+        let critical: Vec<_> = warnings.iter().filter(|warning| warning.critical).collect();
+        if !critical.is_empty() {
+            let message = critical
+                .iter()
+                .map(|warning| format!("{} {:.1}% free", warning.drive, warning.free_space_percent))
+                .collect::<Vec<_>>()
+                .join(", ");
+            self.notify_on_complete("Disk space critical", &message);
         }
+        Ok(())
+    }
 
-        let total_size = unsafe { *total_bytes.QuadPart() } as f64 / GB_TO_BYTES;
-        let free_space = unsafe { *total_free_bytes.QuadPart() } as f64 / GB_TO_BYTES;
-        let used_space = total_size - free_space;
+    pub fn print_drive_space_overview(&self, drive: &str) -> Result<()> {
+        let analysis = match self.get_drive_space(drive) {
+            Err(AnalyzerError::DriveUnavailable(drive)) => {
+                return Ok(printers::drive_unavailable(&mut self.out(), &drive, self.drive_label(&drive).as_deref())?);
+            }
+            analysis => analysis?,
+        };
+        Ok(printers::drive_space(&mut self.out(), self.units(), &analysis, self.scan_gap(drive, analysis.used_space).as_ref())?)
+    }
 
-        Ok(DriveAnalysis {
-            total_size,
-            used_space,
-            free_space,
-            free_space_percent: (free_space / total_size) * 100.0,
+    // after a scan, how much of the used space it covered, None before there is one
+    fn scan_gap(&self, drive: &str, used_gb: f64) -> Option<ScanGap> {
+        let drive = normalize_path(drive);
+        let files = self.cached_files(&drive)?;
+        Some(ScanGap {
+            scanned_bytes: files.iter().map(|f| f.size_bytes).sum(),
+            file_count: files.len(),
+            used_gb,
+            user_data: self.is_user_data_scan(&drive),
+            system_dirs: self.skipped_system_dirs.get(&drive).cloned().unwrap_or_default(),
+            excluded: self.excluded_counts.get(&drive).copied().unwrap_or(0),
         })
     }
 
-    fn print_file_info(file: &FileInfo) {
-        println!("\n[*] Path: {}", file.full_path);
-        println!("    Size: {:.2} MB / {:.2} GB", file.size_mb, file.size_mb/1000.0);
-        println!("    Last Modified: {}", file.last_modified.as_deref().unwrap_or("Unknown"));
-        if let Some(last_accessed) = &file.last_accessed {
-            println!("    Last Accessed: {}", last_accessed);
+    // analyzes and returns largest folders up to 3 levels deep
+    // hidden folders (those starting with '.') are left out unless include_hidden is on
+    pub fn print_largest_folders(&mut self, drive: &str, options: &ListOptions) -> Result<()> {
+        let top_n = options.count.unwrap_or(self.config.display.top_n);
+        self.collect_and_cache_files(drive)?;
+        let mut folders = self.get_largest_folders(drive, options.order)?;
+        if let Some(min_bytes) = options.min_bytes {
+            folders.retain(|folder| folder.size_bytes >= min_bytes);
         }
+        let folders: Vec<FolderSize> = folders.into_iter().skip(options.offset).take(top_n).collect();
+        self.last_listing = folders.iter().map(|f| f.folder.clone()).collect();
+        Ok(printers::folders(&mut self.out(), self.units(), &folders, options, top_n)?)
     }
 
-    fn collect_and_cache_files(&mut self, drive: &str) -> io::Result<()> {
-        if self.file_cache.contains_key(drive) {
-            println!("Cached file scan found! Proceeding..");
-            return Ok(());
-        } else if self.folder_cache.contains_key(drive) { 
-            println!("Cached folder scan found! Proceeding..");
-            return Ok(());
+    /// The folders right below `drive` as (path, size in bytes, percent of the drive's used
+    /// space), biggest first. Files sitting in the root itself aren't in any of them, so the
+    /// percentages add up to a bit under 100.
+    pub fn folder_space_percentages(&mut self, drive: &str) -> Result<Vec<(PathBuf, u64, f64)>> {
+        let drive = normalize_path(drive);
+        self.collect_and_cache_files(&drive)?;
+        let (total_bytes, free_bytes) = self.drive_space_bytes(&drive)?;
+        let used_bytes = total_bytes.saturating_sub(free_bytes);
+
+        // the folder cache goes 3 levels down, only the first level adds up to the whole
+        let roots: Vec<String> = self.scan_roots(&drive).iter().map(comparable_path).collect();
+        let is_top_level = |folder: &FolderSize| {
+            folder.folder
+                .parent()
+                .is_some_and(|parent| roots.contains(&comparable_path(parent)))
+        };
+        let folders: Vec<FolderSize> = match self.folder_cache.get(&drive) {
+            Some(folders) => folders
+                .iter()
+                .filter(|folder| is_top_level(folder) && !self.is_hidden_folder(&folder.folder))
+                .cloned()
+                .collect(),
+            None => Vec::new(),
+        };
+        Ok(space_shares(folders, used_bytes))
+    }
+
+    /// How each folder of `drive` changed since the scan before the cached one, as (folder,
+    /// bytes back then, bytes now, change in bytes), biggest growth first. The old size is
+    /// `None` for folders that weren't there yet.
+    ///
+    /// Needs two scans of the drive in this session, [`rescan`](Self::rescan) makes the second.
+    pub fn folder_size_deltas(&mut self, drive: &str) -> Result<Vec<FolderDelta>> {
+        let drive = normalize_path(drive);
+        self.collect_and_cache_files(&drive)?;
+        let Some((_, mode, previous)) = self.previous_folders.get(&drive) else {
+            return Err(AnalyzerError::InvalidArgument(format!(
+                "there's no earlier scan of {} to compare with, run rescan {} first", drive, drive
+            )));
+        };
+        // a scan that left out other things would show growth that is only a difference in options
+        if *mode != self.scan_modes.get(&drive).copied() {
+            return Err(AnalyzerError::InvalidArgument(format!(
+                "the earlier scan of {} ran with other scan options, run rescan {} again to compare like with like",
+                drive, drive
+            )));
         }
+        let current = self.folder_cache.get(&drive).map(Vec::as_slice).unwrap_or_default();
+        let listed = |folders: &[FolderSize]| -> Vec<FolderSize> {
+            folders.iter().filter(|folder| !self.is_hidden_folder(&folder.folder)).cloned().collect()
+        };
+        Ok(folder_deltas(&listed(previous), &listed(current)))
+    }
 
-        println!("No cache found, scanning..");
+    pub fn print_folder_growth(&mut self, drive: &str) -> Result<()> {
+        let drive = normalize_path(drive);
+        let deltas = self.folder_size_deltas(&drive)?;
+        let top_n = self.config.display.top_n;
+        let since = self.previous_folders.get(&drive).map(|(scanned_at, _, _)| scanned_at.elapsed()).unwrap_or_default();
+        let deltas: Vec<_> = deltas.into_iter().filter(|(_, _, _, delta)| *delta != 0).take(top_n).collect();
+        self.last_listing = deltas.iter().map(|(folder, _, _, _)| folder.clone()).collect();
+        Ok(printers::folder_growth(&mut self.out(), self.units(), &drive, since, &deltas)?)
+    }
 
-        let file_cache = Arc::new(Mutex::new(Vec::new()));
-        let folder_cache = Arc::new(Mutex::new(Vec::new()));
+    pub fn print_folder_space_percentages(&mut self, drive: &str) -> Result<()> {
+        let shares = self.folder_space_percentages(drive)?;
+        self.last_listing = shares.iter().map(|(folder, _, _)| folder.clone()).collect();
+        Ok(printers::folder_shares(&mut self.out(), self.units(), &normalize_path(drive), &shares)?)
+    }
 
-        // can use WalkDir with max depth to avoid scanning deeply nested directories
-        let walker = WalkDir::new(drive)
-            .into_iter()
-            .filter_map(Result::ok) // Skip errors instead of crashing
-            .filter(|e| e.file_type().is_file()); // Process only files
+    pub fn print_file_type_distribution(&mut self, drive: &str) -> Result<()> {
+        let distribution = self.get_file_type_distribution(drive)?;
+        Ok(printers::type_distribution(
+            &mut self.out(),
+            self.units(),
+            &distribution,
+            self.config.display.top_n,
+            self.config.days.recent_window_days,
+        )?)
+    }
 
-        // process in parallel using Rayon
-        let files: Vec<FileInfo> = walker
-            .par_bridge() // Enables parallel iteration
-            .filter_map(|entry| {
-                let metadata = entry.metadata().ok()?;
-                Some(FileInfo {
-                    full_path: entry.path().to_string_lossy().to_string(),
-                    size_mb: metadata.len() as f64 / MB_TO_BYTES,
-                    last_modified: metadata.modified().ok().map(system_time_to_string),
-                    last_accessed: metadata.accessed().ok().map(system_time_to_string),
-                })
-            })
-            .collect(); // Collect all results in one go (which seems stupid I know)
-        // cache the files
-        {
-            let mut cache = file_cache.lock().unwrap();
-            cache.extend(files);
-        }
+    pub fn print_size_by_category(&mut self, drive: &str) -> Result<()> {
+        let totals = self.size_by_category(drive)?;
+        Ok(printers::category_distribution(&mut self.out(), self.units(), drive, &totals)?)
+    }
 
-        // Cache folder sizes
-        let folders: Vec<FolderSize> = WalkDir::new(drive)
-            .min_depth(1)
-            .max_depth(3)
+    pub fn print_type_dist_per_folder(&mut self, drive: &str) -> Result<()> {
+        let per_folder = self.type_dist_per_folder(drive)?;
+
+        // biggest folders first, judged by the sum of their listed extensions
+        let mut folders: Vec<_> = per_folder
             .into_iter()
-            .filter_map(Result::ok)
-            .filter(|e| e.file_type().is_dir())
-            .filter_map(|entry| self.calculate_folder_size(entry.path()).ok())
+            .filter(|(_, dist)| !dist.is_empty())
             .collect();
-        // cache the folders
-        {
-            let mut cache = folder_cache.lock().unwrap();
-            cache.extend(folders);
+        folders.sort_by_key(|(_, dist)| std::cmp::Reverse(dist.iter().map(|(_, size, _, _)| size).sum::<u64>()));
+        Ok(printers::type_dist_per_folder(&mut self.out(), self.units(), &folders)?)
+    }
+
+    pub fn print_folders_by_content_age(&mut self, drive: &str) -> Result<()> {
+        let folders = self.folders_by_content_age(drive)?;
+        Ok(printers::folder_ages(&mut self.out(), drive, &folders, FOLDER_AGE_SHOWN)?)
+    }
+
+    // every temp location Windows and apps write to, resolved from the environment
+    // %TEMP% and %TMP% usually point at the same place, so duplicates are dropped
+    pub fn get_temp_directories() -> Vec<PathBuf> {
+        let mut dirs: Vec<PathBuf> = ["TEMP", "TMP"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .map(PathBuf::from)
+            .collect();
+        if let Ok(local) = std::env::var("LOCALAPPDATA") {
+            dirs.push(Path::new(&local).join("Temp"));
         }
+        let windows = std::env::var("SystemRoot").unwrap_or_else(|_| "C:\\Windows".to_string());
+        dirs.push(Path::new(&windows).join("Temp"));
 
-        println!("Scanning complete..");
-        // you might ask why do these separately, well.. you never asked
-        self.file_cache.insert(drive.to_string(), Arc::try_unwrap(file_cache).unwrap().into_inner().unwrap());
-        self.folder_cache.insert(drive.to_string(), Arc::try_unwrap(folder_cache).unwrap().into_inner().unwrap());
-        println!("Caching files and folders..");
+        let mut seen = Vec::new();
+        dirs.retain(|dir| {
+            let key = comparable_path(dir);
+            let first = !seen.contains(&key);
+            seen.push(key);
+            first
+        });
+        dirs
+    }
 
-        Ok(())
+    // current size of each temp directory that exists, biggest first
+    pub fn analyze_temp_dirs(&mut self) -> Result<Vec<FolderSize>> {
+        let mut sizes: Vec<FolderSize> = Self::get_temp_directories()
+            .iter()
+            .filter(|dir| dir.is_dir())
+            .map(|dir| self.calculate_folder_size(dir))
+            .collect::<Result<_>>()?;
+        sort_by_size(&mut sizes, SortOrder::Descending);
+        Ok(sizes)
     }
-    fn get_file_type_distribution(&mut self, drive: &str) -> io::Result<Vec<(String, f64, usize)>> {
-        self.collect_and_cache_files(drive)?;
 
-        let file_types: HashMap<String, FileTypeStats> =
-            if let Some(files) = self.file_cache.get(drive) {
-                files
-                    .par_iter()
-                    .fold(
-                        || HashMap::new(),
-                        |mut acc, file_info| {
-                            let ext = Path::new(&file_info.full_path)
-                                .extension()
-                                .map(|e| e.to_string_lossy().to_lowercase())
-                                .unwrap_or_else(|| "(No Extension)".to_string());
-
-                            let size = (file_info.size_mb * MB_TO_BYTES) as u64;
-
-                            let stats: &mut FileTypeStats = acc.entry(ext).or_default();
-                            stats.total_size += size;
-                            stats.count += 1;
-                            acc
-                        },
-                    )
-                    .reduce(
-                        || HashMap::new(),
-                        |mut acc1, acc2| {
-                            for (ext, stats2) in acc2 {
-                                let stats1 = acc1.entry(ext).or_default();
-                                stats1.total_size += stats2.total_size;
-                                stats1.count += stats2.count;
-                            }
-                            acc1
-                        },
-                    )
-            } else {
-                HashMap::new()
-            };
+    pub fn print_temp_dirs(&mut self) -> Result<Vec<FolderSize>> {
+        let sizes = self.analyze_temp_dirs()?;
+        printers::temp_dirs(&mut self.out(), self.units(), &sizes)?;
+        Ok(sizes)
+    }
 
-        let mut distribution: Vec<_> = file_types
-            .into_iter()
-            .map(|(ext, stats)| (ext, stats.total_size as f64 / GB_TO_BYTES, stats.count))
-            .filter(|&(_, size, _)| size > MIN_FILE_TYPE_SIZE_GB)
-            .collect();
+    // directories where installers usually pile up, resolved from the environment
+    // entries whose variable isn't set are skipped
+    pub(crate) fn installer_cache_dirs() -> Vec<String> {
+        let mut dirs = temp_directories();
+        if let Ok(profile) = std::env::var("USERPROFILE") {
+            dirs.push(comparable_path(format!("{}\\Downloads", profile)));
+        }
+        dirs.push(comparable_path("C:/Windows/Installer"));
+        dirs.sort();
+        dirs.dedup();
+        dirs
+    }
 
-        distribution.par_sort_unstable_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
-        Ok(distribution)
+    pub(crate) fn is_cached_installer(file: &FileInfo, cache_dirs: &[String]) -> bool {
+        let is_installer = file.full_path
+            .extension()
+            .map(|e| INSTALLER_EXTS.contains(&e.to_string_lossy().to_lowercase().as_str()))
+            .unwrap_or(false);
+        let path = comparable_path(&file.full_path);
+        is_installer && cache_dirs.iter().any(|dir| is_under(&path, dir))
     }
 
-    fn get_largest_files(&mut self, drive: &str) -> io::Result<Vec<FileInfo>> {
+    // installer packages sitting in temp, download or installer cache folders
+    pub fn find_installer_cache(&mut self, drive: &str) -> Result<Vec<FileInfo>> {
         self.collect_and_cache_files(drive)?;
 
-        if let Some(files) = self.file_cache.get(drive) {
-            let mut result = files.clone();
-            result.par_sort_unstable_by(|a, b| b.size_mb.partial_cmp(&a.size_mb).unwrap());
-            Ok(result)
-        } else {
-            Ok(Vec::new())
-        }
+        let cache_dirs = Self::installer_cache_dirs();
+        let mut files: Vec<FileInfo> = match self.file_cache.get(&normalize_path(drive)) {
+            Some(files) => files
+                .par_iter()
+                .filter(|file| Self::is_cached_installer(file, &cache_dirs))
+                .cloned()
+                .collect(),
+            None => return Ok(Vec::new()),
+        };
+
+        sort_by_size(&mut files, SortOrder::Descending);
+        Ok(files)
     }
-    
-    // main analysis function that calls all the other functions for a full scan
-    pub fn analyze_drive(&mut self, drive: &str) -> io::Result<()> {
-        if !self.drives.contains(&drive.to_string()) {
-            println!("Drive {} is not a valid fixed drive. Valid drives are: {:?}", drive, self.drives);
-            return Ok(());
-        }
 
-        println!("\n=== Storage Distribution Analysis ===");
-        println!("Date: {}", Utc::now().format(DATE_FORMAT));
-        println!("Drive: {}", drive);
+    pub fn print_installer_cache(&mut self, drive: &str) -> Result<()> {
+        let files = self.find_installer_cache(drive)?;
+        self.last_listing = files.iter().map(|f| f.full_path.clone()).collect();
+        Ok(printers::installer_cache(&mut self.out(), self.units(), &files)?)
+    }
 
-        self.print_drive_space_overview(drive)?;
-        self.print_largest_folders(drive)?;
-        self.print_file_type_distribution(drive)?;
-        self.print_largest_files(drive)?;
-        self.print_recent_large_files(drive)?;
-        self.print_old_large_files(drive)?;
+    /// Every file on `drive` with one of [`EXECUTABLE_EXTS`], biggest first.
+    pub fn find_executables(&mut self, drive: &str) -> Result<Vec<FileInfo>> {
+        self.collect_and_cache_files(drive)?;
+        let mut files: Vec<FileInfo> = self
+            .scanned_files(drive)?
+            .par_iter()
+            .filter(|file| {
+                file.full_path
+                    .extension()
+                    .is_some_and(|e| EXECUTABLE_EXTS.contains(&normalize_extension(&e.to_string_lossy()).as_str()))
+            })
+            .cloned()
+            .collect();
+        sort_by_size(&mut files, SortOrder::Descending);
+        Ok(files)
+    }
 
-        Ok(())
+    pub fn print_executables(&mut self, drive: &str) -> Result<()> {
+        let files = self.find_executables(drive)?;
+        self.last_listing = files.iter().take(EXECUTABLES_SHOWN).map(|f| f.full_path.clone()).collect();
+        Ok(printers::executables(&mut self.out(), self.units(), &files, EXECUTABLES_SHOWN)?)
     }
 
-    pub fn print_drive_space_overview(&self, drive: &str) -> io::Result<()> {
-        match self.get_drive_space(drive) {
-            Ok(analysis) => {
-                println!("\n--- Drive Space Overview ---");
-                println!("Total Size: {:.2} GB", analysis.total_size);
-                println!("Used Space: {:.2} GB", analysis.used_space);
-                println!("Free Space: {:.2} GB ({:.2}%)", analysis.free_space, analysis.free_space_percent);
-                Ok(())
-            }
-            Err(e) => {
-                eprintln!("Failed to analyze drive '{}': {}", drive, e);
-                Err(e)
+    // the OneDrive folders in comparable_path form, the client keeps these variables in step
+    // with its registry settings so there's no need to read those
+    pub(crate) fn onedrive_roots() -> Vec<String> {
+        let mut roots: Vec<String> = ONEDRIVE_ENV_VARS
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .filter(|root| !root.is_empty())
+            .map(|root| comparable_path(&root))
+            .collect();
+        if roots.is_empty()
+            && let Some(home) = home_dir() {
+            let root = home.join("OneDrive");
+            if root.is_dir() {
+                roots.push(comparable_path(root));
             }
         }
+        roots.sort();
+        roots.dedup();
+        roots
     }
 
-    // analyzes and returns largest folders up to 3 levels deep
-    // excludes hidden folders (those starting with '.')
-    pub fn print_largest_folders(&mut self, drive: &str) -> io::Result<()> {
-        println!("\n--- Largest Folders (Top 10) ---");
-        
-        if !self.folder_cache.contains_key(drive) {
-            self.collect_and_cache_files(drive)?;
-        }
-        
-        let folders = self.get_largest_folders(drive)?;
+    /// Files on `drive` of at least `min_mb` that live outside every OneDrive folder, biggest first.
+    pub fn find_unsynced_large_files(&mut self, drive: &str, min_mb: f64) -> Result<Vec<FileInfo>> {
+        self.collect_and_cache_files(drive)?;
+        let roots = Self::onedrive_roots();
+        let min_bytes = mb_to_bytes(self.units(), min_mb);
+        let mut files: Vec<FileInfo> = self
+            .scanned_files(drive)?
+            .par_iter()
+            .filter(|file| file.size_bytes >= min_bytes)
+            .filter(|file| {
+                let path = comparable_path(&file.full_path);
+                !roots.iter().any(|root| is_under(&path, root))
+            })
+            .cloned()
+            .collect();
+        sort_by_size(&mut files, SortOrder::Descending);
+        Ok(files)
+    }
 
-        let mut cnt: i8 = 0;
-        for folder in folders.iter().take(10) {
-            cnt += 1;
-            println!("\n[{}] {}", cnt, folder.folder);
-            println!("  Size: {:.2} GB", folder.size_gb);
-            println!("  Files: {}", folder.file_count);
-        }
+    pub fn print_unsynced_large_files(&mut self, drive: &str, min_mb: f64) -> Result<()> {
+        let files = self.find_unsynced_large_files(drive, min_mb)?;
+        self.last_listing = files.iter().map(|f| f.full_path.clone()).collect();
+        Ok(printers::unsynced_files(&mut self.out(), self.units(), &files, &Self::onedrive_roots(), min_mb)?)
+    }
 
-        Ok(())
+    // merges every cleanup heuristic into one deduplicated list, biggest first
+    pub fn cleanup_candidates(&mut self, drive: &str) -> Result<Vec<CleanupCandidate>> {
+        self.collect_and_cache_files(drive)?;
+
+        match self.file_cache.get(&normalize_path(drive)) {
+            Some(files) => Ok(cleanup::collect_candidates(files, self.units())),
+            None => Ok(Vec::new()),
+        }
     }
 
-    pub fn print_file_type_distribution(&mut self, drive: &str) -> io::Result<()> {
-        println!("\n--- File Type Distribution (Top 10) ---");
-        let distribution = self.get_file_type_distribution(drive)?;
-        for (ext, size, count) in distribution.iter().take(10) {
-            println!(
-                "\n[>] {} \n  Count: {} \n  Size: {:.2} GB",
-                ext, count, size
-            );
+    pub fn print_cleanup_candidates(&mut self, drive: &str) -> Result<Vec<CleanupCandidate>> {
+        let candidates = self.cleanup_candidates(drive)?;
+        if !candidates.is_empty() {
+            self.last_listing = candidates.iter().map(|c| c.path.clone()).collect();
         }
-        Ok(())
+        printers::cleanup_candidates(&mut self.out(), self.units(), &candidates)?;
+        Ok(candidates)
     }
 
-    fn get_largest_folders(&self, drive: &str) -> io::Result<Vec<FolderSize>> {
-        if let Some(cached_folders) = self.folder_cache.get(drive) {
+    /// Folders over `thresholds.min_folder_size_gb`, sorted by size in `order`.
+    ///
+    /// Uses the cached scan when there is one, otherwise walks the first three levels itself.
+    pub fn get_largest_folders(&self, drive: &str, order: SortOrder) -> Result<Vec<FolderSize>> {
+        let min_bytes = gb_to_bytes(self.units(), self.config.thresholds.min_folder_size_gb);
+        if let Some(cached_folders) = self.folder_cache.get(&normalize_path(drive)) {
             // Use the cached folder sizes, filtering out folders that are too small.
             let mut folders: Vec<FolderSize> = cached_folders
                 .iter()
                 .cloned()
-                .filter(|folder| folder.size_gb > MIN_FOLDER_SIZE_GB)
+                .filter(|folder| folder.size_bytes > min_bytes && !self.is_hidden_folder(&folder.folder))
                 .collect();
-            // Sort descending by size.
-            folders.sort_unstable_by(|a, b| b.size_gb.partial_cmp(&a.size_gb).unwrap());
+            sort_by_size(&mut folders, order);
             return Ok(folders);
         }
         // Fallback in the unlikely event the cache is missing.
@@ -326,111 +2079,476 @@ impl StorageAnalyzer {
             .into_iter()
             .par_bridge()
             .filter_map(Result::ok)
-            .filter(|e| e.file_type().is_dir())
-            .filter(|e| {
-                !e.file_name()
-                    .to_str()
-                    .map(|s| s.starts_with('.'))
-                    .unwrap_or(false)
-            })
+            .filter(|e| e.file_type().is_dir() && !self.is_hidden_folder(e.path()))
             .filter_map(|entry| {
                 self.calculate_folder_size(entry.path())
                     .ok()
-                    .filter(|size| size.size_gb > MIN_FOLDER_SIZE_GB)
+                    .filter(|size| size.size_bytes > min_bytes)
             })
             .collect::<Vec<_>>();
-        folders.par_sort_unstable_by(|a, b| b.size_gb.partial_cmp(&a.size_gb).unwrap());
+        sort_by_size(&mut folders, order);
         Ok(folders)
     }
 
-    fn calculate_folder_size(&self, path: &Path) -> io::Result<FolderSize> {
-        let files: Vec<_> = WalkDir::new(path)
+    pub fn calculate_folder_size(&self, path: &Path) -> Result<FolderSize> {
+        self.folder_size_within(path, None, self.follows_links())
+    }
+
+    /// Sizes `path` with the depth limit and link following of `options` instead of the
+    /// analyzer's, nothing is cached. The other options only make sense for whole scans.
+    pub fn calculate_folder_size_with(&self, path: &Path, options: &ScanOptions) -> Result<FolderSize> {
+        options.validate().map_err(AnalyzerError::InvalidArgument)?;
+        self.folder_size_within(path, options.max_depth, options.follow_links.unwrap_or_else(|| self.follows_links()))
+    }
+
+    // `max_depth` counts from `path` itself
+    fn folder_size_within(&self, path: &Path, max_depth: Option<usize>, follow_links: bool) -> Result<FolderSize> {
+        let mut visited = HashSet::new();
+        let entries: Vec<_> = WalkDir::new(path)
+            .follow_links(follow_links)
+            .max_depth(max_depth.unwrap_or(usize::MAX))
             .into_iter()
+            .filter_entry(|e| !self.is_skipped(e.path()) && !is_repeat_link(&mut visited, e))
             .par_bridge()
             .filter_map(Result::ok)
-            .filter(|e| e.file_type().is_file())
+            .filter(|e| e.file_type().is_file() || (e.depth() == 1 && e.file_type().is_dir()))
             .collect();
+        let subfolder_count = entries.iter().filter(|e| e.file_type().is_dir()).count();
 
-        let total_size: u64 = files
+        // the sum and the biggest file come out of the same pass over the files
+        let (size_bytes, file_count, largest) = entries
             .par_iter()
-            .map(|entry| entry.metadata().map(|m| m.len()).unwrap_or(0))
-            .sum();
+            .filter(|entry| entry.file_type().is_file())
+            .map(|entry| {
+                let len = entry.metadata().map(|m| m.len()).unwrap_or(0);
+                (len, 1, Some((len, entry)))
+            })
+            .reduce(
+                || (0, 0, None),
+                |(size_a, count_a, max_a), (size_b, count_b, max_b)| {
+                    let max = match (max_a, max_b) {
+                        (Some(a), Some(b)) => Some(if b.0 > a.0 { b } else { a }),
+                        (a, b) => a.or(b),
+                    };
+                    (size_a + size_b, count_a + count_b, max)
+                },
+            );
 
         Ok(FolderSize {
-            folder: path.to_string_lossy().to_string(),
-            size_gb: total_size as f64 / GB_TO_BYTES,
-            file_count: files.len(),
+            folder: path.to_path_buf(),
+            folder_lossy: false,
+            size_bytes,
+            file_count,
+            subfolder_count,
+            largest_file: largest.and_then(|(_, entry)| Self::file_info_from_entry(entry).ok()),
         })
     }
 
-    pub fn print_largest_files(&mut self, drive: &str) -> io::Result<()> {
-        println!("\n--- Largest Files ---");
-        let files = self.get_largest_files(drive)?;
-        for file in files.iter().take(10) {
-            Self::print_file_info(file)
-        }
-        Ok(())
+    // patterns without a slash match the file name, the rest match the whole path
+    pub(crate) fn compile_pattern(pattern: &str) -> Result<(GlobMatcher, bool)> {
+        let matcher = GlobBuilder::new(&pattern.replace('\\', "/"))
+            .case_insensitive(true)
+            .literal_separator(false)
+            .build()
+            .map_err(|e| AnalyzerError::InvalidArgument(e.to_string()))?
+            .compile_matcher();
+        Ok((matcher, pattern.contains(['/', '\\'])))
+    }
+
+    // finds files whose name (or path) matches a glob like "*.log" or "**/cache/*"
+    pub fn find_files_by_pattern(&mut self, drive: &str, pattern: &str) -> Result<Vec<FileInfo>> {
+        let (matcher, match_full_path) = Self::compile_pattern(pattern)?;
+        let is_match = |file: &FileInfo| {
+            let path = file.full_path.as_path();
+            if match_full_path {
+                matcher.is_match(file.full_path.to_string_lossy().replace('\\', "/"))
+            } else {
+                path.file_name().map(|name| matcher.is_match(name)).unwrap_or(false)
+            }
+        };
+
+        let mut files: Vec<FileInfo> = if self.use_streaming && !self.file_cache.contains_key(&normalize_path(drive)) {
+            self.file_stream(drive).filter(|file| is_match(file)).collect()
+        } else {
+            self.collect_and_cache_files(drive)?;
+            match self.file_cache.get(&normalize_path(drive)) {
+                Some(files) => files.iter().filter(|file| is_match(file)).cloned().collect(),
+                None => Vec::new(),
+            }
+        };
+
+        sort_by_size(&mut files, SortOrder::Descending);
+        Ok(files)
+    }
+
+    pub fn print_files_by_pattern(&mut self, drive: &str, pattern: &str) -> Result<()> {
+        let files = self.find_files_by_pattern(drive, pattern)?;
+        self.last_listing = files.iter().take(50).map(|f| f.full_path.clone()).collect();
+        Ok(printers::files_matching(&mut self.out(), self.units(), pattern, &files)?)
+    }
+
+    // `files` comes sorted already, the filters run before the top-N cut so a smaller
+    // result means that's really all there is
+    fn print_top_files(&mut self, title: &str, files: Vec<FileInfo>, options: &ListOptions) -> io::Result<()> {
+        let top_n = options.count.unwrap_or(self.config.display.top_n);
+        let files = options.apply(files, top_n);
+        self.last_listing = files.iter().map(|f| f.full_path.clone()).collect();
+        printers::top_files(&mut self.out(), self.units(), title, &files, options, top_n)
+    }
+
+    pub fn print_largest_files(&mut self, drive: &str, options: &ListOptions) -> Result<()> {
+        let top_n = options.count.unwrap_or(self.config.display.top_n);
+        let files = self.rank_files_where(drive, options.by, options.order, options.offset, top_n, |file| options.keeps(file))?;
+        self.last_listing = files.iter().map(|f| f.full_path.clone()).collect();
+        Ok(printers::top_files(&mut self.out(), self.units(), ranking_title(options.by), &files, options, top_n)?)
+    }
+
+    /// Prints `page_size` files of a ranking from `ranked_files`, starting at `offset`, numbered
+    /// from 1 like any other listing so open and delete can pick from it.
+    pub fn print_files_page(&mut self, ranked: &[FileInfo], options: &ListOptions, offset: usize, page_size: usize) -> Result<()> {
+        let page = &ranked[offset.min(ranked.len())..offset.saturating_add(page_size).min(ranked.len())];
+        self.last_listing = page.iter().map(|f| f.full_path.clone()).collect();
+        Ok(printers::ranked_page(&mut self.out(), self.units(), ranking_title(options.by), page, offset, ranked.len(), options)?)
     }
     
-    // gets recently modified large files (within last 30 days)
-    fn get_recent_large_files(&mut self, drive: &str) -> io::Result<Vec<FileInfo>> {
+    /// Files modified before or after `days` ago, sorted by size in `order`.
+    pub fn large_files_by_age(&mut self, drive: &str, age: Age, days: i64, order: SortOrder) -> Result<Vec<FileInfo>> {
         self.collect_and_cache_files(drive)?;
 
-        let mut files = if let Some(files) = self.file_cache.get(drive) {
+        let mut files = if let Some(files) = self.file_cache.get(&normalize_path(drive)) {
             files.clone()
         } else {
             return Ok(Vec::new());
         };
 
-        let thirty_days_ago = Utc::now().naive_utc() - Duration::days(30);
+        let cutoff = Utc::now() - Duration::days(days);
 
         files.retain(|file| {
-            NaiveDateTime::parse_from_str(&file.last_modified.as_deref().unwrap_or("Unknown"), DATE_FORMAT)
-                .map(|dt| dt > thirty_days_ago)
+            file.last_modified
+                .map(|dt| match age {
+                    Age::NewerThan => dt > cutoff,
+                    Age::OlderThan => dt < cutoff,
+                })
                 .unwrap_or(false)
         });
 
-        files.sort_by(|a, b| b.size_mb.partial_cmp(&a.size_mb).unwrap());
+        sort_by_size(&mut files, order);
         Ok(files)
     }
 
-    pub fn print_recent_large_files(&mut self, drive: &str) -> io::Result<()> {
-        println!("\n--- Recent Large Files ---");
-        let files = self.get_recent_large_files(drive)?;
-        for file in files.iter().take(10) {
-            Self::print_file_info(file)
+    /// Files modified within the last `days.recent_window_days` days (30 by default), biggest first.
+    pub fn get_recent_large_files(&mut self, drive: &str) -> Result<Vec<FileInfo>> {
+        self.large_files_by_age(drive, Age::NewerThan, self.config.days.recent_window_days, SortOrder::Descending)
+    }
+
+    /// Files not modified for `days.old_window_days` days (180 by default), biggest first.
+    pub fn get_old_large_files(&mut self, drive: &str) -> Result<Vec<FileInfo>> {
+        self.large_files_by_age(drive, Age::OlderThan, self.config.days.old_window_days, SortOrder::Descending)
+    }
+
+    // returns (day, file count, total bytes) for every day in the last `days` days
+    // that had modified files, oldest day first
+    pub fn files_by_day(&mut self, drive: &str, days: u32) -> Result<Vec<(String, usize, u64)>> {
+        self.collect_and_cache_files(drive)?;
+
+        let files = self.scanned_files(drive)?;
+
+        // days are counted on the calendar the output is shown in
+        // the earliest date there is when the window reaches back past it, shifting MIN_UTC into
+        // a timezone behind UTC would fall off the calendar
+        let cutoff = Utc::now().checked_sub_signed(Duration::days(days.into())).map_or(chrono::NaiveDate::MIN, display_date);
+        let mut per_day: HashMap<chrono::NaiveDate, (usize, u64)> = HashMap::new();
+        for file in files {
+            if let Some(day) = file.last_modified.map(display_date).filter(|day| *day > cutoff) {
+                let entry = per_day.entry(day).or_default();
+                entry.0 += 1;
+                entry.1 += file.size_bytes;
+            }
         }
-        Ok(())
+
+        let mut activity: Vec<_> = per_day.into_iter().collect();
+        activity.sort_unstable_by_key(|(day, _)| *day);
+        Ok(activity
+            .into_iter()
+            .map(|(day, (count, size_bytes))| (day.format(DAY_FORMAT).to_string(), count, size_bytes))
+            .collect())
     }
-    
-    // gets old large files (older than 6 months)
-    fn get_old_large_files(&mut self, drive: &str) -> io::Result<Vec<FileInfo>> {
+
+    pub fn print_files_by_day(&mut self, drive: &str, days: u32) -> Result<()> {
+        let activity = self.files_by_day(drive, days)?;
+        Ok(printers::files_by_day(&mut self.out(), self.units(), days, &activity)?)
+    }
+
+    /// Cached folders of at least `min_size_gb` with a file modified in the last `days` days
+    /// anywhere below them, biggest first.
+    pub fn recent_large_folders(&mut self, drive: &str, days: u32, min_size_gb: f64) -> Result<Vec<FolderSize>> {
         self.collect_and_cache_files(drive)?;
+        let drive = normalize_path(drive);
+        // a window reaching back past the start of the calendar covers every file
+        let cutoff = Utc::now().checked_sub_signed(Duration::days(days.into())).unwrap_or(DateTime::<Utc>::MIN_UTC);
+        let min_bytes = gb_to_bytes(self.units(), min_size_gb);
 
-        let mut files = if let Some(files) = self.file_cache.get(drive) {
-            files.clone()
-        } else {
-            return Ok(Vec::new());
+        // every folder holding a recent file, however deep down it is
+        let mut touched: HashSet<&Path> = HashSet::new();
+        for file in self.scanned_files(&drive)? {
+            if file.last_modified.is_some_and(|modified| modified > cutoff) {
+                touched.extend(file.full_path.ancestors().skip(1));
+            }
+        }
+
+        let mut folders: Vec<FolderSize> = self
+            .cached_folders(&drive)
+            .unwrap_or_default()
+            .iter()
+            .filter(|folder| {
+                folder.size_bytes >= min_bytes && touched.contains(folder.folder.as_path()) && !self.is_hidden_folder(&folder.folder)
+            })
+            .cloned()
+            .collect();
+        sort_by_size(&mut folders, SortOrder::Descending);
+        Ok(folders)
+    }
+
+    pub fn print_recent_large_folders(&mut self, drive: &str, days: u32, min_size_gb: f64) -> Result<()> {
+        let folders = self.recent_large_folders(drive, days, min_size_gb)?;
+        self.set_last_listing(folders.iter().map(|f| f.folder.clone()).collect());
+        Ok(printers::recent_large_folders(&mut self.out(), self.units(), &folders, days, min_size_gb)?)
+    }
+
+    // `days` overrides the configured window for this one report
+    pub fn print_recent_large_files(&mut self, drive: &str, days: Option<i64>, options: &ListOptions) -> Result<()> {
+        let days = days.unwrap_or(self.config.days.recent_window_days);
+        let files = self.large_files_by_age(drive, Age::NewerThan, days, options.order)?;
+        Ok(self.print_top_files(&format!("Recent Large Files (last {} days)", days), files, options)?)
+    }
+
+    pub fn print_old_large_files(&mut self, drive: &str, days: Option<i64>, options: &ListOptions) -> Result<()> {
+        let days = days.unwrap_or(self.config.days.old_window_days);
+        let files = self.large_files_by_age(drive, Age::OlderThan, days, options.order)?;
+        Ok(self.print_top_files(&format!("Old Large Files (>{} days old)", days), files, options)?)
+    }
+}
+
+// known temp directories, in comparable_path form
+pub(crate) fn temp_directories() -> Vec<String> {
+    StorageAnalyzer::get_temp_directories()
+        .iter()
+        .map(comparable_path)
+        .collect()
+}
+
+// drops roots listed twice or inside another one, their files would be walked twice
+fn outermost_roots(roots: Vec<String>) -> Vec<String> {
+    let paths: Vec<String> = roots.iter().map(comparable_path).collect();
+    roots
+        .into_iter()
+        .enumerate()
+        .filter(|&(i, _)| {
+            !paths.iter().enumerate().any(|(j, other)| {
+                // under a different root, or the same one seen earlier
+                (*other != paths[i] && is_under(&paths[i], other)) || (*other == paths[i] && j < i)
+            })
+        })
+        .map(|(_, root)| root)
+        .collect()
+}
+
+// pairs up the folders of two scans by path, folders that are gone now aren't listed
+fn folder_deltas(previous: &[FolderSize], current: &[FolderSize]) -> Vec<FolderDelta> {
+    let before: HashMap<&Path, u64> = previous.iter().map(|f| (f.folder.as_path(), f.size_bytes)).collect();
+    let mut deltas: Vec<_> = current
+        .iter()
+        .map(|folder| {
+            let old = before.get(folder.folder.as_path()).copied();
+            let delta = folder.size_bytes as i64 - old.unwrap_or(0) as i64;
+            (folder.folder.clone(), old, folder.size_bytes, delta)
+        })
+        .collect();
+    deltas.sort_by(|a, b| b.3.cmp(&a.3).then_with(|| a.0.cmp(&b.0)));
+    deltas
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::test_util::{file, folder};
+
+    #[test]
+    fn top_level_shares_add_up_to_the_used_space() {
+        let folders = vec![folder("C:/Windows", 30_000), folder("C:/Users", 60_000), folder("C:/Program Files", 9_000)];
+        // the last 1000 bytes are files in the root
+        let shares = space_shares(folders, 100_000);
+
+        let paths: Vec<&Path> = shares.iter().map(|(path, _, _)| path.as_path()).collect();
+        assert_eq!(paths, [Path::new("C:/Users"), Path::new("C:/Windows"), Path::new("C:/Program Files")]);
+        assert!((shares[0].2 - 60.0).abs() < 1e-9);
+        let total: f64 = shares.iter().map(|(_, _, percent)| percent).sum();
+        assert!((total - 99.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn folder_growth_is_sorted_and_marks_new_folders() {
+        let previous = vec![folder("C:/Users", 500), folder("C:/Windows", 900), folder("C:/Old", 50)];
+        let current = vec![folder("C:/Users", 800), folder("C:/Windows", 700), folder("C:/Games", 100)];
+        let deltas = folder_deltas(&previous, &current);
+
+        assert_eq!(
+            deltas,
+            [
+                ("C:/Users".into(), Some(500), 800, 300),
+                ("C:/Games".into(), None, 100, 100),
+                ("C:/Windows".into(), Some(900), 700, -200),
+            ]
+        );
+    }
+
+    #[test]
+    fn recent_bytes_only_count_files_modified_since_the_cutoff() {
+        let now = Utc::now();
+        let file = |path: &str, size_bytes, days_ago: Option<i64>| FileInfo {
+            last_modified: days_ago.map(|days| now - Duration::days(days)),
+            ..file(path, size_bytes)
         };
+        let files = [
+            file("C:/logs/a.log", 400, Some(1)),
+            file("C:/logs/b.log", 100, Some(60)),
+            file("C:/videos/old.mp4", 900, Some(400)),
+            file("C:/videos/unknown.mp4", 50, None),
+        ];
+        let distribution = StorageAnalyzer::group_by_extension(&files, None, now - Duration::days(30));
+        assert_eq!(distribution, [("mp4".to_string(), 950, 2, 0), ("log".to_string(), 500, 2, 400)]);
+    }
 
-        let six_months_ago = Utc::now().naive_utc() - Duration::days(180);
+    #[test]
+    fn the_cache_can_be_read_from_behind_a_shared_lock() {
+        fn share<T: Send + Sync>() {}
+        share::<StorageAnalyzer>();
 
-        files.retain(|file| {
-            NaiveDateTime::parse_from_str(&file.last_modified.as_deref().unwrap_or("Unknown"), DATE_FORMAT)
-                .map(|dt| dt < six_months_ago)
-                .unwrap_or(false)
-        });
+        let analyzer = StorageAnalyzer::new();
+        assert!(analyzer.cached_files("C:/").is_none());
+        assert!(analyzer.cached_folders("C:/").is_none());
+        assert!(analyzer.cache_info("C:/").is_none());
+    }
 
-        files.sort_by(|a, b| b.size_mb.partial_cmp(&a.size_mb).unwrap());
-        Ok(files)
+    #[test]
+    fn every_file_counts_toward_exactly_one_category() {
+        let files = [
+            file("C:/Videos/trip.MP4", 5_000),
+            file("C:/Music/song.mp3", 1_000),
+            file("C:/Docs/letter.pdf", 200),
+            file("C:/src/main.rs", 50),
+            file("C:/README", 10),
+        ];
+        let totals = category_totals(&files);
+        assert_eq!(totals[0], (FileCategory::Media, 2, 6_000));
+        assert_eq!(totals[1], (FileCategory::Document, 1, 200));
+        assert_eq!(totals[2], (FileCategory::Code, 1, 50));
+        assert_eq!(totals[3], (FileCategory::Other, 1, 10));
+        // empty categories are still listed, at the bottom
+        assert_eq!(totals.len(), FileCategory::ALL.len());
+        assert_eq!(totals.iter().map(|(_, count, _)| count).sum::<usize>(), files.len());
     }
 
-    pub fn print_old_large_files(&mut self, drive: &str) -> io::Result<()> {
-        println!("\n--- Old Large Files (>6 months old) ---");
-        let files = self.get_old_large_files(drive)?;
-        for file in files.iter().take(10) {
-            Self::print_file_info(file)
+    #[test]
+    fn only_corruption_counts_as_a_filesystem_error() {
+        // ERROR_FILE_CORRUPT, ERROR_DISK_CORRUPT and ERROR_CRC
+        for code in [1392, 1393, 23] {
+            assert!(StorageAnalyzer::is_fs_error(&io::Error::from_raw_os_error(code)), "{}", code);
         }
-        Ok(())
+        // access denied, sharing violation, path too long and a file that's gone
+        for code in [5, 32, 206, 2] {
+            assert!(!StorageAnalyzer::is_fs_error(&io::Error::from_raw_os_error(code)), "{}", code);
+        }
+        assert!(!StorageAnalyzer::is_fs_error(&io::Error::new(ErrorKind::InvalidData, "no os code")));
+    }
+
+    #[test]
+    fn only_the_first_n_are_kept_in_order() {
+        let numbers = vec![5, 3, 9, 1, 7, 3];
+        assert_eq!(first_n_by(numbers.clone(), 3, |a: &i32, b| a.cmp(b)), [1, 3, 3]);
+        assert_eq!(first_n_by(numbers.clone(), 2, |a: &i32, b| b.cmp(a)), [9, 7]);
+        assert_eq!(first_n_by(numbers.clone(), 10, |a: &i32, b| a.cmp(b)), [1, 3, 3, 5, 7, 9]);
+        assert!(first_n_by(numbers, 0, |a: &i32, b| a.cmp(b)).is_empty());
+    }
+
+    #[test]
+    fn nested_and_repeated_roots_are_walked_once() {
+        let roots = ["C:/Users/me", "D:/Data", "c:\\users\\ME\\Documents", "d:/data/", "D:/DataSets"].map(String::from).to_vec();
+        assert_eq!(outermost_roots(roots), ["C:/Users/me", "D:/Data", "D:/DataSets"]);
+    }
+
+    #[test]
+    fn folder_growth_needs_two_scans_made_the_same_way() {
+        let dir = env::temp_dir().join("rusty-analyser-test-growth-mode");
+        fs::create_dir_all(dir.join("nested")).unwrap();
+        fs::write(dir.join("nested/file.bin"), [0u8; 512]).unwrap();
+        let drive = dir.to_string_lossy().to_string();
+
+        let mut analyzer = StorageAnalyzer::new();
+        analyzer.set_quiet(true);
+        analyzer.collect_and_cache_files(&drive).unwrap();
+        analyzer.set_scan_options(&ScanOptions { no_exclude: Some(true), ..ScanOptions::default() });
+        analyzer.rescan(&drive).unwrap();
+        assert!(matches!(analyzer.folder_size_deltas(&drive), Err(AnalyzerError::InvalidArgument(_))));
+
+        analyzer.rescan(&drive).unwrap();
+        assert!(analyzer.folder_size_deltas(&drive).unwrap().iter().all(|(_, _, _, delta)| *delta == 0));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn a_profile_can_turn_off_what_the_config_turns_on() {
+        let mut config = Config::default();
+        config.scan.follow_links = true;
+        let mut analyzer = StorageAnalyzer::with_config(config);
+        assert_eq!(analyzer.options_in_effect().follow_links, Some(true));
+        analyzer.set_scan_options(&ScanOptions { follow_links: Some(false), ..ScanOptions::default() });
+        assert_eq!(analyzer.options_in_effect().follow_links, Some(false));
+        analyzer.set_scan_options(&ScanOptions::default());
+        assert_eq!(analyzer.options_in_effect().follow_links, Some(true));
+    }
+
+    #[test]
+    fn each_analyzer_keeps_its_own_units() {
+        let mut si = StorageAnalyzer::new();
+        si.set_units(Units::Decimal);
+        let iec = StorageAnalyzer::new();
+        assert_eq!(human_readable_size(si.units(), 1_500), "1.50 KB");
+        assert_eq!(human_readable_size(iec.units(), 1_536), "1.50 KiB");
+    }
+
+    #[test]
+    fn a_scan_remembers_the_options_it_ran_with() {
+        let dir = env::temp_dir().join("rusty-analyser-test-scan-options");
+        fs::create_dir_all(dir.join("nested")).unwrap();
+        fs::write(dir.join("nested/big.bin"), [0u8; 2048]).unwrap();
+        fs::write(dir.join("small.txt"), "tiny").unwrap();
+        let drive = dir.to_string_lossy().to_string();
+
+        let mut analyzer = StorageAnalyzer::new();
+        analyzer.set_quiet(true);
+        let broken = ScanOptions { max_depth: Some(0), ..ScanOptions::default() };
+        assert!(matches!(analyzer.collect_and_cache_files_with(&drive, &broken), Err(AnalyzerError::InvalidArgument(_))));
+        assert!(analyzer.cache_info(&drive).is_none());
+
+        let options = ScanOptions { min_file_size: Some("1KB".to_string()), ..ScanOptions::default() };
+        analyzer.collect_and_cache_files_with(&drive, &options).unwrap();
+        let info = analyzer.cache_info(&drive).unwrap();
+        assert_eq!(info.file_count, 1);
+        assert_eq!(info.options.min_file_size.as_deref(), Some("1KB"));
+        assert_eq!(info.options.follow_links, Some(false));
+        assert!(info.is_partial());
+        // the options were for that one scan only
+        assert_eq!(analyzer.options_in_effect().min_file_size, None);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn files_are_grouped_under_the_first_folder_below_the_drive() {
+        assert_eq!(top_level_folder("C:/", Path::new("C:/Users/me/notes.txt")), Path::new("C:/Users"));
+        assert_eq!(top_level_folder("C:/", Path::new("C:/Windows/win.ini")), Path::new("C:/Windows"));
+        assert_eq!(top_level_folder("C:/", Path::new("C:/pagefile.sys")), Path::new("C:/"));
     }
 }
@@ -0,0 +1,25 @@
+// the few colored report lines go through here, so without the `color` feature they come out as plain text
+#[cfg(feature = "color")]
+pub(crate) use colored::Colorize;
+
+#[cfg(not(feature = "color"))]
+pub(crate) trait Colorize {
+    fn red(self) -> String;
+    fn green(self) -> String;
+    fn bright_white(self) -> String;
+}
+
+#[cfg(not(feature = "color"))]
+impl Colorize for &str {
+    fn red(self) -> String {
+        self.to_string()
+    }
+
+    fn green(self) -> String {
+        self.to_string()
+    }
+
+    fn bright_white(self) -> String {
+        self.to_string()
+    }
+}
@@ -0,0 +1,11 @@
+// factories for the unit tests, everything a test doesn't name is left empty
+use super::types::{FileInfo, FolderSize};
+
+pub fn file(path: &str, size_bytes: u64) -> FileInfo {
+    FileInfo { full_path: path.into(), full_path_lossy: false, size_bytes, last_modified: None, last_accessed: None, created: None }
+}
+
+// a folder holding a single file, enough for anything that only looks at sizes
+pub fn folder(path: &str, size_bytes: u64) -> FolderSize {
+    FolderSize { folder: path.into(), size_bytes, file_count: 1, ..FolderSize::default() }
+}
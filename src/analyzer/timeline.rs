@@ -0,0 +1,34 @@
+use super::{
+    error::{AnalyzerError, Result},
+    printers,
+    storage::StorageAnalyzer,
+    utils::filetime_to_utc
+};
+use chrono::{DateTime, Utc};
+use std::{io, os::windows::fs::MetadataExt};
+use walkdir::WalkDir;
+
+impl StorageAnalyzer {
+    // every folder up to `depth` levels deep with when it was created, oldest first
+    // walks the drive itself, the folder cache doesn't keep creation times
+    pub fn folder_creation_timeline(&self, drive: &str, depth: usize) -> Result<Vec<(String, DateTime<Utc>)>> {
+        let mut timeline: Vec<(String, DateTime<Utc>)> = WalkDir::new(drive)
+            .min_depth(1)
+            .max_depth(depth)
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter(|e| e.file_type().is_dir())
+            .filter_map(|entry| {
+                let created = filetime_to_utc(entry.metadata().ok()?.creation_time())?;
+                Some((entry.path().to_string_lossy().to_string(), created))
+            })
+            .collect();
+        timeline.sort_by_key(|(_, created)| *created);
+        Ok(timeline)
+    }
+
+    pub fn print_folder_timeline(&mut self, drive: &str, depth: usize) -> Result<()> {
+        let timeline = self.folder_creation_timeline(drive, depth)?;
+        Ok(printers::folder_timeline(&mut self.out(), drive, &timeline)?)
+    }
+}
@@ -1,30 +1,737 @@
-use serde::Serialize;
+use chrono::{DateTime, Utc};
+use serde::{ser::SerializeStruct, Deserialize, Serialize, Serializer};
+use std::{
+    cmp::Ordering,
+    fmt,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+use super::{
+    config::{ScanOptions, Units},
+    constants::*,
+    utils::{bytes_to_gb, bytes_to_mb, format_gb, format_mb, format_time, human_readable_size, normalize_extension},
+};
 
-#[derive(Debug, Serialize)]
+/// Space on a drive in GB (or GiB, following the units it was made with).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct DriveAnalysis {
+    // which drive this is (e.g. "C:/") and its volume label, so a list of these stands on its own
+    #[serde(default)]
+    pub drive: String,
+    #[serde(default)]
+    pub label: Option<String>,
     pub total_size: f64,
     pub used_space: f64,
     pub free_space: f64,
     pub free_space_percent: f64,
 }
 
-#[derive(Debug, Clone, Serialize)]
+impl DriveAnalysis {
+    /// From the total and free byte counts, a drive reporting no size at all has 0% free.
+    pub fn from_bytes(drive: &str, label: Option<String>, total_bytes: u64, free_bytes: u64, units: Units) -> Self {
+        let total_size = bytes_to_gb(units, total_bytes);
+        let free_space = bytes_to_gb(units, free_bytes);
+        DriveAnalysis {
+            drive: drive.to_string(),
+            label,
+            total_size,
+            used_space: total_size - free_space,
+            free_space,
+            free_space_percent: if total_bytes == 0 { 0.0 } else { free_bytes as f64 / total_bytes as f64 * 100.0 },
+        }
+    }
+
+    /// The drive followed by its label, e.g. `C:/ (Windows)`.
+    pub fn name(&self) -> String {
+        match &self.label {
+            Some(label) => format!("{} ({})", self.drive, label),
+            None => self.drive.clone(),
+        }
+    }
+}
+
+// a drive under the low free space threshold, critical once it's under the critical one too
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiskWarning {
+    pub drive: String,
+    pub label: Option<String>,
+    pub free_space_percent: f64,
+    pub critical: bool,
+}
+
+// sizes are exact byte counts, MB/GB only show up when something is printed or serialized
+/// A folder with the total size and number of files below it.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
 pub struct FolderSize {
-    pub folder: String,
-    pub size_gb: f64,
+    pub folder: PathBuf,
+    // loaded back from a snapshot that could only save it with replacement characters
+    #[serde(default)]
+    pub folder_lossy: bool,
+    pub size_bytes: u64,
     pub file_count: usize,
+    // folders right inside this one, not counting deeper levels
+    #[serde(default)]
+    pub subfolder_count: usize,
+    // the biggest file anywhere below it, picked up while the sizes are added up
+    #[serde(default)]
+    pub largest_file: Option<FileInfo>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+impl FolderSize {
+    /// Bytes per file, 0 for a folder without files.
+    pub fn average_file_bytes(&self) -> u64 {
+        self.size_bytes.checked_div(self.file_count as u64).unwrap_or(0)
+    }
+}
+
+/// One scanned file.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
 pub struct FileInfo {
-    pub full_path: String,
-    pub size_mb: f64,
-    pub last_modified: Option<String>,
-    pub last_accessed: Option<String>,
+    // exactly as the OS gave it, only turned into text to be shown or serialized
+    pub full_path: PathBuf,
+    // loaded back from a snapshot that could only save it with replacement characters, so it
+    // isn't the name on disk any more
+    #[serde(default)]
+    pub full_path_lossy: bool,
+    pub size_bytes: u64,
+    // real instants, only formatted (in local time by default) when printed
+    pub last_modified: Option<DateTime<Utc>>,
+    pub last_accessed: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub created: Option<DateTime<Utc>>,
+}
+
+// paths go out as UTF-8, a name that isn't valid Unicode (unpaired surrogates from old tools)
+// gets replacement characters and `<key>_lossy: true` next to it, so nobody trusts it to open the file
+// `lossy` keeps the flag on a path that already lost its original name when it was read back
+fn serialize_path<S: SerializeStruct>(
+    state: &mut S,
+    key: &'static str,
+    lossy_key: &'static str,
+    path: &Path,
+    lossy: bool,
+) -> Result<(), S::Error> {
+    state.serialize_field(key, &path.to_string_lossy())?;
+    match path.to_str() {
+        Some(_) if !lossy => state.skip_field(lossy_key),
+        _ => state.serialize_field(lossy_key, &true),
+    }
+}
+
+// serialized with the byte count plus a readable `size` next to it, reading one back only uses the bytes
+impl Serialize for FolderSize {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("FolderSize", 8)?;
+        serialize_path(&mut state, "folder", "folder_lossy", &self.folder, self.folder_lossy)?;
+        state.serialize_field("size_bytes", &self.size_bytes)?;
+        state.serialize_field("size", &human_readable_size(Units::Binary, self.size_bytes))?;
+        state.serialize_field("file_count", &self.file_count)?;
+        state.serialize_field("subfolder_count", &self.subfolder_count)?;
+        state.serialize_field("average_file_bytes", &self.average_file_bytes())?;
+        state.serialize_field("largest_file", &self.largest_file)?;
+        state.end()
+    }
+}
+
+// the serialized "size" next to size_bytes is always IEC, so the same scan gives the same JSON
+// whatever units the analyzer that wrote it was showing
+
+/// Something printed with its sizes in `units`, from the `shown` methods below. They go through the
+/// shared formatters, so they follow the configured date format like everything else that gets printed.
+pub struct Shown<'a, T> {
+    value: &'a T,
+    units: Units,
+}
+
+impl DriveAnalysis {
+    /// `units` has to be the one the drive's GB figures were worked out in.
+    pub fn shown(&self, units: Units) -> Shown<'_, Self> {
+        Shown { value: self, units }
+    }
+}
+
+impl FolderSize {
+    pub fn shown(&self, units: Units) -> Shown<'_, Self> {
+        Shown { value: self, units }
+    }
+}
+
+impl FileInfo {
+    pub fn shown(&self, units: Units) -> Shown<'_, Self> {
+        Shown { value: self, units }
+    }
+}
+
+/// The three space lines of a drive overview.
+impl fmt::Display for Shown<'_, DriveAnalysis> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (space, units) = (self.value, self.units);
+        writeln!(f, "Total Size: {}", format_gb(units, space.total_size))?;
+        writeln!(f, "Used Space: {}", format_gb(units, space.used_space))?;
+        write!(f, "Free Space: {} ({:.2}%)", format_gb(units, space.free_space), space.free_space_percent)
+    }
+}
+
+/// The folder, then its size and file count indented below it.
+impl fmt::Display for Shown<'_, FolderSize> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (folder, units) = (self.value, self.units);
+        writeln!(f, "{}", folder.folder.display())?;
+        writeln!(f, "  Size: {}", format_gb(units, bytes_to_gb(units, folder.size_bytes)))?;
+        write!(f, "  Files: {}", folder.file_count)
+    }
+}
+
+/// The path, then size and dates indented below it, last access only when it's known.
+impl fmt::Display for Shown<'_, FileInfo> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (file, units) = (self.value, self.units);
+        writeln!(f, "Path: {}", file.full_path.display())?;
+        writeln!(f, "    Size: {} / {}", format_mb(units, bytes_to_mb(units, file.size_bytes)), format_gb(units, bytes_to_gb(units, file.size_bytes)))?;
+        match file.last_modified {
+            Some(time) => write!(f, "    Last Modified: {}", format_time(time))?,
+            None => write!(f, "    Last Modified: Unknown")?,
+        }
+        if let Some(time) = file.last_accessed {
+            write!(f, "\n    Last Accessed: {}", format_time(time))?;
+        }
+        Ok(())
+    }
+}
+
+/// Anything listed by size, the path breaks ties so sorting the same list twice can't reorder it.
+pub trait HasSize {
+    fn size_bytes(&self) -> u64;
+    fn path(&self) -> &Path;
+}
+
+impl HasSize for FileInfo {
+    fn size_bytes(&self) -> u64 {
+        self.size_bytes
+    }
+
+    fn path(&self) -> &Path {
+        &self.full_path
+    }
+}
+
+impl HasSize for FolderSize {
+    fn size_bytes(&self) -> u64 {
+        self.size_bytes
+    }
+
+    fn path(&self) -> &Path {
+        &self.folder
+    }
+}
+
+impl HasSize for CleanupCandidate {
+    fn size_bytes(&self) -> u64 {
+        self.size_bytes
+    }
+
+    fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl<T: HasSize + ?Sized> HasSize for &T {
+    fn size_bytes(&self) -> u64 {
+        (**self).size_bytes()
+    }
+
+    fn path(&self) -> &Path {
+        (**self).path()
+    }
+}
+
+/// Orders by size, smallest first, then by path.
+///
+/// `files.sort_by(|a, b| BySize(b).cmp(&BySize(a)))` is biggest first.
+#[derive(Debug, Clone, Copy)]
+pub struct BySize<T>(pub T);
+
+impl<T: HasSize> Ord for BySize<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0
+            .size_bytes()
+            .cmp(&other.0.size_bytes())
+            .then_with(|| self.0.path().cmp(other.0.path()))
+    }
+}
+
+impl<T: HasSize> PartialOrd for BySize<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: HasSize> PartialEq for BySize<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T: HasSize> Eq for BySize<T> {}
+
+impl Serialize for FileInfo {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("FileInfo", 7)?;
+        serialize_path(&mut state, "full_path", "full_path_lossy", &self.full_path, self.full_path_lossy)?;
+        state.serialize_field("size_bytes", &self.size_bytes)?;
+        state.serialize_field("size", &human_readable_size(Units::Binary, self.size_bytes))?;
+        state.serialize_field("last_modified", &self.last_modified)?;
+        state.serialize_field("last_accessed", &self.last_accessed)?;
+        state.serialize_field("created", &self.created)?;
+        state.end()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FileCategory {
+    Media,
+    Document,
+    Code,
+    Archive,
+    System,
+    Other,
+}
+
+impl FileCategory {
+    pub const ALL: [FileCategory; 6] = [
+        FileCategory::Media,
+        FileCategory::Document,
+        FileCategory::Code,
+        FileCategory::Archive,
+        FileCategory::System,
+        FileCategory::Other,
+    ];
+
+    // expects a lowercase extension without the leading dot
+    pub fn from_extension(ext: &str) -> Self {
+        if MEDIA_EXTS.contains(&ext) {
+            FileCategory::Media
+        } else if DOCUMENT_EXTS.contains(&ext) {
+            FileCategory::Document
+        } else if CODE_EXTS.contains(&ext) {
+            FileCategory::Code
+        } else if ARCHIVE_EXTS.contains(&ext) {
+            FileCategory::Archive
+        } else if SYSTEM_EXTS.contains(&ext) {
+            FileCategory::System
+        } else {
+            FileCategory::Other
+        }
+    }
+
+    // the category of whatever extension `path` has, no extension is Other
+    pub fn of_path(path: &Path) -> Self {
+        let ext = path.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+        Self::from_extension(&ext)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FileCategory::Media => "media",
+            FileCategory::Document => "document",
+            FileCategory::Code => "code",
+            FileCategory::Archive => "archive",
+            FileCategory::System => "system",
+            FileCategory::Other => "other",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Confidence {
+    Low,
+    Medium,
+    High,
+}
+
+// one thing that could be deleted, and why we think so
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct CleanupCandidate {
+    pub path: PathBuf,
+    #[serde(default)]
+    pub path_lossy: bool,
+    pub size_bytes: u64,
+    pub reason: String,
+    pub confidence: Confidence,
+}
+
+impl Serialize for CleanupCandidate {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("CleanupCandidate", 6)?;
+        serialize_path(&mut state, "path", "path_lossy", &self.path, self.path_lossy)?;
+        state.serialize_field("size_bytes", &self.size_bytes)?;
+        state.serialize_field("size", &human_readable_size(Units::Binary, self.size_bytes))?;
+        state.serialize_field("reason", &self.reason)?;
+        state.serialize_field("confidence", &self.confidence)?;
+        state.end()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum ReclaimTier {
+    Safe,
+    ProbablySafe,
+    Aggressive,
+}
+
+// one line of the reclaim estimate, `report` names the command that lists the items
+#[derive(Debug, Clone, Serialize)]
+pub struct ReclaimSource {
+    pub name: &'static str,
+    pub report: &'static str,
+    pub tier: ReclaimTier,
+    pub size_bytes: u64,
+    pub items: usize,
+}
+
+// a file that would shrink nicely with NTFS compression
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompressionCandidate {
+    pub file: FileInfo,
+    // compressed / original size of the sampled prefix
+    pub estimated_ratio: f64,
+    pub estimated_savings_bytes: u64,
+}
+
+// "files with this extension shouldn't take more than limit_gb", set up in the config file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuotaRule {
+    pub extension: String,
+    pub limit_gb: f64,
 }
 
 #[derive(Debug, Default)]
 pub struct FileTypeStats {
-    pub total_size: u64,
+    pub total_bytes: u64,
     pub count: usize,
-}
\ No newline at end of file
+    // the part of total_bytes modified within the recent window
+    pub recent_bytes: u64,
+}
+
+// how much of one extension a drive holds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExtensionStats {
+    pub drive: String,
+    pub extension: String,
+    pub count: usize,
+    pub size_bytes: u64,
+}
+
+// a path the scan couldn't read because of the filesystem itself, not permissions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScanError {
+    pub path: String,
+    pub code: i32,
+}
+
+/// Everything a scan couldn't read (denied, gone by the time it got there, broken), counted
+/// by kind, with the paths of the first `UNREADABLE_PATHS_KEPT` kept.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Unreadable {
+    pub counts: Vec<(ErrorKind, usize)>,
+    pub paths: Vec<(String, ErrorKind)>,
+}
+
+impl Unreadable {
+    pub fn record(&mut self, path: String, kind: ErrorKind) {
+        match self.counts.iter_mut().find(|(k, _)| *k == kind) {
+            Some((_, count)) => *count += 1,
+            None => self.counts.push((kind, 1)),
+        }
+        if self.paths.len() < UNREADABLE_PATHS_KEPT {
+            self.paths.push((path, kind));
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.counts.iter().map(|(_, count)| count).sum()
+    }
+}
+
+// what a folder's DACL allows, as far as the acl-report cares
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AclSummary {
+    // Everyone (S-1-1-0) has an allow entry, or there's no DACL at all
+    pub has_everyone_access: bool,
+    pub ace_count: u32,
+    // false when inheritance was turned off with "protect from parent"
+    pub inherits_parent: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortOrder {
+    // biggest first, what every listing does unless told otherwise
+    #[default]
+    Descending,
+    Ascending,
+}
+
+/// What a file listing is ranked by, [`SortOrder`] picks which end comes first.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortKey {
+    #[default]
+    Size,
+    Modified,
+    Accessed,
+    Created,
+    Path,
+}
+
+impl SortKey {
+    pub const ALL: [SortKey; 5] = [SortKey::Size, SortKey::Modified, SortKey::Accessed, SortKey::Created, SortKey::Path];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SortKey::Size => "size",
+            SortKey::Modified => "modified",
+            SortKey::Accessed => "accessed",
+            SortKey::Created => "created",
+            SortKey::Path => "path",
+        }
+    }
+
+    fn time(&self, file: &FileInfo) -> Option<DateTime<Utc>> {
+        match self {
+            SortKey::Modified => file.last_modified,
+            SortKey::Accessed => file.last_accessed,
+            SortKey::Created => file.created,
+            SortKey::Size | SortKey::Path => None,
+        }
+    }
+
+    /// Where `a` goes relative to `b` when ranked by this key in `order`, a total order.
+    ///
+    /// Size ties fall back on the path the way [`BySize`] does. Files without the timestamp
+    /// go last in either order, equal timestamps (or two missing ones) go by path A to Z.
+    pub fn compare(&self, order: SortOrder, a: &FileInfo, b: &FileInfo) -> Ordering {
+        let ordered = |ordering: Ordering| match order {
+            SortOrder::Descending => ordering.reverse(),
+            SortOrder::Ascending => ordering,
+        };
+        match self {
+            SortKey::Size => ordered(BySize(a).cmp(&BySize(b))),
+            SortKey::Path => ordered(a.full_path.cmp(&b.full_path)),
+            _ => match (self.time(a), self.time(b)) {
+                (Some(x), Some(y)) => ordered(x.cmp(&y)),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            }
+            .then_with(|| a.full_path.cmp(&b.full_path)),
+        }
+    }
+}
+
+// how a listing command narrows down and orders what it prints
+#[derive(Debug, Clone, Default)]
+pub struct ListOptions {
+    pub min_bytes: Option<u64>,
+    // lowercase, without the dot
+    pub ext: Option<String>,
+    // overrides display.top_n for this one listing
+    pub count: Option<usize>,
+    // how many of the matches to skip before the listing starts, for paging through a long one
+    pub offset: usize,
+    pub order: SortOrder,
+    // only largest-files looks at this, folders have no timestamps to go by
+    pub by: SortKey,
+    // header row plus tab-separated rows, raw bytes and RFC 3339 times, nothing decorative
+    pub tsv: bool,
+}
+
+// which side of a cutoff date a file has to be on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Age {
+    NewerThan,
+    OlderThan,
+}
+
+/// (extension, bytes, file count, bytes modified within `days.recent_window_days`)
+pub type TypeDistribution = Vec<(String, u64, usize, u64)>;
+
+/// (folder, bytes in the earlier scan or `None` if it's new, bytes now, change in bytes)
+pub type FolderDelta = (PathBuf, Option<u64>, u64, i64);
+
+/// (category, file count, bytes)
+pub type CategoryTotals = Vec<(FileCategory, usize, u64)>;
+
+/// (top-level folder, oldest file modified, newest file modified)
+pub type FolderAge = (PathBuf, DateTime<Utc>, DateTime<Utc>);
+
+impl ListOptions {
+    // the extension and size filters, then `offset` skipped and the cut to `top_n`
+    // they run before the cut, so a shorter result means that's really all there is
+    pub fn apply(&self, files: Vec<FileInfo>, top_n: usize) -> Vec<FileInfo> {
+        files.into_iter().filter(|file| self.keeps(file)).skip(self.offset).take(top_n).collect()
+    }
+
+    // whether `file` gets past the extension and size filters
+    pub fn keeps(&self, file: &FileInfo) -> bool {
+        self.ext.as_ref().is_none_or(|ext| {
+            file.full_path
+                .extension()
+                .is_some_and(|e| normalize_extension(&e.to_string_lossy()) == *ext)
+        }) && self.min_bytes.is_none_or(|min_bytes| file.size_bytes >= min_bytes)
+    }
+}
+
+// what a drive's cached scan covered next to the used space the OS reports
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScanGap {
+    pub scanned_bytes: u64,
+    pub file_count: usize,
+    pub used_gb: f64,
+    pub user_data: bool,
+    // system dirs the scan skipped, and how many entries the exclusions kept out
+    pub system_dirs: Vec<String>,
+    pub excluded: usize,
+}
+
+/// How complete the scan [`collect_and_cache_files`](super::StorageAnalyzer::collect_and_cache_files) left in the cache is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanStatus {
+    Complete,
+    // the scan timeout ran out, only what was walked until then is cached
+    Partial,
+}
+
+/// What a drive's cached scan is, from [`StorageAnalyzer::cache_info`](super::StorageAnalyzer::cache_info).
+#[derive(Debug, Clone, PartialEq)]
+pub struct CacheInfo {
+    pub scanned_at: DateTime<Utc>,
+    pub file_count: usize,
+    pub folder_count: usize,
+    // how far down the scan went, None when it went all the way
+    pub max_depth: Option<usize>,
+    // files under this size were never cached
+    pub min_file_bytes: Option<u64>,
+    pub user_data: bool,
+    pub follows_links: bool,
+    // entries the exclude list kept out and paths that couldn't be read
+    pub excluded: usize,
+    pub errors: usize,
+    // the scan timeout stopped the walk before it was done
+    pub timed_out: bool,
+    // entries the scan couldn't read for any reason, errors only counts the filesystem ones
+    pub unreadable: usize,
+    // every option the scan ran with, for saying how the numbers were gathered
+    pub options: ScanOptions,
+}
+
+impl CacheInfo {
+    /// Whether the scan left parts of the drive out (a depth limit, --user-data, a minimum file
+    /// size or the scan timeout), totals built from it don't add up to the drive.
+    pub fn is_partial(&self) -> bool {
+        self.max_depth.is_some() || self.min_file_bytes.is_some() || self.user_data || self.timed_out
+    }
+}
+
+// every analyze_drive section as data, so the html, json and summary outputs share one collection pass
+pub struct DriveReport {
+    pub space: DriveAnalysis,
+    pub folders: Vec<FolderSize>,
+    pub distribution: TypeDistribution,
+    pub largest: Vec<FileInfo>,
+    pub recent: Vec<FileInfo>,
+    pub old: Vec<FileInfo>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::test_util::file;
+    use chrono::TimeZone;
+
+    #[test]
+    fn file_info_renders() {
+        assert_eq!(
+            file("C:/Videos/holiday.mp4", 1_572_864_000).shown(Units::Binary).to_string(),
+            "Path: C:/Videos/holiday.mp4\n    Size: 1500.00 MiB / 1.46 GiB\n    Last Modified: Unknown"
+        );
+
+        let accessed = Utc::now();
+        let seen = FileInfo { last_accessed: Some(accessed), ..file("C:/a.log", 512) };
+        assert_eq!(
+            seen.shown(Units::Binary).to_string(),
+            format!("Path: C:/a.log\n    Size: 0.00 MiB / 0.00 GiB\n    Last Modified: Unknown\n    Last Accessed: {}", format_time(accessed))
+        );
+    }
+
+    #[test]
+    fn sort_keys_break_ties_by_path_and_put_missing_times_last() {
+        let day = |d| Some(Utc.with_ymd_and_hms(2024, 5, d, 12, 0, 0).unwrap());
+        let files = [
+            FileInfo { last_modified: day(3), ..file("C:/b.txt", 10) },
+            FileInfo { last_modified: day(1), ..file("C:/c.txt", 30) },
+            file("C:/z.txt", 20),
+            FileInfo { last_modified: day(3), ..file("C:/a.txt", 10) },
+            file("C:/y.txt", 20),
+        ];
+        let ranked = |key: SortKey, order| {
+            let mut sorted = files.to_vec();
+            sorted.sort_by(|a, b| key.compare(order, a, b));
+            sorted.into_iter().map(|f| f.full_path.to_string_lossy().into_owned()).collect::<Vec<_>>()
+        };
+
+        assert_eq!(ranked(SortKey::Modified, SortOrder::Descending), ["C:/a.txt", "C:/b.txt", "C:/c.txt", "C:/y.txt", "C:/z.txt"]);
+        assert_eq!(ranked(SortKey::Modified, SortOrder::Ascending), ["C:/c.txt", "C:/a.txt", "C:/b.txt", "C:/y.txt", "C:/z.txt"]);
+        // nothing has a creation time, so it's just the path
+        assert_eq!(ranked(SortKey::Created, SortOrder::Descending), ["C:/a.txt", "C:/b.txt", "C:/c.txt", "C:/y.txt", "C:/z.txt"]);
+        assert_eq!(ranked(SortKey::Size, SortOrder::Descending), ["C:/c.txt", "C:/z.txt", "C:/y.txt", "C:/b.txt", "C:/a.txt"]);
+        assert_eq!(ranked(SortKey::Path, SortOrder::Ascending), ["C:/a.txt", "C:/b.txt", "C:/c.txt", "C:/y.txt", "C:/z.txt"]);
+    }
+
+    #[test]
+    fn pages_start_after_the_filters() {
+        let files: Vec<FileInfo> = (1..=6).map(|i| file(&format!("C:/{}.{}", i, if i % 2 == 0 { "iso" } else { "txt" }), i)).collect();
+        let options = ListOptions { ext: Some("iso".to_string()), offset: 1, ..ListOptions::default() };
+        let page: Vec<u64> = options.apply(files.clone(), 5).iter().map(|f| f.size_bytes).collect();
+        assert_eq!(page, [4, 6]);
+        let past_the_end = ListOptions { offset: 10, ..ListOptions::default() };
+        assert!(past_the_end.apply(files, 5).is_empty());
+    }
+
+    #[test]
+    fn folder_size_renders() {
+        let folder = FolderSize { folder: "C:/Games".into(), size_bytes: 3 * 1024 * 1024 * 1024, file_count: 1200, ..FolderSize::default() };
+        assert_eq!(folder.shown(Units::Binary).to_string(), "C:/Games\n  Size: 3.00 GiB\n  Files: 1200");
+        assert_eq!(folder.shown(Units::Decimal).to_string(), "C:/Games\n  Size: 3.22 GB\n  Files: 1200");
+    }
+
+    #[test]
+    fn drive_analysis_renders() {
+        let drive = DriveAnalysis { total_size: 476.0, used_space: 400.5, free_space: 75.5, free_space_percent: 15.861, ..DriveAnalysis::default() };
+        assert_eq!(
+            drive.shown(Units::Binary).to_string(),
+            "Total Size: 476.00 GiB\nUsed Space: 400.50 GiB\nFree Space: 75.50 GiB (15.86%)"
+        );
+    }
+
+    #[test]
+    fn by_size_breaks_ties_on_the_path() {
+        let mut files = [file("C:/b", 10), file("C:/c", 30), file("C:/a", 10)];
+        files.sort_by(|a, b| BySize(b).cmp(&BySize(a)));
+        let paths: Vec<&Path> = files.iter().map(|f| f.full_path.as_path()).collect();
+        assert_eq!(paths, [Path::new("C:/c"), Path::new("C:/b"), Path::new("C:/a")]);
+        assert!(BySize(&files[1]) != BySize(&files[2]));
+    }
+
+    #[test]
+    fn unreadable_counts_everything_but_keeps_only_the_first_paths() {
+        let mut unreadable = Unreadable::default();
+        for i in 0..UNREADABLE_PATHS_KEPT + 5 {
+            unreadable.record(format!("C:/denied/{}", i), ErrorKind::PermissionDenied);
+        }
+        unreadable.record("C:/gone".to_string(), ErrorKind::NotFound);
+
+        assert_eq!(unreadable.total(), UNREADABLE_PATHS_KEPT + 6);
+        assert_eq!(unreadable.counts, [(ErrorKind::PermissionDenied, UNREADABLE_PATHS_KEPT + 5), (ErrorKind::NotFound, 1)]);
+        assert_eq!(unreadable.paths.len(), UNREADABLE_PATHS_KEPT);
+        assert_eq!(unreadable.paths[0].0, "C:/denied/0");
+    }
+}
@@ -0,0 +1,63 @@
+use super::{
+    error::{AnalyzerError, Result},
+    progress::{scan_cancelled, ScanGuard},
+    storage::StorageAnalyzer,
+};
+use chrono::Utc;
+use log::warn;
+use std::{
+    fs::OpenOptions,
+    io::Write,
+    path::Path,
+    thread,
+    time::{Duration, Instant},
+};
+
+const CSV_HEADER: &str = "timestamp,drive,total_gb,used_gb,free_gb,free_pct";
+
+impl StorageAnalyzer {
+    /// Appends a `timestamp,drive,total_gb,used_gb,free_gb,free_pct` row per drive to the CSV
+    /// file at `log_path` every `interval_secs` seconds, the header only goes in when the file is new.
+    ///
+    /// Runs until Ctrl+C (or [`progress::cancel_running_scan`](super::progress::cancel_running_scan))
+    /// stops it and returns how many rows were written. A drive that can't be read is skipped
+    /// for that round with a warning.
+    pub fn log_disk_usage_periodically(&self, drives: &[&str], interval_secs: u64, log_path: &str) -> Result<usize> {
+        if interval_secs == 0 {
+            return Err(AnalyzerError::InvalidArgument("the interval has to be at least 1 second".to_string()));
+        }
+        let is_new = Path::new(log_path).metadata().map(|m| m.len() == 0).unwrap_or(true);
+        let mut file = OpenOptions::new().create(true).append(true).open(log_path)?;
+        if is_new {
+            writeln!(file, "{}", CSV_HEADER)?;
+        }
+
+        // counts as a running scan so Ctrl+C ends the loop instead of the program
+        let _guard = ScanGuard::start();
+        let interval = Duration::from_secs(interval_secs);
+        let mut rows = 0;
+        while !scan_cancelled() {
+            let started = Instant::now();
+            let timestamp = Utc::now().to_rfc3339();
+            for drive in drives {
+                match self.get_drive_space(drive) {
+                    Ok(space) => {
+                        writeln!(
+                            file,
+                            "{},{},{:.3},{:.3},{:.3},{:.2}",
+                            timestamp, drive, space.total_size, space.used_space, space.free_space, space.free_space_percent
+                        )?;
+                        rows += 1;
+                    }
+                    Err(e) => warn!("Skipping {} in the usage log: {}", drive, e),
+                }
+            }
+            file.flush()?;
+
+            while !scan_cancelled() && started.elapsed() < interval {
+                thread::sleep(Duration::from_millis(200).min(interval.saturating_sub(started.elapsed())));
+            }
+        }
+        Ok(rows)
+    }
+}
@@ -1,12 +1,387 @@
-use chrono::{DateTime, TimeZone, Utc};
-use std::time::{SystemTime, UNIX_EPOCH};
-use crate::DATE_FORMAT;
+use chrono::{format::{Item, StrftimeItems}, DateTime, Local, NaiveDate, TimeZone, Utc};
+use std::{
+    env,
+    fmt::Display,
+    path::{Path, PathBuf},
+    ptr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        PoisonError, RwLock,
+    },
+    time::SystemTime,
+};
+use super::config::Units;
+use super::constants::DATE_FORMAT;
+use winapi::um::winbase::{FormatMessageW, FORMAT_MESSAGE_FROM_SYSTEM, FORMAT_MESSAGE_IGNORE_INSERTS};
+
+// every byte <-> MB/GB conversion goes through these, with the units of the analyzer doing the
+// printing (StorageAnalyzer::units) so two analyzers never see each other's setting
+
+// sizes are stored as bytes, these are for showing them in MB/GB
+pub fn bytes_to_mb(units: Units, bytes: u64) -> f64 {
+    bytes as f64 / units.bytes_per_mb()
+}
+
+pub fn bytes_to_gb(units: Units, bytes: u64) -> f64 {
+    bytes as f64 / units.bytes_per_gb()
+}
+
+// thresholds are configured in MB/GB, compare them as bytes
+pub fn mb_to_bytes(units: Units, mb: f64) -> u64 {
+    (mb * units.bytes_per_mb()) as u64
+}
+
+pub fn gb_to_bytes(units: Units, gb: f64) -> u64 {
+    (gb * units.bytes_per_gb()) as u64
+}
+
+pub fn mb_label(units: Units) -> &'static str {
+    units.labels()[2]
+}
+
+pub fn gb_label(units: Units) -> &'static str {
+    units.labels()[3]
+}
+
+pub fn format_mb(units: Units, mb: f64) -> String {
+    format!("{:.2} {}", mb, mb_label(units))
+}
+
+pub fn format_gb(units: Units, gb: f64) -> String {
+    format!("{:.2} {}", gb, gb_label(units))
+}
+
+// picks whichever unit keeps the number readable, e.g. 1536 -> "1.50 KiB"
+pub fn human_readable_size(units: Units, bytes: u64) -> String {
+    let mut size = bytes as f64;
+    let mut label = 0;
+    while size >= units.base() && label < units.labels().len() - 1 {
+        size /= units.base();
+        label += 1;
+    }
+    if label == 0 {
+        format!("{} {}", bytes, units.labels()[0])
+    } else {
+        format!("{:.2} {}", size, units.labels()[label])
+    }
+}
+
+// timestamps are kept as real UTC instants, these only decide how they're shown
+// set from the config at startup, until then it's DATE_FORMAT in local time
+static TIME_FORMAT: RwLock<Option<String>> = RwLock::new(None);
+static SHOW_UTC: AtomicBool = AtomicBool::new(false);
+
+pub fn set_time_display(format: &str, utc: bool) {
+    // a panic elsewhere while it was held doesn't make the old format any less usable
+    *TIME_FORMAT.write().unwrap_or_else(PoisonError::into_inner) = Some(format.to_string());
+    SHOW_UTC.store(utc, Ordering::Relaxed);
+}
+
+// whether chrono can use this as a format string, so a typo can't panic mid-report
+pub fn is_valid_time_format(format: &str) -> bool {
+    !StrftimeItems::new(format).any(|item| matches!(item, Item::Error))
+}
+
+// format_time and display_date in a zone of the caller's choosing, with the configured format
+fn format_time_in<Tz: TimeZone>(time: DateTime<Utc>, tz: &Tz) -> String
+where
+    Tz::Offset: Display,
+{
+    let format = TIME_FORMAT.read().unwrap_or_else(PoisonError::into_inner);
+    time.with_timezone(tz).format(format.as_deref().unwrap_or(DATE_FORMAT)).to_string()
+}
+
+fn display_date_in<Tz: TimeZone>(time: DateTime<Utc>, tz: &Tz) -> NaiveDate {
+    time.with_timezone(tz).date_naive()
+}
+
+pub fn format_time(time: DateTime<Utc>) -> String {
+    if SHOW_UTC.load(Ordering::Relaxed) {
+        format_time_in(time, &Utc)
+    } else {
+        format_time_in(time, &Local)
+    }
+}
+
+// the calendar day `time` falls on for whoever is reading the output
+pub fn display_date(time: DateTime<Utc>) -> NaiveDate {
+    if SHOW_UTC.load(Ordering::Relaxed) {
+        display_date_in(time, &Utc)
+    } else {
+        display_date_in(time, &Local)
+    }
+}
 
 // helper function to convert system time to formatted string
 pub fn system_time_to_string(system_time: SystemTime) -> String {
-    let datetime: DateTime<Utc> = system_time
-        .duration_since(UNIX_EPOCH)
-        .map(|duration| Utc.timestamp_opt(duration.as_secs() as i64, 0).unwrap())
-        .unwrap_or_else(|_| Utc::now());
-    datetime.format(DATE_FORMAT).to_string()
-}
\ No newline at end of file
+    format_time(DateTime::<Utc>::from(system_time))
+}
+
+// a Windows FILETIME (100ns ticks since 1601) as an instant, None for 0 which means "not set"
+pub fn filetime_to_utc(ticks: u64) -> Option<DateTime<Utc>> {
+    const TICKS_PER_SECOND: u64 = 10_000_000;
+    const SECONDS_1601_TO_1970: i64 = 11_644_473_600;
+    if ticks == 0 {
+        return None;
+    }
+    let seconds = (ticks / TICKS_PER_SECOND) as i64 - SECONDS_1601_TO_1970;
+    let nanos = (ticks % TICKS_PER_SECOND) as u32 * 100;
+    DateTime::from_timestamp(seconds, nanos)
+}
+
+// one spelling per path so cache keys match, e.g. "c:\\Foo\\" -> "C:/Foo"
+// the drive letter is uppercased and a root like "C:/" keeps its slash
+pub fn normalize_path(path: &str) -> String {
+    let mut normalized = path.replace('\\', "/");
+    if normalized.as_bytes().get(1) == Some(&b':') {
+        normalized[..1].make_ascii_uppercase();
+    }
+    while normalized.ends_with('/') && !is_root(&normalized) {
+        normalized.pop();
+    }
+    normalized
+}
+
+fn is_root(path: &str) -> bool {
+    path == "/" || (path.len() == 3 && path.ends_with(":/"))
+}
+
+// "d", "d:" and "d:/" all mean the root of drive D
+pub fn as_drive_root(arg: &str) -> Option<String> {
+    let mut chars = arg.chars();
+    let letter = chars.next().filter(|c| c.is_ascii_alphabetic())?;
+    matches!(chars.as_str(), "" | ":" | ":/" | ":\\")
+        .then(|| format!("{}:/", letter.to_ascii_uppercase()))
+}
+
+// "14", "14d", "2w" and "3m" (30 day months) as a number of days
+pub fn parse_days(value: &str) -> Result<i64, String> {
+    let value = value.trim().to_lowercase();
+    let (number, multiplier) = match value.char_indices().last() {
+        Some((i, 'd')) => (&value[..i], 1),
+        Some((i, 'w')) => (&value[..i], 7),
+        Some((i, 'm')) => (&value[..i], 30),
+        _ => (value.as_str(), 1),
+    };
+    let number: i64 = number
+        .parse()
+        .map_err(|_| format!("'{}' is not a duration, use something like 14d, 2w or 3m", value))?;
+    if number < 1 {
+        return Err(format!("'{}' has to be at least one day", value));
+    }
+    number
+        .checked_mul(multiplier)
+        .ok_or_else(|| format!("'{}' is too long", value))
+}
+
+// "500MB", "1.5 GB", "2GiB" or a plain byte count as bytes
+// KiB/MiB/GiB are always 1024 based, KB/MB/GB follow the units setting so they mean what the output shows
+pub fn parse_size(value: &str, units: Units) -> Result<u64, String> {
+    let value = value.trim();
+    let split = value.find(|c: char| c.is_ascii_alphabetic()).unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: f64 = number
+        .trim()
+        .parse()
+        .map_err(|_| format!("'{}' is not a size, use something like 500MB or 1.5GB", value))?;
+    let base = units.base();
+    let multiplier = match unit.trim().to_lowercase().as_str() {
+        "" | "b" => 1.0,
+        "k" | "kb" => base,
+        "m" | "mb" => base.powi(2),
+        "g" | "gb" => base.powi(3),
+        "t" | "tb" => base.powi(4),
+        "kib" => 1024.0,
+        "mib" => 1024f64.powi(2),
+        "gib" => 1024f64.powi(3),
+        "tib" => 1024f64.powi(4),
+        other => return Err(format!("unknown size unit '{}', use KB, MB, GB or TB", other)),
+    };
+    if number < 0.0 || number.is_nan() {
+        return Err(format!("'{}' can't be negative", value));
+    }
+    Ok((number * multiplier) as u64)
+}
+
+// "mp4", ".mp4" and ".MP4" all mean the same extension
+pub fn normalize_extension(ext: &str) -> String {
+    ext.trim_start_matches('.').to_lowercase()
+}
+
+// lowercased, forward-slashed form of a path for case-insensitive comparisons
+pub fn comparable_path(path: impl AsRef<Path>) -> String {
+    path.as_ref().to_string_lossy().replace('\\', "/").trim_end_matches('/').to_lowercase()
+}
+
+// whether `path` is `dir` itself or somewhere below it, both in comparable_path form
+pub fn is_under(path: &str, dir: &str) -> bool {
+    path == dir || path.starts_with(&format!("{}/", dir))
+}
+
+// short form of a count for summaries, e.g. 1234567 -> "1.2M"
+pub fn compact_count(count: usize) -> String {
+    match count {
+        0..=999 => count.to_string(),
+        1_000..=999_999 => format!("{:.1}K", count as f64 / 1_000.0),
+        _ => format!("{:.1}M", count as f64 / 1_000_000.0),
+    }
+}
+
+// %USERPROFILE% on Windows, $HOME everywhere else
+pub fn home_dir() -> Option<PathBuf> {
+    env::var_os("USERPROFILE")
+        .or_else(|| env::var_os("HOME"))
+        .map(PathBuf::from)
+}
+
+// quotes a value for a CSV cell when it would otherwise break the row
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// the system's own description of a Win32 error code, e.g. 1392 -> "The file or directory is corrupted and unreadable."
+pub fn format_os_error(code: i32) -> String {
+    let mut buffer = [0u16; 512];
+    let len = unsafe {
+        FormatMessageW(
+            FORMAT_MESSAGE_FROM_SYSTEM | FORMAT_MESSAGE_IGNORE_INSERTS,
+            ptr::null(),
+            code as u32,
+            0,
+            buffer.as_mut_ptr(),
+            buffer.len() as u32,
+            ptr::null_mut(),
+        )
+    };
+    if len == 0 {
+        return format!("unknown error {}", code);
+    }
+    String::from_utf16_lossy(&buffer[..len as usize]).trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, FixedOffset, LocalResult, NaiveDateTime};
+
+    fn utc(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    // every analyzer the tests build leaves the display at the default DATE_FORMAT in local time
+
+    // Europe/Berlin with its 2024 changes, chrono-tz isn't a dependency so this is the zone
+    // database written out for the one year: CET, CEST from 31 March 01:00 UTC, CET again
+    // from 27 October 01:00 UTC
+    #[derive(Debug, Clone, Copy)]
+    struct Berlin;
+
+    impl Berlin {
+        const CET: i32 = 3600;
+        const CEST: i32 = 2 * 3600;
+    }
+
+    impl TimeZone for Berlin {
+        type Offset = FixedOffset;
+
+        fn from_offset(_: &FixedOffset) -> Self {
+            Berlin
+        }
+
+        fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> FixedOffset {
+            let summer = utc.and_utc() >= self::utc(2024, 3, 31, 1, 0) && utc.and_utc() < self::utc(2024, 10, 27, 1, 0);
+            FixedOffset::east_opt(if summer { Berlin::CEST } else { Berlin::CET }).unwrap()
+        }
+
+        fn offset_from_utc_date(&self, utc: &NaiveDate) -> FixedOffset {
+            self.offset_from_utc_datetime(&utc.and_hms_opt(0, 0, 0).unwrap())
+        }
+
+        // a wall clock time in the spring gap has no offset, one in the repeated autumn hour has two
+        fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> LocalResult<FixedOffset> {
+            let fits: Vec<FixedOffset> = [Berlin::CEST, Berlin::CET]
+                .into_iter()
+                .map(|seconds| FixedOffset::east_opt(seconds).unwrap())
+                .filter(|offset| self.offset_from_utc_datetime(&(*local - Duration::seconds(offset.local_minus_utc() as i64))) == *offset)
+                .collect();
+            match fits.as_slice() {
+                [] => LocalResult::None,
+                [offset] => LocalResult::Single(*offset),
+                [earlier, later] => LocalResult::Ambiguous(*earlier, *later),
+                _ => unreachable!(),
+            }
+        }
+
+        fn offset_from_local_date(&self, local: &NaiveDate) -> LocalResult<FixedOffset> {
+            self.offset_from_local_datetime(&local.and_hms_opt(0, 0, 0).unwrap())
+        }
+    }
+
+    #[test]
+    fn spring_forward_skips_the_gap() {
+        // 2024-03-31 01:00 UTC is when 02:00 CET jumps to 03:00 CEST
+        let before = utc(2024, 3, 31, 0, 59);
+        let after = utc(2024, 3, 31, 1, 0);
+        assert_eq!(format_time_in(before, &Berlin), "2024-03-31 01:59:00");
+        assert_eq!(format_time_in(after, &Berlin), "2024-03-31 03:00:00");
+        // a minute apart in real time, no matter what the wall clock says
+        assert_eq!(after - before, Duration::minutes(1));
+        // and nothing in the zone ever reads 02:30 that morning
+        assert_eq!(Berlin.with_ymd_and_hms(2024, 3, 31, 2, 30, 0), LocalResult::None);
+    }
+
+    #[test]
+    fn fall_back_repeats_an_hour_but_keeps_the_order() {
+        // 2024-10-27 01:00 UTC is when 03:00 CEST goes back to 02:00 CET
+        let first = utc(2024, 10, 27, 0, 30);
+        let second = utc(2024, 10, 27, 1, 30);
+        let first_shown = format_time_in(first, &Berlin);
+        let second_shown = format_time_in(second, &Berlin);
+        // both read 02:30, comparing the formatted strings would call them equal
+        assert_eq!(first_shown, "2024-10-27 02:30:00");
+        assert_eq!(first_shown, second_shown);
+        assert!(first < second);
+        assert!(matches!(Berlin.with_ymd_and_hms(2024, 10, 27, 2, 30, 0), LocalResult::Ambiguous(..)));
+    }
+
+    #[test]
+    fn late_evening_utc_is_the_next_day_further_east() {
+        let time = utc(2024, 3, 30, 23, 30);
+        assert_eq!(display_date_in(time, &Utc), NaiveDate::from_ymd_opt(2024, 3, 30).unwrap());
+        assert_eq!(display_date_in(time, &Berlin), NaiveDate::from_ymd_opt(2024, 3, 31).unwrap());
+    }
+
+    #[test]
+    fn local_display_is_the_local_zone_across_the_changes() {
+        // every half hour through both 2024 changes, whatever zone the machine is in
+        let nights = [utc(2024, 3, 30, 12, 0), utc(2024, 10, 26, 12, 0)];
+        for time in nights.iter().flat_map(|start| (0..48).map(move |i| *start + Duration::minutes(30 * i))) {
+            assert_eq!(format_time(time), format_time_in(time, &Local));
+            assert_eq!(display_date(time), display_date_in(time, &Local));
+        }
+    }
+
+    #[test]
+    fn a_poisoned_time_format_still_formats() {
+        let _ = std::thread::spawn(|| {
+            let _held = TIME_FORMAT.write().unwrap_or_else(PoisonError::into_inner);
+            panic!("poisoning the lock on purpose");
+        })
+        .join();
+        assert!(TIME_FORMAT.is_poisoned());
+        assert_eq!(format_time_in(utc(2024, 5, 1, 8, 15), &Utc), "2024-05-01 08:15:00");
+        set_time_display(DATE_FORMAT, false);
+        assert!(!format_time(utc(2024, 5, 1, 8, 15)).is_empty());
+    }
+
+    #[test]
+    fn rejects_broken_format_strings() {
+        assert!(is_valid_time_format("%Y-%m-%d %H:%M"));
+        assert!(is_valid_time_format("%d/%m/%Y %I:%M %p"));
+        assert!(!is_valid_time_format("%Y-%m-%d %Q"));
+    }
+}
@@ -0,0 +1,41 @@
+//! Disk usage analysis for Windows drives, the engine behind the Rusty-Analyser shell.
+//!
+//! [`StorageAnalyzer`] walks a drive (or any folder) once, caches what it found and answers
+//! every query from that cache. The `get_*`, `find_*` and other data methods only return
+//! values, the `print_*` methods are what the shell uses to show them. Scan progress and
+//! warnings go through the [`log`](https://docs.rs/log) crate, so they only show up once the
+//! program installs a logger, [`StorageAnalyzerBuilder::quiet`] drops progress to debug level.
+//!
+//! Make one with [`StorageAnalyzer::builder`], [`StorageAnalyzer::new`] is the shortcut for
+//! an analyzer with every default.
+//!
+//! Sizes are exact byte counts, MB/GB only come in when something is formatted for display.
+//! Fallible methods return [`AnalyzerError`], so a missing root, a denied root or a cancelled
+//! scan can be told apart without parsing OS error strings.
+//!
+//! ```
+//! use rusty_analyser::{SortOrder, StorageAnalyzer};
+//! use std::fs;
+//!
+//! let dir = std::env::temp_dir().join("rusty-analyser-doctest-largest");
+//! fs::create_dir_all(&dir)?;
+//! fs::write(dir.join("small.bin"), vec![0u8; 1_000])?;
+//! fs::write(dir.join("big.bin"), vec![0u8; 50_000])?;
+//!
+//! let mut analyzer = StorageAnalyzer::builder().quiet(true).build()?;
+//! let files = analyzer.get_largest_files(&dir.to_string_lossy(), SortOrder::Descending)?;
+//!
+//! assert_eq!(files.len(), 2);
+//! assert!(files[0].full_path.ends_with("big.bin"));
+//! assert_eq!(files[0].size_bytes, 50_000);
+//!
+//! fs::remove_dir_all(&dir)?;
+//! # Ok::<(), rusty_analyser::AnalyzerError>(())
+//! ```
+
+pub mod analyzer;
+
+pub use analyzer::{
+    AnalyzerError, CacheInfo, Config, DriveAnalysis, FileInfo, FolderSize, ScanOptions, SortKey, SortOrder, StorageAnalyzer,
+    StorageAnalyzerBuilder, TypeDistribution,
+};
@@ -1,33 +1,209 @@
-mod analyzer;
 mod shell;
-use crate::analyzer::{
-    constants::* 
+use rusty_analyser::analyzer::{
+    StorageAnalyzer,
+    config::Config,
+    error::Result,
+    metrics::serve_metrics,
+    types::ListOptions,
+    utils::as_drive_root,
+    constants::*
 };
+use log::{info, warn};
+use std::{env, io, path::PathBuf, process};
+
+const CLI_USAGE: &str = "\
+Usage: Rusty-Analyser [options]
+
+Options:
+  --metrics              expose Prometheus metrics while the shell is running (off by default)
+  --metrics-addr <addr>  address to bind the metrics endpoint to (default: 127.0.0.1:9184)
+  --drive <letter>       drive to analyze without starting the shell, needs --json-only
+  --json-only            print the analysis of --drive as a single JSON object and exit
+                         e.g. Rusty-Analyser --drive C --json-only > report.json
+  --tsv <listing>        print one listing of --drive as tab-separated rows and exit, the listing is
+                         largest-files, largest-folder, recent-large-files or old-large-files
+                         e.g. Rusty-Analyser --drive C --tsv largest-files > files.tsv
+  --ndjson               stream every file on --drive as one JSON object per line while it's walked,
+                         nothing is held in memory
+                         e.g. Rusty-Analyser --drive C --ndjson | jq 'select(.size_bytes > 100000000)'
+  --log-level <level>    how much of the analyzer's progress and warnings goes to stderr: off, error,
+                         warn, info (default) or debug, RUST_LOG=<level> does the same
+  --log-file <path>      also append everything down to debug to this file, with timestamps
+                         e.g. Rusty-Analyser --log-file rsa.log, attach it to bug reports
+  --read-only            start the shell with delete, move and cleanup commands disabled,
+                         for auditing a drive that isn't yours (set-readonly false turns it off)
+  --help                 show this message
+
+Metrics (served at http://<addr>/metrics, all gauges):
+  rusty_analyser_drive_total_bytes{drive}          total size of the drive
+  rusty_analyser_drive_free_bytes{drive}           free space on the drive
+  rusty_analyser_scanned_files{drive}              files found by the last scan
+  rusty_analyser_scan_age_seconds{drive}           seconds since the last scan finished
+  rusty_analyser_category_bytes{drive,category}    bytes per category from the last scan
+                                                   (media, document, code, archive, system, other)
+
+Exit codes (--drive mode):
+  0 ok, 1 I/O error, 2 invalid argument, 3 not a fixed drive, 4 path doesn't exist,
+  5 access denied, 6 no cached scan, 130 cancelled";
+
+struct CliArgs {
+    metrics: bool,
+    metrics_addr: String,
+    drive: Option<String>,
+    json_only: bool,
+    tsv: Option<String>,
+    ndjson: bool,
+    read_only: bool,
+    log_level: Option<String>,
+    log_file: Option<PathBuf>,
+}
+
+fn parse_args() -> Result<CliArgs, String> {
+    let mut cli = CliArgs {
+        metrics: false,
+        metrics_addr: DEFAULT_METRICS_ADDR.to_string(),
+        drive: None,
+        json_only: false,
+        tsv: None,
+        ndjson: false,
+        read_only: false,
+        log_level: None,
+        log_file: None,
+    };
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--metrics" => cli.metrics = true,
+            "--metrics-addr" => {
+                cli.metrics_addr = args.next().ok_or("--metrics-addr needs an address")?;
+            }
+            "--drive" => {
+                let drive = args.next().ok_or("--drive needs a drive letter")?;
+                cli.drive = Some(as_drive_root(&drive).ok_or(format!("not a drive: {}", drive))?);
+            }
+            "--json-only" => cli.json_only = true,
+            "--ndjson" => cli.ndjson = true,
+            "--read-only" => cli.read_only = true,
+            "--log-level" => cli.log_level = Some(args.next().ok_or("--log-level needs a level, e.g. debug")?),
+            "--log-file" => cli.log_file = Some(args.next().ok_or("--log-file needs a path")?.into()),
+            "--tsv" => {
+                let listing = args.next().ok_or("--tsv needs a listing, e.g. largest-files")?;
+                if !TSV_LISTINGS.contains(&listing.as_str()) {
+                    return Err(format!("--tsv: {} isn't a listing, pick one of {}", listing, TSV_LISTINGS.join(", ")));
+                }
+                cli.tsv = Some(listing);
+            }
+            "--help" | "-h" => {
+                println!("{}", CLI_USAGE);
+                process::exit(0);
+            }
+            other => return Err(format!("unknown argument: {}", other)),
+        }
+    }
+    if [cli.json_only, cli.tsv.is_some(), cli.ndjson].iter().filter(|&&mode| mode).count() > 1 {
+        return Err("only one of --json-only, --tsv and --ndjson can be used".to_string());
+    }
+    if cli.drive.is_some() != (cli.json_only || cli.tsv.is_some() || cli.ndjson) {
+        return Err("--drive has to be used together with --json-only, --tsv or --ndjson".to_string());
+    }
+    Ok(cli)
+}
+
+const TSV_LISTINGS: &[&str] = &["largest-files", "largest-folder", "recent-large-files", "old-large-files"];
+
+fn print_tsv_listing(analyzer: &mut StorageAnalyzer, listing: &str, drive: &str) -> Result<()> {
+    let options = ListOptions { tsv: true, ..ListOptions::default() };
+    match listing {
+        "largest-files" => analyzer.print_largest_files(drive, &options),
+        "largest-folder" => analyzer.print_largest_folders(drive, &options),
+        "recent-large-files" => analyzer.print_recent_large_files(drive, None, &options),
+        _ => analyzer.print_old_large_files(drive, None, &options),
+    }
+}
 
 #[cfg(feature = "DEBUG_MODE")]
-fn debug_test() -> std::io::Result<()> {
-    let mut analyzer = StorageAnalyzer::new();
-    analyzer.print_recent_large_files("C:\\")?;
-    analyzer.print_old_large_files("C:\\")?;
+fn debug_test() -> Result<()> {
+    let mut analyzer = StorageAnalyzer::builder().config(Config::load()?).build()?;
+    analyzer.print_recent_large_files("C:\\", None, &ListOptions::default())?;
+    analyzer.print_old_large_files("C:\\", None, &ListOptions::default())?;
     Ok(())
 }
 
-fn main() -> std::io::Result<()> {
-    #[cfg(debug_assertions)]
-    {
-        println!("--- WARNING ---");
-        println!("DEV PROFILE : Running dev profile!");
-        println!("if you are a normal user, consider using cargo run --release\n\n\n");
+fn main() -> Result<()> {
+    let cli = match parse_args() {
+        Ok(cli) => cli,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, CLI_USAGE);
+            process::exit(2);
+        }
+    };
+
+    let log_level = match shell::logging::level_from(cli.log_level.as_deref()) {
+        Ok(level) => level,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, CLI_USAGE);
+            process::exit(2);
+        }
+    };
+    if let Err(e) = shell::logging::init(log_level, cli.log_file.as_deref()) {
+        eprintln!("Couldn't open the log file: {}", e);
+        process::exit(1);
     }
 
+    #[cfg(debug_assertions)]
+    warn!("DEV PROFILE : Running dev profile! If you are a normal user, consider using cargo run --release");
+
     #[cfg(feature = "DEBUG_MODE")]
     {
-        println!("--- WARNING ---");
-        println!("DEBUG MODE : Running debug function!");
+        warn!("DEBUG MODE : Running debug function!");
         return debug_test();
     }
-    
+
+    let mut config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Invalid config: {}", e);
+            process::exit(2);
+        }
+    };
+    config.apply_env();
+    if !config.display.color {
+        colored::control::set_override(false);
+    }
+
+    // headless mode, stdout only ever gets the JSON, NDJSON or TSV so it can be redirected to a file
+    if let Some(drive) = &cli.drive {
+        let mut analyzer = match StorageAnalyzer::builder().config(config).quiet(true).build() {
+            Ok(analyzer) => analyzer,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                process::exit(e.exit_code());
+            }
+        };
+        let result = match &cli.tsv {
+            Some(listing) => {
+                colored::control::set_override(false);
+                print_tsv_listing(&mut analyzer, listing, drive)
+            }
+            None if cli.ndjson => analyzer.stream_files_as_json(drive, &mut io::BufWriter::new(io::stdout().lock())),
+            None => analyzer.analyze_drive_to_json(drive).map(|json| println!("{}", json)),
+        };
+        if let Err(e) = result {
+            eprintln!("Error: {}", e);
+            process::exit(e.exit_code());
+        }
+        return Ok(());
+    }
+
+    let analyzer = StorageAnalyzer::builder().config(config).read_only(cli.read_only).build()?;
+
+    if cli.metrics {
+        serve_metrics(&cli.metrics_addr, analyzer.metrics_drives(), analyzer.metrics_registry())?;
+        info!("Serving metrics on http://{}/metrics", cli.metrics_addr);
+    }
+
     // where the main code will run
-    shell::bash_commands();
+    shell::bash_commands(analyzer);
     Ok(())
-}
\ No newline at end of file
+}
@@ -1,49 +1,100 @@
-use crate::analyzer::{
+use rusty_analyser::analyzer::{
     StorageAnalyzer,
-    constants::*
+    batch,
+    cleanup,
+    config::{Config, ScanOptions, Units, TUNABLES},
+    constants::*,
+    email,
+    error::{AnalyzerError, Result},
+    exclude::ExcludeList,
+    file_ops,
+    report,
+    types::{ListOptions, SortKey, SortOrder},
+    utils::{
+        as_drive_root, human_readable_size, mb_label, normalize_extension, normalize_path, parse_days,
+        parse_size,
+        system_time_to_string
+    }
 };
 use super::{
-    help_cmd::*
+    help_cmd::*,
+    types::PagedListing,
+    watch,
+    wizard
 };
 use std::{
     env,
+    fs,
     io::{self, Write},
+    path::{Path, PathBuf},
     process,
+    time::{Duration, Instant},
 };
 use colored::{ColoredString, Colorize};
 use lazy_static::lazy_static;
 use rayon::max_num_threads;
+use rustyline::{
+    error::ReadlineError,
+    history::{History, SearchDirection},
+    DefaultEditor,
+};
+use walkdir::WalkDir;
 use whoami::fallible;
 
 
-fn prompter_fn() {
+// prints the user@host line and returns the part rustyline reads input after
+fn prompter_fn(last_duration: Duration) -> String {
+    if last_duration > Duration::from_millis(SHOW_DURATION_AFTER_MS) {
+        println!("{}", format!("took {:.2?}", last_duration).bright_black());
+    }
     let _user: String = whoami::username();
     let _host: String = fallible::hostname().unwrap();
     let prompt: String = format!(
-        "\n{}{}{}\n{} ",
+        "\n{}{}{}",
         "user".green(),
         "@".white(),
         "host".blue(),
-        "$".cyan()
     );
-    print!("{}", prompt);
-    io::stdout().flush().unwrap();
+    println!("{}", prompt);
+    format!("{} ", "$".cyan())
+}
+
+// kept next to the config file so it survives restarts
+fn history_path() -> Option<PathBuf> {
+    Config::path().and_then(|path| Some(path.parent()?.join(HISTORY_FILE_NAME)))
+}
+
+// every history entry, oldest first, numbered from 1 like bash does
+fn history_entries(editor: &DefaultEditor) -> Vec<(usize, String)> {
+    let history = editor.history();
+    (0..history.len())
+        .filter_map(|i| {
+            let entry = history.get(i, SearchDirection::Forward).ok()??;
+            Some((i + 1, entry.entry.into_owned()))
+        })
+        .collect()
+}
+
+fn print_history(entries: &[(usize, String)]) {
+    for (i, entry) in entries {
+        println!("{:>5}  {}", i, entry);
+    }
 }
 
 fn validate_and_format_drive<F>(drive: &str, action: F)
 where
-    F: FnOnce(&str) -> Result<(), io::Error>,
+    F: FnOnce(&str) -> Result<()>,
 {
-    let drive = drive.to_uppercase();
+    let drive = normalize_path(drive);
     
     if drive.len() == 1 && drive.chars().all(|c| c.is_ascii_alphabetic()) {
         // user entered just the letter (e.g., "C"), format it properly
-        if let Err(e) = action(format!("{}:/", drive).as_str()) { 
+        if let Err(e) = action(format!("{}:/", drive.to_uppercase()).as_str()) { 
             eprintln!("Error: {}", e);
         }
     } else if drive.len() == 3 && drive.ends_with(":/") &&
         drive.chars().next().unwrap().is_ascii_alphabetic() {
-        // user entered a valid full path (e.g., "C:/"), use it directly
+        // user entered a valid full path (e.g., "C:/" or "c:\\"), use it directly
         if let Err(e) = action(drive.as_str()) {
             eprintln!("Error: {}", e);
         }
@@ -54,6 +105,502 @@ where
     }
 }
 
+// asks a question on stdin, anything other than the expected answer counts as "no"
+fn confirm(question: &str, expected: &str) -> bool {
+    print!("{} ", question);
+    io::stdout().flush().unwrap();
+    let mut answer = String::new();
+    if io::stdin().read_line(&mut answer).is_err() {
+        return false;
+    }
+    answer.trim().eq_ignore_ascii_case(expected)
+}
+
+// arguments that aren't flags, joined back together so paths with spaces survive
+fn positional_arg(raw_command: &[String], skip: usize) -> Option<String> {
+    let words: Vec<&str> = raw_command
+        .iter()
+        .skip(skip)
+        .filter(|w| !w.starts_with("--"))
+        .map(|w| w.as_str())
+        .collect();
+    (!words.is_empty()).then(|| words.join(" "))
+}
+
+// removes `flag` and the word after it, returning that word
+fn take_flag_value(raw_command: &mut Vec<String>, flag: &str) -> Option<String> {
+    let i = raw_command.iter().position(|w| w.eq_ignore_ascii_case(flag))?;
+    raw_command.remove(i);
+    (i < raw_command.len()).then(|| raw_command.remove(i))
+}
+
+fn find_profile<'a>(config: &'a Config, name: &str) -> Option<(&'a String, &'a ScanOptions)> {
+    config.profiles.iter().find(|(profile, _)| profile.eq_ignore_ascii_case(name))
+}
+
+// strips the scan flags out of the command and merges them over the profile, flags win
+fn take_scan_options(raw_command: &mut Vec<String>, config: &Config, session_profile: Option<&str>) -> Result<ScanOptions, String> {
+    let max_depth = match take_flag_value(raw_command, "--max-depth").map(|d| d.parse::<usize>()) {
+        Some(Ok(depth)) if depth > 0 => Some(depth),
+        Some(_) => return Err("--max-depth needs a number of levels, at least 1".to_string()),
+        None => None,
+    };
+    let flags = ScanOptions {
+        max_depth,
+        min_file_size: None,
+        follow_links: take_flag(raw_command, "--follow-links").then_some(true),
+        include_system: take_flag(raw_command, "--include-system").then_some(true),
+        user_data: take_flag(raw_command, "--user-data").then_some(true),
+        no_exclude: take_flag(raw_command, "--no-exclude").then_some(true),
+    };
+
+    let name = take_flag_value(raw_command, "--profile");
+    match name.as_deref().or(session_profile) {
+        Some(name) => match find_profile(config, name) {
+            Some((_, profile)) => Ok(flags.or(profile)),
+            None => Err(format!("no profile called '{}', see profile list", name)),
+        },
+        None => Ok(flags),
+    }
+}
+
+// removes every copy of `flag` and returns whether there was one
+fn take_flag(raw_command: &mut Vec<String>, flag: &str) -> bool {
+    let before = raw_command.len();
+    raw_command.retain(|w| !w.eq_ignore_ascii_case(flag));
+    raw_command.len() != before
+}
+
+// the words after the command name without any `--flag value` pairs for `flags`
+// and without any other `--switch`
+fn args_without(command: &[String], flags: &[&str]) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut words = command.iter().skip(1);
+    while let Some(word) = words.next() {
+        if flags.contains(&word.as_str()) {
+            words.next();
+        } else if !word.starts_with("--") {
+            args.push(word.clone());
+        }
+    }
+    args
+}
+
+const LIST_FLAGS: &[&str] = &["--min", "--min-size", "--ext", "--count", "--by", "--page"];
+
+// the listings that have a --tsv layout, everything else refuses the flag rather than ignore it
+const TSV_LISTINGS: &[&str] = &["largest-files", "largest-folder", "recent-large-files", "old-large-files"];
+
+// `--min 1GB`, `--ext iso`, `--count 25`, `--by modified` and `--reverse` / `--asc` for the listing commands
+fn list_options(command: &[String], units: Units) -> Result<ListOptions, String> {
+    let mut options = ListOptions::default();
+    for flag in ["--min", "--min-size"] {
+        if let Some(min) = flag_value(command, flag) {
+            options.min_bytes = Some(parse_size(&min, units).map_err(|e| format!("{}: {}", flag, e))?);
+        }
+    }
+    if let Some(ext) = flag_value(command, "--ext") {
+        // a folder's size is every extension inside it, there's nothing to narrow it to
+        if command.first().is_some_and(|name| name == "largest-folder") {
+            return Err("--ext only works on file listings".to_string());
+        }
+        options.ext = Some(normalize_extension(&ext));
+    }
+    if let Some(count) = flag_value(command, "--count") {
+        match count.parse::<usize>() {
+            Ok(count) if count > 0 => options.count = Some(count),
+            _ => return Err(format!("--count: '{}' isn't a positive number", count)),
+        }
+    }
+    if let Some(by) = flag_value(command, "--by") {
+        options.by = SortKey::ALL
+            .into_iter()
+            .find(|key| key.as_str().eq_ignore_ascii_case(&by))
+            .ok_or_else(|| format!("--by: '{}' isn't one of size, modified, accessed, created, path", by))?;
+    }
+    if command.iter().any(|w| w == "--reverse" || w == "--asc") {
+        options.order = SortOrder::Ascending;
+    }
+    options.tsv = command.iter().any(|w| w == "--tsv");
+    Ok(options)
+}
+
+// `largest-files C 20 --page 3` is files 41 to 60, the page size falls back on --count, then top_n
+fn page_of(command: &[String], args: &[String], options: &ListOptions, top_n: usize) -> Result<(usize, usize), String> {
+    let page_size = match args.get(1) {
+        Some(size) => size.parse::<usize>().ok().filter(|&size| size > 0).ok_or_else(|| format!("'{}' isn't a positive page size", size))?,
+        None => options.count.unwrap_or(top_n),
+    };
+    let page = match flag_value(command, "--page") {
+        Some(page) => page.parse::<usize>().ok().filter(|&page| page > 0).ok_or_else(|| format!("--page: '{}' isn't a page number", page))?,
+        None => 1,
+    };
+    Ok(((page - 1).saturating_mul(page_size), page_size))
+}
+
+// value following a flag, e.g. `--export out.csv`, with its original casing
+fn flag_value(raw_command: &[String], flag: &str) -> Option<String> {
+    raw_command
+        .iter()
+        .position(|w| w.eq_ignore_ascii_case(flag))
+        .and_then(|i| raw_command.get(i + 1))
+        .cloned()
+}
+
+// turns "#3" into the third path of the last printed report, anything else is taken as a path
+fn resolve_target(target: &str, analyzer: &StorageAnalyzer) -> Option<PathBuf> {
+    match target.strip_prefix('#') {
+        Some(index) => {
+            let index = index.parse::<usize>().ok()?;
+            analyzer.last_listing().get(index.checked_sub(1)?).cloned()
+        }
+        None => Some(PathBuf::from(target)),
+    }
+}
+
+fn delete_target(analyzer: &mut StorageAnalyzer, path: &Path, permanent: bool) {
+    let units = analyzer.units();
+    let metadata = match fs::metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) => {
+            eprintln!("delete: cannot access '{}': {}", path.display(), e);
+            return;
+        }
+    };
+
+    let size_bytes = if metadata.is_dir() {
+        analyzer
+            .calculate_folder_size(path)
+            .map(|f| f.size_bytes)
+            .unwrap_or(0)
+    } else {
+        metadata.len()
+    };
+    let unknown = || "Unknown".to_string();
+
+    println!("\n{}", path.display().to_string().bright_white());
+    println!("  Type: {}", if metadata.is_dir() { "Folder" } else { "File" });
+    println!("  Size: {}", human_readable_size(units, size_bytes));
+    println!("  Last Modified: {}", metadata.modified().map(system_time_to_string).unwrap_or_else(|_| unknown()));
+    println!("  Last Accessed: {}", metadata.accessed().map(system_time_to_string).unwrap_or_else(|_| unknown()));
+
+    let result = if permanent {
+        println!("\n{}", "This will PERMANENTLY delete it, the Recycle Bin will NOT have a copy.".red().bold());
+        if !confirm("Type 'delete' to confirm:", "delete") {
+            println!("Cancelled.");
+            return;
+        }
+        file_ops::delete_permanently(path)
+    } else {
+        if !confirm("\nMove to the Recycle Bin? [y/N]", "y") {
+            println!("Cancelled.");
+            return;
+        }
+        file_ops::safe_delete(path)
+    };
+
+    match result {
+        Ok(()) => {
+            analyzer.forget_path(path);
+            println!("Deleted {}", path.display());
+        }
+        Err(e) => eprintln!("delete: failed to delete '{}': {}", path.display(), e),
+    }
+}
+
+// recycles everything in the temp directories in one shell operation, when a file that is
+// still in use stops it the rest go one at a time so only that file stays behind
+fn clean_temp_dirs(analyzer: &mut StorageAnalyzer) -> Result<()> {
+    let units = analyzer.units();
+    analyzer.print_temp_dirs()?;
+    let files: Vec<(PathBuf, u64)> = StorageAnalyzer::get_temp_directories()
+        .iter()
+        .flat_map(|dir| WalkDir::new(dir).min_depth(1).into_iter().filter_map(Result::ok))
+        .filter(|e| e.file_type().is_file())
+        .map(|e| (e.path().to_path_buf(), e.metadata().map(|m| m.len()).unwrap_or(0)))
+        .collect();
+    if files.is_empty() {
+        println!("Temp directories are already empty");
+        return Ok(());
+    }
+
+    let total_bytes: u64 = files.iter().map(|(_, size)| size).sum();
+    let question = format!(
+        "\nMove {} files ({}) to the Recycle Bin? [y/N]",
+        files.len(), human_readable_size(units, total_bytes)
+    );
+    if !confirm(&question, "y") {
+        println!("Cancelled, no changes made.");
+        return Ok(());
+    }
+
+    let paths: Vec<&Path> = files.iter().map(|(path, _)| path.as_path()).collect();
+    match file_ops::safe_delete_many(&paths) {
+        // no Recycle Bin on that volume, one at a time wouldn't get any further
+        Err(e) if e.kind() == io::ErrorKind::Unsupported => return Err(e.into()),
+        Err(_) => {
+            for path in paths.iter().filter(|path| path.exists()) {
+                let _ = file_ops::safe_delete(path);
+            }
+        }
+        Ok(()) => {}
+    }
+    let (recycled, left): (Vec<_>, Vec<_>) = files.iter().partition(|(path, _)| !path.exists());
+    let recycled_bytes: u64 = recycled.iter().map(|(_, size)| size).sum();
+    // cheaper than forgetting thousands of files one by one: drop each directory and re-add what's left
+    for dir in StorageAnalyzer::get_temp_directories() {
+        analyzer.forget_path(&dir);
+        analyzer.record_path(&dir);
+    }
+    // still on the disk until the Recycle Bin is emptied, so nothing is freed yet
+    println!("Recycled {} files ({})", recycled.len(), human_readable_size(units, recycled_bytes));
+    if !left.is_empty() {
+        println!("{} files were skipped, most likely because a program still has them open", left.len());
+    }
+    Ok(())
+}
+
+fn config_path_display() -> String {
+    Config::path()
+        .map(|path| path.display().to_string())
+        .unwrap_or_else(|| "the config file (no %APPDATA% or home directory found)".to_string())
+}
+
+fn email_report(analyzer: &mut StorageAnalyzer, drive: &str, recipient: &str) -> Result<()> {
+    let smtp = match analyzer.config().smtp.clone() {
+        Some(smtp) => smtp,
+        None => {
+            println!("Configure [smtp] in {}", config_path_display());
+            return Ok(());
+        }
+    };
+
+    let html = analyzer.generate_html_report(drive)?;
+    let subject = format!("Storage report for {}", drive);
+    email::send_html_email(&smtp, recipient, &subject, html)?;
+    println!("Email sent to {}", recipient);
+    Ok(())
+}
+
+fn ext_quotas(analyzer: &mut StorageAnalyzer, drive: &str) -> Result<()> {
+    let rules = analyzer.config().quotas.clone();
+    if rules.is_empty() {
+        println!("No quotas configured, add [[quota]] entries (extension, limit_gb) to {}", config_path_display());
+        return Ok(());
+    }
+    analyzer.print_ext_quotas(drive, &rules)
+}
+
+// changes the exclude list in the config file and applies it to what's already cached
+fn edit_excludes<F>(analyzer: &mut StorageAnalyzer, edit: F) -> Result<()>
+where
+    F: FnOnce(&mut Vec<String>) -> Result<()>,
+{
+    let mut config = Config::load()?;
+    edit(&mut config.exclude)?;
+    // compiled before saving so a broken glob never ends up in the file
+    let excludes = ExcludeList::new(&config.exclude)?;
+    config.save()?;
+    analyzer.config_mut().exclude = config.exclude;
+    let pruned = analyzer.set_excludes(excludes);
+    if pruned > 0 {
+        println!("Dropped {} already scanned files that are now excluded", pruned);
+    }
+    Ok(())
+}
+
+fn print_tunables(analyzer: &StorageAnalyzer) {
+    let defaults = Config::default();
+    println!("\n--- Settings ---");
+    println!("{:<20} {:>10} {:>10}  Config key", "Name", "Current", "Default");
+    for (name, key) in TUNABLES {
+        let current = analyzer.config().get_tunable(name).unwrap_or_default();
+        let default = defaults.get_tunable(name).unwrap_or_default();
+        let line = format!("{:<20} {:>10} {:>10}  {}", name, current, default, key);
+        if current != default {
+            println!("{}", line.bright_white());
+        } else {
+            println!("{}", line);
+        }
+    }
+}
+
+// only the one setting is written, whatever else is in the file stays as it is
+fn save_tunable(name: &str, value: &str) -> io::Result<()> {
+    let mut config = Config::load()?;
+    config
+        .set_tunable(name, value)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    config.save()
+}
+
+// a setting is "session" when it differs from what the file and RSA_ variables give,
+// e.g. after set without --save
+fn print_settings(analyzer: &StorageAnalyzer) -> Result<()> {
+    let file_keys = Config::file_keys()?;
+    let env = &analyzer.config().env_overrides;
+    // the RSA_ variables as they were applied at startup, so nothing warns about them again
+    let mut loaded = Config::load()?.flatten();
+    for (key, value) in &mut loaded {
+        if let Some((_, from_env)) = env.iter().find(|(k, _)| k == key) {
+            *value = from_env.clone();
+        }
+    }
+    println!("\n--- Settings ({}) ---", config_path_display());
+    println!("{:<42} {:<30} Source", "Key", "Value");
+    for (key, value) in analyzer.config().flatten() {
+        let from_file = loaded.iter().find(|(k, _)| *k == key).map(|(_, v)| v);
+        let line = if from_file != Some(&value) {
+            format!("{:<42} {:<30} session", key, value).bright_white()
+        } else if env.iter().any(|(k, _)| *k == key) {
+            format!("{:<42} {:<30} env", key, value).normal()
+        } else if file_keys.contains(&key) {
+            format!("{:<42} {:<30} file", key, value).normal()
+        } else {
+            format!("{:<42} {:<30} default", key, value).normal()
+        };
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+fn apply_color(enabled: bool) {
+    if enabled {
+        colored::control::unset_override();
+    } else {
+        colored::control::set_override(false);
+    }
+}
+
+// a broken file leaves the current settings alone, session overrides are dropped on success
+fn reload_settings(analyzer: &mut StorageAnalyzer) {
+    let mut config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Kept the current settings, the config file is invalid: {}", e);
+            return;
+        }
+    };
+    config.apply_env();
+    apply_color(config.display.color);
+    match analyzer.apply_config(config) {
+        Ok(()) => println!("Reloaded {}", config_path_display()),
+        Err(e) => eprintln!("Kept the current settings: {}", e),
+    }
+}
+
+// opens the file in %EDITOR% (or notepad) and reloads once the editor is closed
+fn edit_settings(analyzer: &mut StorageAnalyzer) -> Result<()> {
+    let path = Config::path()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "couldn't find the config directory"))?;
+    if !path.exists() {
+        Config::load()?.save()?;
+    }
+
+    let editor = env::var("EDITOR").unwrap_or_else(|_| "notepad".to_string());
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or("notepad");
+    let status = process::Command::new(program).args(words).arg(&path).status()?;
+    if !status.success() {
+        println!("{} exited with {}, not reloading", program, status);
+        return Ok(());
+    }
+    reload_settings(analyzer);
+    Ok(())
+}
+
+// RSA_ overrides are listed too since they change what the config loads,
+// the smtp username and password are only reported as set
+fn print_environment() {
+    let mut vars: Vec<(String, String)> = ENV_REPORT_VARS
+        .iter()
+        .map(|name| {
+            let value = match env::var_os(name) {
+                Some(path) if *name == "PATH" => {
+                    let entries: Vec<_> = env::split_paths(&path).collect();
+                    let mut shown: Vec<String> = entries
+                        .iter()
+                        .take(ENV_REPORT_PATH_ENTRIES)
+                        .map(|p| p.display().to_string())
+                        .collect();
+                    if entries.len() > ENV_REPORT_PATH_ENTRIES {
+                        shown.push(format!("(+{} more)", entries.len() - ENV_REPORT_PATH_ENTRIES));
+                    }
+                    shown.join(";")
+                }
+                Some(value) => value.to_string_lossy().into_owned(),
+                None => "(not set)".to_string(),
+            };
+            (name.to_string(), value)
+        })
+        .collect();
+    let mut overrides: Vec<(String, String)> = env::vars_os()
+        .filter_map(|(name, value)| {
+            let name = name.into_string().ok()?;
+            if !name.starts_with("RSA_") {
+                return None;
+            }
+            let value = if name == "RSA_SMTP_USERNAME" || name == "RSA_SMTP_PASSWORD" {
+                "(set, hidden)".to_string()
+            } else {
+                value.to_string_lossy().into_owned()
+            };
+            Some((name, value))
+        })
+        .collect();
+    overrides.sort();
+    vars.extend(overrides);
+
+    println!("\n--- Environment ---");
+    let width = vars.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    for (name, value) in &vars {
+        println!("{:<width$} = {}", name, value, width = width);
+    }
+}
+
+fn print_excludes(analyzer: &StorageAnalyzer) {
+    let patterns = analyzer.excludes().patterns();
+    if patterns.is_empty() {
+        println!("Nothing is excluded, add entries with exclude add <path-or-glob>");
+        return;
+    }
+    println!("\n--- Excluded From Scans ---");
+    for (i, pattern) in patterns.iter().enumerate() {
+        println!("[{}] {}", i + 1, pattern);
+    }
+}
+
+fn move_target(analyzer: &mut StorageAnalyzer, src: &Path, dest: &str, verify_hash: bool) {
+    let units = analyzer.units();
+    let dest_dir = as_drive_root(dest).unwrap_or_else(|| dest.to_string());
+    println!("Moving {} -> {}", src.display(), dest_dir);
+
+    let mut last_percent = u64::MAX;
+    let result = file_ops::move_path(src, Path::new(&dest_dir), verify_hash, &mut |copied, total| {
+        let percent = (copied * 100).checked_div(total).unwrap_or(100);
+        if percent != last_percent {
+            last_percent = percent;
+            let filled = (percent / 5) as usize;
+            print!(
+                "\r[{}{}] {:>3}% {:.1}/{:.1} {}",
+                "#".repeat(filled), " ".repeat(20 - filled), percent,
+                copied as f64 / units.bytes_per_mb(), total as f64 / units.bytes_per_mb(), mb_label(units)
+            );
+            io::stdout().flush().unwrap();
+        }
+    });
+    println!();
+
+    match result {
+        Ok(new_path) => {
+            analyzer.forget_path(src);
+            analyzer.record_path(&new_path);
+            println!("Moved to {}", new_path.display());
+        }
+        Err(e) => eprintln!("move: failed to move '{}': {}", src.display(), e),
+    }
+}
+
 fn print_command_help(command: &String) {
         if let Some(info) = COMMAND_DESCRIPTIONS.get(command.as_str()) {
             print!("\n{}\n-------------\n{}\n",
@@ -79,29 +626,100 @@ fn print_all_help() {
     }
 }
 
-pub fn bash_commands() {
+pub fn bash_commands(mut analyzer: StorageAnalyzer) {
     
-    prompter_fn();
+    watch::install_ctrlc_handler();
+    analyzer.set_rescan_prompt(|question| confirm(question, "y"));
+
+    let mut editor = match DefaultEditor::new() {
+        Ok(editor) => editor,
+        Err(e) => {
+            eprintln!("couldn't start the shell: {}", e);
+            return;
+        }
+    };
+    let history_file = history_path();
+    if let Some(path) = &history_file {
+        // a missing file just means there's no history yet
+        let _ = editor.load_history(path);
+    }
+
+    // what the previous command printed, for copy-last
+    let mut last_output = String::new();
+    // set with `profile <name>`, used by every command that doesn't pass --profile
+    let mut session_profile: Option<String> = None;
+    // how long the previous command ran, shown once before the next prompt when it was slow
+    let mut last_duration = Duration::ZERO;
+    // what `more` continues
+    let mut paged: Option<PagedListing> = None;
 
     // wait for user input
-    let stdin = io::stdin();
-    let mut input = String::new();
-    let mut analyzer: StorageAnalyzer = StorageAnalyzer::new();
     loop {
-        stdin.read_line(&mut input).unwrap();
-        let command: Vec<String> = input
+        let input = match editor.readline(&prompter_fn(std::mem::take(&mut last_duration))) {
+            Ok(input) => input,
+            // same as Ctrl+C outside a long running command before rustyline took over the prompt
+            Err(ReadlineError::Interrupted) => process::exit(130),
+            Err(ReadlineError::Eof) => process::exit(0),
+            Err(e) => {
+                eprintln!("couldn't read input: {}", e);
+                return;
+            }
+        };
+        if !input.trim().is_empty() {
+            let _ = editor.add_history_entry(input.trim());
+            if let Some(path) = &history_file {
+                if let Some(dir) = path.parent() {
+                    let _ = fs::create_dir_all(dir);
+                }
+                let _ = editor.save_history(path);
+            }
+        }
+        // original casing is kept around for arguments like paths
+        let mut raw_command: Vec<String> = input
             .trim()
             .split_whitespace()
+            .map(|s| s.to_string())
+            .collect();
+        // scan flags work with any command that scans, so they're taken out before matching
+        if let Some(name) = &session_profile
+            && find_profile(analyzer.config(), name).is_none() {
+            println!("profile '{}' is gone from the config, scanning without it", name);
+            session_profile = None;
+        }
+        match take_scan_options(&mut raw_command, analyzer.config(), session_profile.as_deref()) {
+            Ok(options) => analyzer.set_scan_options(&options),
+            Err(e) => {
+                println!("{}", e);
+                continue;
+            }
+        }
+        let command: Vec<String> = raw_command
+            .iter()
             .map(|s| s.to_lowercase())
             .collect();
 
         if command.is_empty() {
-            input.clear();
-            prompter_fn();
             continue;
         }
 
+        // what the reports print is kept for copy-last, the shell's own messages and prompts aren't
+        let capture = command[0] != "copy-last";
+        if capture {
+            analyzer.start_capture();
+        }
+        // tsv output is meant for other programs, so no scan chatter and no color codes
+        let tsv = command.iter().any(|w| w == "--tsv");
+        if tsv {
+            analyzer.set_quiet(true);
+            apply_color(false);
+        }
+
+        let started = Instant::now();
         match command.iter().map(|s| s.as_str()).collect::<Vec<_>>()[..] {
+            [name, ..] if tsv && !TSV_LISTINGS.contains(&name) => {
+                println!("{}: --tsv only works with {}", name, TSV_LISTINGS.join(", "))
+            }
+
             // some default commands
             ["exit", ..] => match command.get(1) {
                 Some(code) => process::exit(code.parse::<i32>().unwrap()),
@@ -117,6 +735,43 @@ pub fn bash_commands() {
                 Ok(path) => println!("{}", path.display()),
                 Err(e) => println!("pwd: error getting current directory: {}", e),
             },
+            ["show-env"] => print_environment(),
+            ["history"] => {
+                let entries = history_entries(&editor);
+                print_history(&entries[entries.len().saturating_sub(HISTORY_SHOW_LAST)..]);
+            }
+            ["history", "search", ..] => match positional_arg(&raw_command, 2) {
+                Some(pattern) => {
+                    let pattern = pattern.to_lowercase();
+                    let matches: Vec<_> = history_entries(&editor)
+                        .into_iter()
+                        .filter(|(_, entry)| entry.to_lowercase().contains(&pattern))
+                        .collect();
+                    if matches.is_empty() {
+                        println!("no history entries contain '{}'", pattern);
+                    }
+                    print_history(&matches);
+                }
+                None => println!("usage: history search <pattern>"),
+            }
+            ["history", "clear"] => match editor.clear_history() {
+                Ok(()) => {
+                    if let Some(path) = &history_file {
+                        let _ = editor.save_history(path);
+                    }
+                    println!("History cleared");
+                }
+                Err(e) => println!("history: couldn't clear history: {}", e),
+            }
+            // everything that deletes or moves files stops here while read-only mode is on
+            ["delete" | "move" | "cleanup", ..] if analyzer.read_only() => println!("{}", READ_ONLY_MESSAGE),
+            // reading a label is fine, giving one writes to the volume
+            ["drive-label", ..] if analyzer.read_only() && positional_arg(&raw_command, 2).is_some() => {
+                println!("{}", READ_ONLY_MESSAGE)
+            }
+            ["temp-dirs", ..] if analyzer.read_only() && command.iter().any(|a| a == "--clean") => {
+                println!("{}", READ_ONLY_MESSAGE)
+            }
             ["help", ..] => match command.get(1) {
                 Some(cword) => print_command_help(cword),
                 None => print_all_help(),
@@ -128,6 +783,24 @@ pub fn bash_commands() {
                     (drive, |d| analyzer.print_drive_space_overview(d)),
                 None => println!("didnt put any inputs for DriveSpace"),
             }
+
+            ["show-drives", ..] => {
+                if let Err(e) = analyzer.print_drives() {
+                    eprintln!("Error: {}", e);
+                }
+            }
+
+            ["drive-label", ..] => match (command.get(1), positional_arg(&raw_command, 2)) {
+                (Some(drive), None) => validate_and_format_drive(drive, |d| {
+                    match analyzer.drive_label(d) {
+                        Some(label) => println!("{} is labelled \"{}\"", d, label),
+                        None => println!("{} has no label", d),
+                    }
+                    Ok(())
+                }),
+                (Some(drive), Some(label)) => validate_and_format_drive(drive, |d| analyzer.set_drive_label(d, &label)),
+                (None, _) => println!("usage: drive-label <drive> [new label]"),
+            }
             
             ["file-type-dist", ..] => match command.get(1) {
                     Some(drive) => validate_and_format_drive
@@ -135,41 +808,776 @@ pub fn bash_commands() {
                     None => println!("didnt put any inputs for DriveSpace"),
                 }
             
-            ["largest-files", ..] => match command.get(1) {
+            ["category-dist", ..] => match command.get(1) {
+                Some(drive) => validate_and_format_drive(drive, |d| analyzer.print_size_by_category(d)),
+                None => println!("usage: category-dist <drive>"),
+            }
+
+            ["folder-type-dist", ..] => match command.get(1) {
                     Some(drive) => validate_and_format_drive
-                        (drive, |d| analyzer.print_largest_files(d)),
-                    None => println!("didnt put any inputs for DriveSpace"),
+                        (drive, |d| analyzer.print_type_dist_per_folder(d)),
+                    None => println!("didnt put any inputs for FolderTypeDist"),
                 }
             
-            ["largest-folder", ..] => match command.get(1) {
-                    Some(drive) => validate_and_format_drive
-                        (drive, |d| analyzer.print_largest_folders(d)),
-                    None => println!("didnt put any inputs for DriveSpace"),
+            ["folder-age", ..] => match command.get(1) {
+                Some(drive) => validate_and_format_drive(drive, |d| analyzer.print_folders_by_content_age(d)),
+                None => println!("usage: folder-age <drive>"),
+            }
+
+            ["largest-files", ..] if !tsv => {
+                let args = args_without(&command, LIST_FLAGS);
+                let top_n = analyzer.config().display.top_n;
+                let request = list_options(&command, analyzer.units())
+                    .and_then(|options| page_of(&command, &args, &options, top_n).map(|page| (options, page)));
+                match (args.first(), request) {
+                    (_, Err(e)) => println!("largest-files: {}", e),
+                    (Some(drive), Ok((options, (offset, page_size)))) => validate_and_format_drive(drive, |d| {
+                        let ranked = analyzer.ranked_files(d, &options)?;
+                        analyzer.print_files_page(&ranked, &options, offset, page_size)?;
+                        paged = Some(PagedListing {
+                            ranked,
+                            generation: analyzer.cache_generation(),
+                            options,
+                            next: offset + page_size,
+                            page_size,
+                        });
+                        Ok(())
+                    }),
+                    (None, _) => println!("usage: largest-files <drive> [page size] [--page <n>]"),
+                }
+            }
+
+            ["more"] => match paged.take() {
+                None => println!("more: nothing to continue, run largest-files first"),
+                Some(listing) if analyzer.cache_generation() != listing.generation => {
+                    println!("more: the cache changed since (a scan, delete, move or new exclude), run largest-files again")
+                }
+                Some(listing) if listing.next >= listing.ranked.len() => {
+                    println!("more: that was the last page");
+                    paged = Some(listing);
                 }
+                Some(mut listing) => {
+                    if let Err(e) = analyzer.print_files_page(&listing.ranked, &listing.options, listing.next, listing.page_size) {
+                        eprintln!("Error: {}", e);
+                    }
+                    listing.next += listing.page_size;
+                    paged = Some(listing);
+                }
+            }
+
+            ["largest-files" | "largest-folder", ..] => {
+                let folders = command[0] == "largest-folder";
+                match (args_without(&command, LIST_FLAGS).first(), list_options(&command, analyzer.units())) {
+                    (_, Err(e)) => println!("{}: {}", command[0], e),
+                    // only the paged file listing keeps a position to turn pages from
+                    _ if flag_value(&command, "--page").is_some() => {
+                        println!("{}: --page only works with largest-files without --tsv", command[0])
+                    }
+                    (_, Ok(options)) if folders && options.by != SortKey::Size => {
+                        println!("largest-folder: folders can only be ranked by size")
+                    }
+                    (Some(drive), Ok(options)) => validate_and_format_drive(drive, |d| if folders {
+                        analyzer.print_largest_folders(d, &options)
+                    } else {
+                        analyzer.print_largest_files(d, &options)
+                    }),
+                    (None, _) => println!("didnt put any inputs for DriveSpace"),
+                }
+            }
+            
+            ["recent-large-files" | "old-large-files", ..] => {
+                let args = args_without(&command, LIST_FLAGS);
+                let recent = command[0] == "recent-large-files";
+                match (args.first(), args.get(1).map(|d| parse_days(d)), list_options(&command, analyzer.units())) {
+                    (_, Some(Err(e)), _) | (_, _, Err(e)) => println!("{}: {}", command[0], e),
+                    (Some(drive), days, Ok(options)) => {
+                        let days = days.and_then(Result::ok);
+                        validate_and_format_drive(drive, |d| if recent {
+                            analyzer.print_recent_large_files(d, days, &options)
+                        } else {
+                            analyzer.print_old_large_files(d, days, &options)
+                        })
+                    }
+                    (None, ..) if recent => println!("didnt put any inputs for RecentLargeFiles"),
+                    (None, ..) => println!("didnt put any inputs for OldLargeFiles"),
+                }
+            }
             
-            ["recent-large-files", ..] => match command.get(1) {
+            ["recent-large-folders", ..] => match (
+                command.get(1),
+                command.get(2).map(|days| days.parse::<u32>()),
+                command.get(3).map(|gb| gb.parse::<f64>()),
+            ) {
+                (None, ..) => println!("usage: recent-large-folders <drive> [days] [min_gb]"),
+                (_, Some(Err(_)), _) => println!("recent-large-folders: days has to be a whole number"),
+                (_, _, Some(Err(_))) => println!("recent-large-folders: min_gb has to be a number"),
+                (Some(drive), days, min_gb) => {
+                    let days = days.and_then(Result::ok).unwrap_or(RECENT_FOLDERS_DAYS);
+                    let min_gb = min_gb.and_then(Result::ok).unwrap_or(RECENT_FOLDERS_MIN_GB);
+                    validate_and_format_drive(drive, |d| analyzer.print_recent_large_folders(d, days, min_gb))
+                }
+            }
+            
+            ["installer-cache", ..] => match command.get(1) {
                 Some(drive) => validate_and_format_drive
-                    (drive, |d| analyzer.print_recent_large_files(d)),
-                None => println!("didnt put any inputs for DriveSpace"),
+                    (drive, |d| analyzer.print_installer_cache(d)),
+                None => println!("didnt put any inputs for InstallerCache"),
             }
             
-            ["old-large-files", ..] => match command.get(1) {
+            ["find-execs", ..] => match command.get(1) {
                 Some(drive) => validate_and_format_drive
-                    (drive, |d| analyzer.print_old_large_files(d)),
-                None => println!("didnt put any inputs for DriveSpace"),
+                    (drive, |d| analyzer.print_executables(d)),
+                None => println!("usage: find-execs <drive>"),
+            }
+
+            ["unsynced-large", ..] => match (command.get(1), command.get(2).map(|mb| mb.parse::<f64>())) {
+                (None, _) => println!("usage: unsynced-large <drive> [min_mb]"),
+                (_, Some(Err(_))) => println!("unsynced-large: min_mb has to be a number"),
+                (Some(drive), min_mb) => {
+                    let min_mb = min_mb.and_then(|mb| mb.ok()).unwrap_or(UNSYNCED_MIN_SIZE_MB);
+                    validate_and_format_drive(drive, |d| analyzer.print_unsynced_large_files(d, min_mb))
+                }
+            }
+
+            ["files-by-day", ..] => match command.get(1) {
+                Some(drive) => match command.get(2).map(|d| d.parse::<u32>()) {
+                    None => validate_and_format_drive
+                        (drive, |d| analyzer.print_files_by_day(d, analyzer.config().days.activity)),
+                    Some(Ok(days)) if days > 0 => validate_and_format_drive
+                        (drive, |d| analyzer.print_files_by_day(d, days)),
+                    Some(_) => println!("files-by-day: days must be a positive number"),
+                },
+                None => println!("didnt put any inputs for FilesByDay"),
             }
             
-            ["full-drive-analysis", ..] => match command.get(1) {
+            ["cleanup-candidates", ..] => match command.get(1) {
+                Some(drive) => validate_and_format_drive(drive, |d| {
+                    let candidates = analyzer.print_cleanup_candidates(d)?;
+                    if let Some(export_path) = flag_value(&raw_command, "--export") {
+                        cleanup::export_candidates(d, &candidates, Path::new(&export_path), analyzer.units())?;
+                        println!("Exported {} candidates to {}", candidates.len(), export_path);
+                    }
+                    Ok(())
+                }),
+                None => println!("didnt put any inputs for CleanupCandidates"),
+            }
+            
+            ["ext-mismatch" | "type-check", ..] => match command.get(1) {
+                Some(drive) => validate_and_format_drive
+                    (drive, |d| analyzer.print_extension_mismatches(d)),
+                None => println!("didnt put any inputs for ExtMismatch"),
+            }
+            
+            ["folder-percent", ..] => match command.get(1) {
+                Some(drive) => validate_and_format_drive
+                    (drive, |d| analyzer.print_folder_space_percentages(d)),
+                None => println!("usage: folder-percent <drive>"),
+            }
+            
+            ["folder-growth", ..] => match command.get(1) {
+                Some(drive) => validate_and_format_drive
+                    (drive, |d| analyzer.print_folder_growth(d)),
+                None => println!("usage: folder-growth <drive>"),
+            }
+
+            ["top-ext-files", ..] => {
+                let limit = command.get(3).map(|n| n.parse::<usize>());
+                match (command.get(1), command.get(2), limit) {
+                    (_, _, Some(Err(_) | Ok(0))) => println!("top-ext-files: limit must be a number, at least 1"),
+                    (Some(drive), Some(ext), limit) => validate_and_format_drive
+                        (drive, |d| analyzer.print_top_files_by_ext(d, ext, limit.and_then(Result::ok))),
+                    _ => println!("usage: top-ext-files <drive> <ext> [limit]"),
+                }
+            }
+            
+            ["ext-census", ..] => {
+                let args = args_without(&command, &["--min-count", "--export"]);
+                let min_count = flag_value(&command, "--min-count").map(|n| n.parse::<usize>());
+                match (args.first(), min_count) {
+                    (_, Some(Err(_))) => println!("ext-census: --min-count must be a number"),
+                    (Some(drive), min_count) => validate_and_format_drive(drive, |d| {
+                        let census = analyzer.print_extension_census(d, min_count.and_then(Result::ok).unwrap_or(1))?;
+                        if let Some(export_path) = flag_value(&raw_command, "--export") {
+                            report::export_extension_census(&census, Path::new(&export_path), analyzer.units())?;
+                            println!("Exported {} extensions to {}", census.len(), export_path);
+                        }
+                        Ok(())
+                    }),
+                    (None, _) => println!("didnt put any inputs for ExtCensus"),
+                }
+            }
+            
+            ["cleanup", ..] => match command.get(1) {
+                Some(drive) => validate_and_format_drive
+                    (drive, |d| wizard::run_cleanup_wizard(&mut analyzer, d)),
+                None => println!("didnt put any inputs for Cleanup"),
+            }
+            
+            ["compress-candidates", ..] => match command.get(1) {
+                Some(drive) => validate_and_format_drive
+                    (drive, |d| analyzer.print_compression_candidates(d)),
+                None => println!("didnt put any inputs for CompressCandidates"),
+            }
+            
+            ["ext-quotas", ..] => match command.get(1) {
+                Some(drive) => validate_and_format_drive
+                    (drive, |d| ext_quotas(&mut analyzer, d)),
+                None => println!("didnt put any inputs for ExtQuotas"),
+            }
+            
+            ["set"] | ["get"] => print_tunables(&analyzer),
+            
+            ["get", name] => match analyzer.config().get_tunable(name) {
+                Some(value) => println!("{} = {}", name, value),
+                None => println!("unknown setting '{}', run get to list them", name),
+            }
+            
+            ["set", ..] => {
+                let save = command.iter().any(|a| a == "--save");
+                let args: Vec<&str> = command.iter().skip(1).filter(|a| !a.starts_with("--")).map(|a| a.as_str()).collect();
+                match args.as_slice() {
+                    [name, value] => match analyzer.config_mut().set_tunable(name, value) {
+                        Ok(()) if save => match save_tunable(name, value) {
+                            Ok(()) => println!("{} = {} (saved)", name, value),
+                            Err(e) => eprintln!("{} = {} for this session, but saving failed: {}", name, value, e),
+                        },
+                        Ok(()) => println!("{} = {} (this session only, add --save to keep it)", name, value),
+                        Err(e) => eprintln!("Error: {}", e),
+                    },
+                    _ => println!("usage: set <name> <value> [--save], or just set to list everything"),
+                }
+            }
+            
+            ["config", "path"] => match Config::path() {
+                Some(path) if path.exists() => println!("{}", path.display()),
+                Some(path) => println!("{} (not created yet, using the defaults)", path.display()),
+                None => println!("no %APPDATA% or home directory found, using the defaults"),
+            }
+            
+            ["config", ..] => println!("usage: config path"),
+            
+            ["settings"] => if let Err(e) = print_settings(&analyzer) {
+                eprintln!("Error: {}", e);
+            },
+            ["settings", "edit"] => if let Err(e) = edit_settings(&mut analyzer) {
+                eprintln!("settings edit: {}", e);
+            },
+            ["settings", "reload"] => reload_settings(&mut analyzer),
+            ["settings", ..] => println!("usage: settings, settings edit or settings reload"),
+            
+            ["compare-ext", ext, drive_a, drive_b] => validate_and_format_drive(drive_a, |a| {
+                validate_and_format_drive(drive_b, |b| analyzer.print_compare_ext(ext, a, b));
+                Ok(())
+            }),
+            
+            ["compare-ext", ..] => println!("usage: compare-ext <ext> <driveA> <driveB>"),
+            
+            ["exclude", "add", ..] => match positional_arg(&raw_command, 2) {
+                Some(target) => {
+                    // #N adds a path from the last report, anything else is taken as a path or glob
+                    let pattern = resolve_target(&target, &analyzer)
+                        .map(|path| path.to_string_lossy().into_owned())
+                        .unwrap_or(target);
+                    let result = edit_excludes(&mut analyzer, |list| {
+                        if !list.contains(&pattern) {
+                            list.push(pattern.clone());
+                        }
+                        Ok(())
+                    });
+                    match result {
+                        Ok(()) => println!("Excluded {}", pattern),
+                        Err(e) => eprintln!("Error: {}", e),
+                    }
+                }
+                None => println!("usage: exclude add <path-or-glob>"),
+            }
+            
+            ["exclude", "list"] => print_excludes(&analyzer),
+            
+            ["exclude", "remove", n] => match n.parse::<usize>() {
+                Ok(n) if n >= 1 => {
+                    let result = edit_excludes(&mut analyzer, |list| {
+                        if n > list.len() {
+                            return Err(AnalyzerError::InvalidArgument(
+                                format!("there is no entry {}, see exclude list", n)));
+                        }
+                        println!("No longer excluding {}", list.remove(n - 1));
+                        Ok(())
+                    });
+                    match result {
+                        // files it kept out only come back with a fresh scan
+                        Ok(()) => println!("Run the scan again to pick up the files it was hiding"),
+                        Err(e) => eprintln!("Error: {}", e),
+                    }
+                }
+                _ => println!("usage: exclude remove <n>, numbers come from exclude list"),
+            }
+            
+            ["exclude", ..] => println!("usage: exclude <add <path-or-glob> | list | remove <n>>"),
+            
+            ["find", ..] => match (command.get(1), positional_arg(&raw_command, 2)) {
+                (Some(drive), Some(pattern)) => validate_and_format_drive
+                    (drive, |d| analyzer.print_files_by_pattern(d, &pattern)),
+                _ => println!("usage: find <drive> <pattern>"),
+            }
+            
+            ["filter-cache", ..] => match (command.get(1), positional_arg(&raw_command, 2)) {
+                (Some(drive), Some(expr)) => {
+                    // quoting the whole expression is optional, drop one pair if it's there
+                    let expr = expr.strip_prefix('"').and_then(|e| e.strip_suffix('"')).unwrap_or(&expr);
+                    validate_and_format_drive(drive, |d| analyzer.print_filter_cache(d, expr))
+                }
+                _ => println!("usage: filter-cache <drive> \"<expr>\", e.g. filter-cache C \"size_mb > 500 AND NOT ext == 'tmp'\""),
+            }
+            
+            ["set-streaming", value] => match value {
+                "true" | "on" => {
+                    analyzer.set_use_streaming(true);
+                    println!("Streaming enabled, single pass queries will walk the disk instead of caching");
+                }
+                "false" | "off" => {
+                    analyzer.set_use_streaming(false);
+                    println!("Streaming disabled");
+                }
+                _ => println!("usage: set-streaming <true|false>"),
+            }
+            
+            ["profile"] => match &session_profile {
+                Some(name) => println!("Active profile: {}", name),
+                None => println!("No profile active, scans use the defaults and their flags"),
+            }
+            
+            ["profile", "list"] => {
+                let profiles = &analyzer.config().profiles;
+                if profiles.is_empty() {
+                    let path = Config::path().map(|p| p.display().to_string()).unwrap_or_else(|| "the config".to_string());
+                    println!("No profiles yet, add [profile.<name>] tables to {}", path);
+                }
+                for (name, options) in profiles {
+                    let active = session_profile.as_deref().is_some_and(|p| p.eq_ignore_ascii_case(name));
+                    println!("{}{}: {}", name, if active { " (active)" } else { "" }, options.describe());
+                }
+            }
+            
+            ["profile", "off" | "none"] => {
+                session_profile = None;
+                println!("Profile cleared");
+            }
+            
+            ["profile", _] => match find_profile(analyzer.config(), &raw_command[1]) {
+                Some((name, options)) => {
+                    println!("Using profile {} for this session ({})", name, options.describe());
+                    session_profile = Some(name.clone());
+                }
+                None => println!("profile: no profile called '{}', see profile list", raw_command[1]),
+            }
+            
+            ["snapshot", "save", _] => {
+                if let Err(e) = analyzer.print_save_snapshot(&raw_command[2]) {
+                    println!("snapshot: {}", e);
+                }
+            }
+
+            ["snapshot", "load", _] => {
+                if let Err(e) = analyzer.print_load_snapshot(&raw_command[2]) {
+                    println!("snapshot: {}", e);
+                }
+            }
+
+            ["snapshot", "list"] => {
+                if let Err(e) = analyzer.print_snapshots() {
+                    println!("snapshot: {}", e);
+                }
+            }
+
+            ["snapshot", ..] => println!("usage: snapshot save <name> | snapshot load <name> | snapshot list"),
+
+            ["set-readonly", value] => match value {
+                "true" | "on" => {
+                    analyzer.set_read_only(true);
+                    println!("Read-only mode enabled, delete, move, cleanup and temp-dirs --clean are blocked");
+                }
+                "false" | "off" => {
+                    analyzer.set_read_only(false);
+                    println!("Read-only mode disabled");
+                }
+                _ => println!("usage: set-readonly <true|false>"),
+            }
+            
+            ["set-cache-ttl"] => match analyzer.cache_ttl() {
+                Some(0) => println!("Caching is off, every query scans again"),
+                Some(minutes) => println!("Cached scans expire after {} minutes", minutes),
+                None => println!("Cached scans never expire, see set-cache-ttl <minutes|off>"),
+            }
+            
+            ["set-cache-ttl", value] => match value {
+                "off" | "none" | "never" => {
+                    analyzer.set_cache_ttl(None);
+                    println!("Cached scans never expire, they stay until rescan or a settings reload");
+                }
+                _ => match value.parse::<u64>() {
+                    Ok(0) => {
+                        analyzer.set_cache_ttl(Some(0));
+                        println!("Caching disabled, every query scans the drive again");
+                    }
+                    Ok(minutes) => {
+                        analyzer.set_cache_ttl(Some(minutes));
+                        println!("Cached scans now expire after {} minutes", minutes);
+                    }
+                    Err(_) => println!("usage: set-cache-ttl <minutes|off>"),
+                },
+            }
+            
+            ["set-timeout"] => match analyzer.scan_timeout() {
+                Some(timeout) => println!("Scans stop after {}s and keep what they reached", timeout.as_secs()),
+                None => println!("Scans run until they're done, see set-timeout <seconds|off>"),
+            }
+
+            ["set-timeout", value] => match value {
+                "off" | "none" | "0" => {
+                    analyzer.set_scan_timeout(None);
+                    println!("Scan timeout off, scans run until they're done");
+                }
+                _ => match value.parse::<u64>() {
+                    Ok(secs) => {
+                        analyzer.set_scan_timeout(Some(secs));
+                        println!("Scans now stop after {}s, what was reached by then is cached as a partial scan", secs);
+                    }
+                    Err(_) => println!("usage: set-timeout <seconds|off>"),
+                },
+            }
+            
+            ["set-include-hidden", value] => match value {
+                "true" | "on" => {
+                    analyzer.set_include_hidden(true);
+                    println!("Hidden folders (names starting with .) are now included in folder listings");
+                }
+                "false" | "off" => {
+                    analyzer.set_include_hidden(false);
+                    println!("Hidden folders are left out of folder listings again");
+                }
+                _ => println!("usage: set-include-hidden <true|false>"),
+            }
+            
+            ["scan-progress-ui"] => println!(
+                "Live scan table is {}",
+                if analyzer.live_ui() { "on" } else { "off" }
+            ),
+            
+            ["scan-progress-ui", value] => match value {
+                "true" | "on" => {
+                    analyzer.set_live_ui(true);
+                    println!("Live scan table enabled, scans show their counters updating in place");
+                }
+                "false" | "off" => {
+                    analyzer.set_live_ui(false);
+                    println!("Live scan table disabled");
+                }
+                _ => println!("usage: scan-progress-ui <on|off>"),
+            }
+            
+            ["set-units", value] => match value {
+                "iec" => {
+                    analyzer.set_units(Units::Binary);
+                    println!("Sizes are now shown in KiB/MiB/GiB (1024 bytes per KiB)");
+                }
+                "si" => {
+                    analyzer.set_units(Units::Decimal);
+                    println!("Sizes are now shown in KB/MB/GB (1000 bytes per KB)");
+                }
+                _ => println!("usage: set-units <iec|si>"),
+            }
+            
+            ["duplicates", ..] => match command.get(1) {
+                Some(drive) => validate_and_format_drive
+                    (drive, |d| analyzer.print_duplicates(d)),
+                None => println!("didnt put any inputs for Duplicates"),
+            }
+            
+            ["cross-dup", ..] => match (args_without(&command, &["--min"]).as_slice(), flag_value(&command, "--min").map(|min| parse_size(&min, analyzer.units()))) {
+                (_, Some(Err(e))) => println!("cross-dup: --min: {}", e),
+                ([drive_a, drive_b], min) => {
+                    let min_bytes = min.and_then(|min| min.ok());
+                    validate_and_format_drive(drive_a, |a| {
+                        validate_and_format_drive(drive_b, |b| analyzer.print_cross_drive_duplicates(a, b, min_bytes));
+                        Ok(())
+                    })
+                }
+                _ => println!("usage: cross-dup <driveA> <driveB> [--min <size>]"),
+            },
+
+            ["reclaim-estimate", ..] => match command.get(1) {
                 Some(drive) => validate_and_format_drive
-                    (drive, |d| analyzer.analyze_drive(d)),
+                    (drive, |d| analyzer.print_reclaim_estimate(d)),
+                None => println!("didnt put any inputs for ReclaimEstimate"),
+            }
+            
+            ["email-report", ..] => match (command.get(1), raw_command.get(2)) {
+                (Some(drive), Some(recipient)) => validate_and_format_drive
+                    (drive, |d| email_report(&mut analyzer, d, recipient)),
+                _ => println!("usage: email-report <drive> <recipient>"),
+            }
+            
+            ["full-drive-analysis", ..] => match command.get(1) {
+                Some(drive) => validate_and_format_drive(drive, |d| {
+                    // only for this run, set-include-hidden is the one that sticks
+                    let include_hidden = analyzer.include_hidden();
+                    if command.iter().any(|a| a == "--include-hidden") {
+                        analyzer.set_include_hidden(true);
+                    }
+                    let result = if command.iter().any(|a| a == "--summary") {
+                        analyzer.print_drive_summary(d)
+                    } else {
+                        analyzer.analyze_drive(d)
+                    };
+                    analyzer.set_include_hidden(include_hidden);
+                    result?;
+                    if command.iter().any(|a| a == "--notify")
+                        && let Some(summary) = analyzer.scan_summary(d) {
+                        analyzer.notify_on_complete("Rusty Analyser", &summary);
+                    }
+                    Ok(())
+                }),
                 None => println!("didnt put any inputs for DriveSpace"),
             }
             
+            ["temp-dirs", ..] => {
+                let result = if command.iter().any(|a| a == "--clean") {
+                    clean_temp_dirs(&mut analyzer)
+                } else {
+                    analyzer.print_temp_dirs().map(|_| ())
+                };
+                if let Err(e) = result {
+                    eprintln!("Error: {}", e);
+                }
+            }
+            
+            ["compressed-files", ..] => match command.get(1) {
+                Some(drive) => validate_and_format_drive
+                    (drive, |d| analyzer.print_compressed_files(d)),
+                None => println!("didnt put any inputs for CompressedFiles"),
+            }
+            
+            ["analyze-downloads"] => if let Err(e) = analyzer.analyze_downloads() {
+                eprintln!("analyze-downloads: {}", e);
+            }
+
+            ["scan-usb"] | ["scan-usb-drives"] => {
+                let removable = analyzer.list_removable_drives();
+                if removable.is_empty() {
+                    println!("No removable drives detected");
+                } else {
+                    println!("Removable drives: {}", removable.join(", "));
+                }
+                for drive in removable {
+                    if !confirm(&format!("\nAnalyze {}? [y/N]", drive), "y") {
+                        continue;
+                    }
+                    if let Err(e) = analyzer.analyze_drive(&drive) {
+                        eprintln!("scan-usb: {}", e);
+                    }
+                }
+            }
+            
+            ["rescan", ..] => match command.get(1) {
+                Some(drive) => validate_and_format_drive(drive, |d| {
+                    analyzer.rescan(d)?;
+                    if let Some(summary) = analyzer.scan_summary(d) {
+                        println!("{}", summary);
+                    }
+                    Ok(())
+                }),
+                None => println!("didnt put any inputs for Rescan"),
+            }
+            
+            ["sparse-files", ..] => match command.get(1) {
+                Some(drive) => validate_and_format_drive
+                    (drive, |d| analyzer.print_sparse_files(d)),
+                None => println!("didnt put any inputs for SparseFiles"),
+            }
+            
+            ["top-creators", ..] => match command.get(1) {
+                Some(drive) => validate_and_format_drive
+                    (drive, |d| analyzer.print_top_creators(d)),
+                None => println!("didnt put any inputs for TopCreators"),
+            }
+            
+            ["tune-threads", ..] => match command.get(1) {
+                Some(drive) => validate_and_format_drive(drive, |d| {
+                    println!("\n--- Thread Count Benchmark ---");
+                    println!("Scanning {} once per thread count, this takes a while..", d);
+                    let best = analyzer.auto_tune_threads(d)?;
+                    println!("\nUsing {} threads for the rest of this session, set threads = {} under [scan] to keep it", best, best);
+                    Ok(())
+                }),
+                None => println!("didnt put any inputs for TuneThreads"),
+            }
+            
+            ["folder-timeline", ..] => match command.get(1) {
+                Some(drive) => match command.get(2).map(|d| d.parse::<usize>()) {
+                    None => validate_and_format_drive
+                        (drive, |d| analyzer.print_folder_timeline(d, 1)),
+                    Some(Ok(depth)) if depth > 0 => validate_and_format_drive
+                        (drive, |d| analyzer.print_folder_timeline(d, depth)),
+                    Some(_) => println!("folder-timeline: depth must be a positive number"),
+                },
+                None => println!("didnt put any inputs for FolderTimeline"),
+            }
+            
+            ["top-disk-writers"] => {
+                if let Err(e) = analyzer.print_top_disk_writers() {
+                    eprintln!("top-disk-writers: {}", e);
+                }
+            }
+
+            ["acl-report", ..] => match command.get(1) {
+                Some(drive) => match command.get(2).map(|d| d.parse::<usize>()) {
+                    None => validate_and_format_drive
+                        (drive, |d| analyzer.print_acl_report(d, 1)),
+                    Some(Ok(depth)) if depth > 0 => validate_and_format_drive
+                        (drive, |d| analyzer.print_acl_report(d, depth)),
+                    Some(_) => println!("acl-report: depth must be a positive number"),
+                },
+                None => println!("didnt put any inputs for AclReport"),
+            }
+            
+            ["check-permissions", ..] => match command.get(1) {
+                Some(drive) => validate_and_format_drive
+                    (drive, |d| analyzer.print_inaccessible_files(d)),
+                None => println!("didnt put any inputs for CheckPermissions"),
+            }
+            
+            ["symlink-cycles", ..] => match command.get(1) {
+                Some(drive) => validate_and_format_drive
+                    (drive, |d| analyzer.print_symlink_cycles(d)),
+                None => println!("usage: symlink-cycles <drive>"),
+            }
+
+            ["show-fs-errors", ..] => match command.get(1) {
+                Some(drive) => validate_and_format_drive
+                    (drive, |d| analyzer.print_fs_errors(d)),
+                None => println!("didnt put any inputs for ShowFsErrors"),
+            }
+
+            ["scan-errors", ..] => match command.get(1) {
+                Some(drive) => validate_and_format_drive(drive, |d| analyzer.print_scan_errors(d)),
+                None => println!("usage: scan-errors <drive>"),
+            }
+
+            ["cache-info", ..] => match command.get(1) {
+                Some(drive) => validate_and_format_drive(drive, |d| analyzer.print_cache_info(d)),
+                None => println!("usage: cache-info <drive>"),
+            }
+            
+            // commands covering every drive look again first, so a disk plugged in since startup is part of it
+            ["disk-warnings"] => {
+                analyzer.refresh_drives();
+                if let Err(e) = analyzer.print_disk_warnings() {
+                    eprintln!("disk-warnings: {}", e);
+                }
+            }
+
+            ["refresh-drives"] => {
+                if let Err(e) = analyzer.print_refresh_drives() {
+                    eprintln!("refresh-drives: {}", e);
+                }
+            }
+            
+            ["scan-batch", ..] => match positional_arg(&raw_command, 1) {
+                Some(manifest) => {
+                    let parallel = command.iter().any(|a| a == "--parallel");
+                    let result = batch::read_manifest(Path::new(&manifest))
+                        .and_then(|targets| analyzer.print_scan_batch(&targets, parallel));
+                    if let Err(e) = result {
+                        eprintln!("scan-batch: {}: {}", manifest, e);
+                    }
+                }
+                None => println!("usage: scan-batch <manifest_path> [--parallel]"),
+            }
+
+            ["usage-log", ..] => {
+                // a trailing number is the interval, everything before it the path
+                let mut words = raw_command[1..].to_vec();
+                let interval = match words.last().and_then(|w| w.parse::<u64>().ok()) {
+                    Some(secs) if words.len() > 1 => {
+                        words.pop();
+                        secs
+                    }
+                    _ => USAGE_LOG_INTERVAL_SECS,
+                };
+                match positional_arg(&words, 0) {
+                    Some(path) => {
+                        analyzer.refresh_drives();
+                        let drives: Vec<&str> = analyzer.drives.iter().map(String::as_str).collect();
+                        println!("Logging {} to {} every {}s (press Ctrl+C to stop)..", drives.join(", "), path, interval);
+                        match analyzer.log_disk_usage_periodically(&drives, interval, &path) {
+                            Ok(rows) => println!("Stopped, {} rows written to {}", rows, path),
+                            Err(e) => eprintln!("usage-log: {}", e),
+                        }
+                    }
+                    None => println!("usage: usage-log <log_path> [interval_secs]"),
+                }
+            }
+
+            ["watch-folder", ..] => match positional_arg(&raw_command, 1) {
+                Some(path) => if let Err(e) = watch::watch_folder(&path) {
+                    eprintln!("watch-folder: couldn't watch '{}': {}", path, e);
+                },
+                None => println!("didnt put any inputs for WatchFolder"),
+            }
+            
+            // file actions
+            ["delete", ..] => match positional_arg(&raw_command, 1) {
+                Some(target) => match resolve_target(&target, &analyzer) {
+                    Some(path) => delete_target
+                        (&mut analyzer, &path, command.iter().any(|a| a == "--permanent")),
+                    None => println!("delete: {} is not in the last report", target),
+                },
+                None => println!("didnt put any inputs for Delete"),
+            }
+            
+            ["move", ..] => {
+                // the last word is the destination, everything before it is the source
+                let words: Vec<&String> = raw_command.iter().skip(1).filter(|w| !w.starts_with("--")).collect();
+                match words.split_last() {
+                    Some((dest, src)) if !src.is_empty() => {
+                        let src = src.iter().map(|w| w.as_str()).collect::<Vec<_>>().join(" ");
+                        match resolve_target(&src, &analyzer) {
+                            Some(path) => move_target
+                                (&mut analyzer, &path, dest, command.iter().any(|a| a == "--verify-hash")),
+                            None => println!("move: {} is not in the last report", src),
+                        }
+                    }
+                    _ => println!("usage: move <path or #N> <drive or folder>"),
+                }
+            }
+            
+            ["open", ..] => match positional_arg(&raw_command, 1) {
+                Some(target) => match resolve_target(&target, &analyzer) {
+                    Some(path) => if let Err(e) = file_ops::open_in_file_manager(&path) {
+                        eprintln!("open: failed to open '{}': {}", path.display(), e);
+                    },
+                    None => println!("open: {} is not in the last report", target),
+                },
+                None => println!("didnt put any inputs for Open"),
+            }
+            
+            ["copy-last"] => match arboard::Clipboard::new().and_then(|mut c| c.set_text(last_output.trim())) {
+                Ok(()) => println!("Copied to clipboard ({} chars)", last_output.trim().chars().count()),
+                Err(e) => println!("copy-last: couldn't copy to the clipboard: {}", e),
+            }
+            
             _ => {
                 println!("{}: not found", command[0]);
             }
         }
-        input.clear();
-        prompter_fn();
+        last_duration = started.elapsed();
+        if tsv {
+            analyzer.set_quiet(false);
+            apply_color(analyzer.config().display.color);
+        }
+        if capture {
+            last_output = analyzer.finish_capture();
+        }
     }
 }
@@ -34,6 +34,22 @@ lazy_static! {
           title      : "Echo",
           description: "Repeats what you say, probably",
         }
+        add_command!{
+          m, "copy-last",
+          title      : "Copy Last",
+          description: "usage: copy-last \n\
+                        copies the report the previous command printed to the clipboard as plain text \n\
+                        (up to 1 MB, prompts and shell messages aren't included), \n\
+                        e.g. run largest-files C and then copy-last to paste the list into a ticket",
+        }
+        add_command!{
+          m, "history",
+          title      : "History",
+          description: "usage: history | history search <pattern> | history clear \n\
+                        shows the last 20 commands, every command containing the pattern (case-insensitive), \n\
+                        or wipes the history, up and down arrows go through it at the prompt \n\
+                        it's saved as history.txt next to the config file",
+        }
         add_command!{
           m, "type",
           title      : "Type",
@@ -44,16 +60,116 @@ lazy_static! {
           title      : "pwd",
           description: "Shows the location the program is ran in",
         }
+        add_command!{
+          m, "show-env",
+          title      : "Show Environment",
+          description: "usage: show-env \n\
+                        prints the environment variables that decide where the config, cache and temp folders are, \n\
+                        PATH (first 5 entries) and any RSA_ overrides, handy to paste into a bug report \n\
+                        secrets like RSA_SMTP_USERNAME and RSA_SMTP_PASSWORD only show as set",
+        }
+        add_command!{
+          m, "top-disk-writers",
+          title      : "Top Disk Writers",
+          description: "usage: top-disk-writers \n\
+                        watches the per-process write counters for 3 seconds and lists the 5 processes that wrote the most, \n\
+                        for finding out what keeps filling a drive, read-only and needs no administrator rights",
+        }
         add_command!{
           m, "drive-space",
           title      : "Drive Space",
           description: "Shows the amount of space in a drive, what else do you want?",
         }
+        add_command!{
+          m, "show-drives",
+          title      : "Show Drives",
+          description: "usage: show-drives \n\
+                        lists every drive the system reports with its kind, volume label, total and free space",
+        }
+        add_command!{
+          m, "refresh-drives",
+          title      : "Refresh Drives",
+          description: "usage: refresh-drives \n\
+                        looks for drives again, so a disk plugged in after the shell started can be analyzed \n\
+                        prints what was added and removed, the cached scan of a removed drive is dropped \n\
+                        disk-warnings and usage-log do this by themselves",
+        }
+        add_command!{
+          m, "drive-label",
+          title      : "Drive Label",
+          description: "usage: drive-label <drive> [new label] \n\
+                        shows the volume label of a drive, or renames it when a new label is given \n\
+                        labels are at most 32 characters and can't contain * ? / \\ | . , ; : + = [ ] < > \" \n\
+                        renaming usually needs the shell to run as administrator, windows only",
+        }
         add_command!{
           m, "file-type-dist",
           title      : "File Type Distribution",
           description: "Shows the distribution of the 10 file formats taking the largest space",
         }
+        add_command!{
+          m, "more",
+          title      : "More",
+          description: "usage: more \n\
+                        shows the next page of the last largest-files listing, picking up where it left off, \n\
+                        after a scan, delete, move or new exclude since largest-files has to be run again",
+        }
+        add_command!{
+          m, "category-dist",
+          title      : "Category Distribution",
+          description: "usage: category-dist <drive> \n\
+                        file count, size and share of the scanned space for media (video, audio, images), documents, \n\
+                        code, archives, system files and everything else, grouped by extension",
+        }
+        add_command!{
+          m, "folder-age",
+          title      : "Folder Age",
+          description: "usage: folder-age <drive> \n\
+                        the oldest and newest modification time of the files in each top-level folder \n\
+                        lists the 10 folders whose newest file is the oldest (nothing touched them in years, \n\
+                        worth archiving) and the 10 changed most recently",
+        }
+        add_command!{
+          m, "ext-mismatch",
+          title      : "Extension Mismatch",
+          description: "usage: ext-mismatch <drive> (or type-check <drive>) \n\
+                        reads the header of the 1000 largest files and lists the ones whose content doesn't match \n\
+                        their extension, like a .mp4 renamed to .pdf, files with no recognizable header are skipped",
+        }
+        add_command!{
+          m, "ext-census",
+          title      : "Extension Census",
+          description: "usage: ext-census <drive> [--min-count <n>] [--export <file.csv>] \n\
+                        lists every extension by how many files have it, no size cutoff, \n\
+                        good for spotting half a million .tmp files or a runaway .part downloader",
+        }
+        add_command!{
+          m, "folder-percent",
+          title      : "Folder Percentages",
+          description: "usage: folder-percent <drive> \n\
+                        the folders right below the drive root with their share of the used space and a bar, \n\
+                        files in the root itself and what the scan can't see make up the rest of the 100%",
+        }
+        add_command!{
+          m, "folder-growth",
+          title      : "Folder Growth",
+          description: "usage: folder-growth <drive> \n\
+                        compares the folder sizes of the cached scan with the scan it replaced, growth in red, \n\
+                        shrinking in green, folders that weren't there before say New \n\
+                        needs two scans this session, e.g. folder-percent C, then rescan C later and folder-growth C",
+        }
+        add_command!{
+          m, "top-ext-files",
+          title      : "Top Files By Extension",
+          description: "usage: top-ext-files <drive> <ext> [limit] \n\
+                        the largest files with one extension (mp4, .log, ...), biggest first, \n\
+                        limit defaults to display.top_n, no need for the full type distribution",
+        }
+        add_command!{
+          m, "folder-type-dist",
+          title      : "Folder Type Distribution",
+          description: "Shows the top 3 file formats taking the largest space in each top-level folder",
+        }
         add_command!{
           m, "Error-680089",
           title      : "????????",
@@ -62,28 +178,446 @@ lazy_static! {
         add_command!{
           m, "largest-files",
           title      : "Largest Files",
-          description: "Shows the top 10 largest files",
+          description: "Shows the top 10 largest files \n\
+                        usage: largest-files <drive> [page size] [--page <n>] [--min <size>] [--ext <ext>] [--count <n>] [--by <key>] [--reverse] \n\
+                        e.g. largest-files C 20 --page 3 lists files 41 to 60, `more` then shows the next 20 \n\
+                        e.g. largest-files C --min 1GB --ext iso --count 25, largest-files C --by modified \n\
+                        with --min only files at least that big are listed, --ext keeps one extension, \n\
+                        --count overrides top-n and --reverse (or --asc) lists the smallest matches first \n\
+                        --by ranks by size (default), modified, accessed, created or path instead, newest first \n\
+                        (oldest with --reverse), path goes Z to A (A to Z with --reverse), \n\
+                        files missing that time come last \n\
+                        --tsv prints a header row and tab-separated rows (bytes, RFC 3339 times) and nothing else, \n\
+                        for piping into PowerShell, awk or a spreadsheet, the other commands refuse it \n\
+                        recent- and old-large-files take the same flags except --by, largest-folder also leaves out --ext",
         }
         add_command!{
           m, "largest folder",
           title      : "Largest Folder",
           description: "Shows the top 10 largest folders up to 3 levels deep \n\
+                        usage: largest-folder <drive> [--min <size>] [--count <n>] [--reverse] \n\
+                        --reverse lists the smallest of the folders found first \n\
                         Excludes hidden folders (those starting with '.')",
         }
         add_command!{
           m, "recent-large-files",
           title      : "Recent Large Files",
-          description: "Shows most recent files within last 30 days that are large",
+          description: "Shows the largest files modified recently \n\
+                        usage: recent-large-files <drive> [window] [--min <size>] [--ext <ext>] [--count <n>] [--reverse], e.g. recent-large-files C 14d --min 500MB \n\
+                        the window takes days, weeks or months (14d, 2w, 3m), without it the \n\
+                        recent-window setting is used (30 days unless changed with set or the config) \n\
+                        --reverse lists the smallest of the recent files first",
+        }
+        add_command!{
+          m, "recent-large-folders",
+          title      : "Recent Large Folders",
+          description: "Shows the large folders with a file modified recently anywhere inside them \n\
+                        usage: recent-large-folders <drive> [days] [min_gb], e.g. recent-large-folders C 7 5 \n\
+                        days defaults to 30 and min_gb to 1",
         }
         add_command!{
           m, "old-large-files",
           title      : "Old Large Files",
-          description: "Shows older than 6 months files that are your m- i mean large",
+          description: "Shows large files that haven't been modified in a long time, your m- i mean large \n\
+                        usage: old-large-files <drive> [window] [--min <size>] [--ext <ext>] [--count <n>] [--reverse], e.g. old-large-files C 12m --min 1GB \n\
+                        the window takes days, weeks or months (90d, 8w, 6m), without it the \n\
+                        old-window setting is used (180 days unless changed with set or the config) \n\
+                        --reverse lists the smallest of the old files first",
+        }
+        add_command!{
+          m, "installer-cache",
+          title      : "Installer Cache",
+          description: "Shows leftover installer packages (.msi, .exe, .deb, ...) sitting in \n\
+                        temp, downloads and the Windows installer cache",
+        }
+        add_command!{
+          m, "find-execs",
+          title      : "Find Executables",
+          description: "usage: find-execs <drive> \n\
+                        the 20 biggest files that can run something (.exe, .dll, .sys, .bat, .ps1, .sh, ...) with the \n\
+                        total count and size of all of them, Windows has no execute bit so it goes by extension",
+        }
+        add_command!{
+          m, "unsynced-large",
+          title      : "Unsynced Large Files",
+          description: "usage: unsynced-large <drive> [min_mb] \n\
+                        files of at least min_mb (100 by default) that sit outside your OneDrive folders, \n\
+                        so they aren't backed up by it, with the total at the end",
+        }
+        add_command!{
+          m, "files-by-day",
+          title      : "Files By Day",
+          description: "Shows how many files were modified on each day, calendar style \n\
+                        usage: files-by-day <drive> [days], looks at the last 30 days by default",
+        }
+        add_command!{
+          m, "cleanup-candidates",
+          title      : "Cleanup Candidates",
+          description: "Lists things that are probably safe to delete (temp files, leftover installers, \n\
+                        dev artifacts, thumbnail clutter and big stale files) with a reason for each \n\
+                        usage: cleanup-candidates <drive> [--export <file.json|file.csv>]",
+        }
+        add_command!{
+          m, "cleanup",
+          title      : "Cleanup Wizard",
+          description: "Walks through the cleanup candidates one category at a time and asks what to delete \n\
+                        y = delete, n = keep, a = whole category, s = skip category, q = stop reviewing \n\
+                        approved items go to the Recycle Bin in one batch, system folders are never touched \n\
+                        and an undo log is kept in ~/.rusty-analyser/undo.log",
+        }
+        add_command!{
+          m, "compress-candidates",
+          title      : "Compression Candidates",
+          description: "Suggests large, old, rarely used files (logs, text, CSV, bitmaps, symbols) that would \n\
+                        shrink with NTFS compression, with savings estimated from a sample of each file \n\
+                        this only reports, nothing gets compressed",
+        }
+        add_command!{
+          m, "ext-quotas",
+          title      : "Extension Quotas",
+          description: "Checks how much space each extension uses against the limits in the config file \n\
+                        add rules to the config file (see config path) like: \n\
+                        [[quota]] \n\
+                        extension = \"mp4\" \n\
+                        limit_gb = 100",
+        }
+        add_command!{
+          m, "find",
+          title      : "Find",
+          description: "Finds files matching a glob pattern, case insensitive \n\
+                        usage: find <drive> <pattern>, e.g. find C *.iso or find C **/steamapps/**/*.vpk \n\
+                        patterns without a slash only look at the file name",
+        }
+        add_command!{
+          m, "filter-cache",
+          title      : "Filter Cache",
+          description: "Lists the cached files an expression matches, biggest first \n\
+                        usage: filter-cache <drive> \"<expr>\", e.g. filter-cache C \"size_mb > 500 AND (ext == 'mp4' OR ext == 'mkv')\" \n\
+                        fields: size_mb, ext, last_modified, last_accessed (dates as YYYY-MM-DD) \n\
+                        operators: > < >= <= == != combined with AND, OR, NOT and parentheses",
+        }
+        add_command!{
+          m, "compare-ext",
+          title      : "Compare Extension",
+          description: "usage: compare-ext <ext> <driveA> <driveB>, e.g. compare-ext mp4 C D \n\
+                        shows count and size of that extension on both drives and the difference (B - A) \n\
+                        both drives get scanned if they aren't cached yet",
+        }
+        add_command!{
+          m, "set",
+          title      : "Set",
+          description: "usage: set <name> <value> [--save], e.g. set top-n 25 or set min-folder-size 0.5 \n\
+                        changes a setting for this session, --save also writes it to the config file \n\
+                        with no arguments it lists every setting with its current and default value",
+        }
+        add_command!{
+          m, "get",
+          title      : "Get",
+          description: "usage: get <name>, shows the current value of a setting, get alone lists them all",
+        }
+        add_command!{
+          m, "config",
+          title      : "Config",
+          description: "usage: config path, shows where the config file lives \n\
+                        sections: [thresholds] min_folder_size_gb, min_file_type_size_gb, low_free_space_percent, \n\
+                        critical_free_space_percent / [days] recent_window_days, old_window_days, activity / [display] top_n, color, \n\
+                        units (binary/iec or decimal/si), date_format (strftime, e.g. %d/%m/%Y %H:%M), utc / [scan] threads, \n\
+                        exclude (globs like \"**/node_modules/**\" or \"*.tmp\", add --no-exclude to a command to scan without them), \n\
+                        skip_system_dirs (on by default, leaves Windows, System Volume Information and $Recycle.Bin out, \n\
+                        add --include-system to a command to scan them anyway), \n\
+                        user_data_roots (extra folders --user-data scans along with your profile), \n\
+                        follow_links (off by default, walks into symlinks and junctions, same as --follow-links), \n\
+                        max_depth (how many levels the scan goes down, unset for all, same as --max-depth), \n\
+                        cache_ttl_minutes (how long a scan stays cached, 0 for never, unset for until it's cleared), plus [smtp], [[quota]] \n\
+                        and [profile.<name>] (see profile) \n\
+                        anything left out keeps its default, changes apply on the next start or after settings reload \n\
+                        RSA_ environment variables override the file, e.g. RSA_THREADS=4, RSA_UNITS=decimal, RSA_NO_COLOR=1, \n\
+                        RSA_SCAN_EXCLUDE=\"**/node_modules/**;*.tmp\" (lists use ;), settings shows which ones are active",
+        }
+        add_command!{
+          m, "settings",
+          title      : "Settings",
+          description: "Shows every setting in effect, its value and whether it comes from the default, the file or this session \n\
+                        usage: settings, settings edit, settings reload \n\
+                        edit opens the config in %EDITOR% (notepad if unset) and reloads it once the editor closes, \n\
+                        GUI editors need their wait flag, e.g. EDITOR=\"code --wait\" \n\
+                        reload re-reads the file (and RSA_ variables) and keeps the current settings if it doesn't validate",
+        }
+        add_command!{
+          m, "exclude",
+          title      : "Exclude",
+          description: "Keeps paths out of every scan, saved in the config file \n\
+                        usage: exclude add <path-or-glob>, exclude list, exclude remove <n> \n\
+                        e.g. exclude add D:/Backups, exclude add *.ost or exclude add #3 from the last report \n\
+                        globs use the same rules as find, a match also excludes everything below it \n\
+                        --no-exclude on any scanning command ignores this list and [scan] exclude for that one command",
+        }
+        add_command!{
+          m, "set-streaming",
+          title      : "Set Streaming",
+          description: "usage: set-streaming <true|false> \n\
+                        when on, single pass queries like find walk the disk directly instead of \n\
+                        scanning everything into the cache first (uses a lot less memory)",
+        }
+        add_command!{
+          m, "profile",
+          title      : "Profile",
+          description: "usage: profile | profile list | profile <name> | profile off \n\
+                        profiles are named sets of scan options in the config, e.g. \n\
+                        [profile.quick] max_depth = 4, min_file_size = \"1MB\" / [profile.deep] follow_links = true \n\
+                        (also include_system, user_data, no_exclude), profile <name> uses one for the rest of the session \n\
+                        and --profile <name> on any scanning command uses one just for that command, \n\
+                        flags given on the command win over whatever the profile sets",
+        }
+        add_command!{
+          m, "snapshot",
+          title      : "Snapshot",
+          description: "usage: snapshot save <name> | snapshot load <name> | snapshot list \n\
+                        save writes the cached scans of every drive scanned so far to \n\
+                        ~/.rusty-analyser/snapshots/<name>.json (an existing one of that name is replaced), \n\
+                        load puts them back as the cache, so reports show the disk as it was then, \n\
+                        list shows every snapshot with when it was saved and how many files it holds",
+        }
+        add_command!{
+          m, "set-readonly",
+          title      : "Set Read-Only",
+          description: "usage: set-readonly <true|false> \n\
+                        while on, delete, move, cleanup, temp-dirs --clean and drive-label <drive> <label> refuse to run, \n\
+                        meant for auditing someone else's drive, starting with --read-only turns it on",
+        }
+        add_command!{
+          m, "set-cache-ttl",
+          title      : "Set Cache TTL",
+          description: "usage: set-cache-ttl [minutes|off] \n\
+                        cached scans older than this are thrown away and the drive is scanned again on the next query, \n\
+                        0 turns caching off, off (the default) keeps them until rescan, same as [scan] cache_ttl_minutes",
+        }
+        add_command!{
+          m, "set-timeout",
+          title      : "Set Scan Timeout",
+          description: "usage: set-timeout [seconds|off] \n\
+                        stops a scan that runs longer than this and caches whatever it reached, for network drives \n\
+                        that never finish, reports from such a scan only cover part of the drive, off (the default) waits",
+        }
+        add_command!{
+          m, "set-include-hidden",
+          title      : "Set Include Hidden",
+          description: "usage: set-include-hidden <true|false> \n\
+                        folders whose name starts with . (.cache, .local, .git and the like) are left out of \n\
+                        largest-folders and the folder part of every report unless this is on, their files always count",
+        }
+        add_command!{
+          m, "scan-progress-ui",
+          title      : "Scan Progress UI",
+          description: "usage: scan-progress-ui [on|off] \n\
+                        when on, scans redraw one line in place with files, folders and errors so far, \n\
+                        files per second and elapsed time, without an argument it shows the current mode",
+        }
+        add_command!{
+          m, "set-units",
+          title      : "Set Units",
+          description: "usage: set-units <iec|si> \n\
+                        iec shows sizes as KiB/MiB/GiB (powers of 1024, what Explorer calls KB/MB/GB) \n\
+                        si shows them as KB/MB/GB (powers of 1000, what drive makers use) \n\
+                        lasts for this session, set [display] units in the config to keep it",
+        }
+        add_command!{
+          m, "duplicates",
+          title      : "Duplicates",
+          description: "Finds files over 1 MB with identical contents and how much keeping one copy would save",
+        }
+        add_command!{
+          m, "cross-dup",
+          title      : "Cross-Drive Duplicates",
+          description: "usage: cross-dup <driveA> <driveB> [--min <size>] \n\
+                        files of 1 MB (or --min) and up on driveA with an identical copy on driveB (matched by size, then a SHA-256 of the contents), \n\
+                        each one next to its copy, and how much dropping one side would free, \n\
+                        for checking what a backup drive already has",
+        }
+        add_command!{
+          m, "reclaim-estimate",
+          title      : "Reclaim Estimate",
+          description: "Estimates how much space cleaning up could free, without deleting anything \n\
+                        Safe          : Recycle Bin, temp files, thumbnail clutter \n\
+                        Probably safe : + installers older than 30 days (not C:/Windows/Installer), dev artifacts \n\
+                        Aggressive    : + extra copies of duplicate files, large files untouched for 6 months \n\
+                        each tier includes the ones above it, every line names the report listing its items",
+        }
+        add_command!{
+          m, "email-report",
+          title      : "Email Report",
+          description: "Emails the full drive analysis as an HTML report \n\
+                        usage: email-report <drive> <recipient> \n\
+                        needs an [smtp] section (host, port, username, password) in the config file (see config path)",
         }
         add_command!{
           m, "full-drive-analysis",
           title      : "Full Drive Analysis",
-          description: "cant you read?",
+          description: "cant you read? \n\
+                        --summary prints one line per section (usage, biggest folder, top type, largest file, \n\
+                        recent growth, old files) instead of the full listings \n\
+                        --notify shows a desktop notification with a summary when it's done \n\
+                        --user-data (works with every scanning command) only scans your user folder and [scan] user_data_roots, \n\
+                        much faster and covers what you can actually clean up, a later full scan replaces it \n\
+                        --follow-links (also on every scanning command) walks into symlinked and junctioned folders, \n\
+                        each target only once, reports from such a scan say so since sizes overlap between drives \n\
+                        --include-hidden lists folders starting with . (like .cache) for this run, see set-include-hidden",
+        }
+        add_command!{
+          m, "analyze-downloads",
+          title      : "Analyze Downloads",
+          description: "usage: analyze-downloads \n\
+                        scans your Downloads folder and shows its total size, the file type distribution, \n\
+                        the largest files and everything over 90 days old",
+        }
+        add_command!{
+          m, "scan-usb",
+          title      : "Scan USB Drives",
+          description: "usage: scan-usb (or scan-usb-drives) \n\
+                        lists the removable drives that are plugged in and asks for each one \n\
+                        whether to run the full analysis on it",
+        }
+        add_command!{
+          m, "temp-dirs",
+          title      : "Temp Directories",
+          description: "Shows the size of every temp directory (%TEMP%, %TMP%, %LOCALAPPDATA%\\Temp, %SystemRoot%\\Temp) \n\
+                        usage: temp-dirs [--clean], --clean moves all their files to the Recycle Bin after asking, \n\
+                        files a program still has open are skipped",
+        }
+        add_command!{
+          m, "compressed-files",
+          title      : "Compressed Files",
+          description: "usage: compressed-files <drive> \n\
+                        lists files with NTFS compression turned on next to their real size on disk, \n\
+                        plus how much space the compression saves in total",
+        }
+        add_command!{
+          m, "rescan",
+          title      : "Rescan",
+          description: "usage: rescan <drive> [--max-depth <n>] \n\
+                        throws away the cached scan of the drive and scans it again \n\
+                        --max-depth (works with every scanning command) stops n levels below the drive, much faster \n\
+                        on spinning disks, everything built from such a scan says (scan limited to depth n) and asking \n\
+                        for a deeper report later offers to rescan",
+        }
+        add_command!{
+          m, "sparse-files",
+          title      : "Sparse Files",
+          description: "usage: sparse-files <drive> \n\
+                        lists NTFS sparse files (VM disks, databases..) that take up less than half their size on disk, \n\
+                        so their reported size overstates what deleting them would free",
+        }
+        add_command!{
+          m, "top-creators",
+          title      : "Top Creators",
+          description: "usage: top-creators <drive> \n\
+                        groups the 10,000 largest files by owner account and lists who owns the most space",
+        }
+        add_command!{
+          m, "tune-threads",
+          title      : "Tune Threads",
+          description: "usage: tune-threads <drive> \n\
+                        scans the drive with 1, 2, 4, 8, one per core and two per core threads, prints how long each took \n\
+                        and uses the fastest for the rest of the session, the first run also warms the disk cache",
+        }
+        add_command!{
+          m, "folder-timeline",
+          title      : "Folder Timeline",
+          description: "Lists folders in the order they were created, the ones from the last year are highlighted \n\
+                        usage: folder-timeline <drive> [depth], only the top level folders by default",
+        }
+        add_command!{
+          m, "acl-report",
+          title      : "ACL Report",
+          description: "Summarizes the permissions on the top level folders of a drive \n\
+                        usage: acl-report <drive> [depth], folders where Everyone has access are shown in red \n\
+                        and can be used as #1, #2.. afterwards, nothing is changed",
+        }
+        add_command!{
+          m, "check-permissions",
+          title      : "Check Permissions",
+          description: "usage: check-permissions <drive> \n\
+                        lists files and folders you can't read, grouped by the error \n\
+                        (files that need admin rights, files locked by another program, ...)",
+        }
+        add_command!{
+          m, "show-fs-errors",
+          title      : "Show Filesystem Errors",
+          description: "usage: show-fs-errors <drive> \n\
+                        lists paths the last scan couldn't read because of filesystem problems like corruption, \n\
+                        with the Windows error code and message (access denied is left out, that's normal)",
+        }
+        add_command!{
+          m, "scan-errors",
+          title      : "Scan Errors",
+          description: "usage: scan-errors <drive> \n\
+                        everything the last scan of the drive couldn't read (access denied, files gone mid-scan, \n\
+                        broken entries), counted by kind with the first 100 paths, whatever is inside them \n\
+                        is missing from every total (show-fs-errors only lists the filesystem errors)",
+        }
+        add_command!{
+          m, "cache-info",
+          title      : "Cache Info",
+          description: "usage: cache-info <drive> \n\
+                        when the cached scan of a drive was made, how many files and folders it holds and \n\
+                        every option it ran with (depth, minimum file size, links, system dirs, exclusions), \n\
+                        doesn't scan anything",
+        }
+        add_command!{
+          m, "symlink-cycles",
+          title      : "Symlink Cycles",
+          description: "usage: symlink-cycles <drive> \n\
+                        walks the drive following every symlink and junction and lists the ones that lead back \n\
+                        into a folder above them (link -> target), the scans skip them but other tools may not",
+        }
+        add_command!{
+          m, "disk-warnings",
+          title      : "Disk Warnings",
+          description: "Lists drives below 20% free space, and sends a desktop notification \n\
+                        when one drops below 10%",
+        }
+        add_command!{
+          m, "watch-folder",
+          title      : "Watch Folder",
+          description: "Prints files being created, modified, renamed or deleted in a folder as it happens \n\
+                        usage: watch-folder <path>, press Ctrl+C to stop watching",
+        }
+        add_command!{
+          m, "scan-batch",
+          title      : "Batch Scan",
+          description: "usage: scan-batch <manifest_path> [--parallel] \n\
+                        scans every drive or folder listed in the manifest, one per line (# starts a comment), \n\
+                        then shows files and size for each, paths that don't exist are skipped with a warning \n\
+                        --parallel scans them all at once, quicker across separate disks or network drives \n\
+                        a target the scan timeout cut short shows as partial, with --parallel the timeout covers the whole batch",
+        }
+        add_command!{
+          m, "usage-log",
+          title      : "Usage Log",
+          description: "usage: usage-log <log_path> [interval_secs] \n\
+                        appends timestamp,drive,total_gb,used_gb,free_gb,free_pct for every fixed drive to a CSV file \n\
+                        every interval (300 seconds by default) until Ctrl+C, the header is only written to a new file",
+        }
+        add_command!{
+          m, "delete",
+          title      : "Delete",
+          description: "Moves a file or folder to the Recycle Bin after asking for confirmation \n\
+                        usage: delete <path> or delete #N to pick the Nth entry of the last report \n\
+                        --permanent skips the Recycle Bin, there is no getting it back after that",
+        }
+        add_command!{
+          m, "move",
+          title      : "Move",
+          description: "Moves a file or folder to another drive or folder, with a progress bar \n\
+                        usage: move <path or #N> <drive or folder> \n\
+                        the source is only removed after the copy's size checks out, \n\
+                        --verify-hash also compares the contents",
+        }
+        add_command!{
+          m, "open",
+          title      : "Open",
+          description: "Shows a file in Explorer with it selected, folders are opened directly \n\
+                        usage: open <path> or open #N to pick the Nth entry of the last report",
         }
         m
     };
@@ -0,0 +1,76 @@
+// the analyzer only logs, this decides where it ends up: stderr at the chosen level the way
+// the status lines always looked, and with --log-file everything down to debug, timestamped
+use chrono::Local;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Write},
+    path::Path,
+    sync::Mutex,
+};
+
+struct ShellLogger {
+    stderr_level: LevelFilter,
+    file: Option<Mutex<File>>,
+}
+
+impl ShellLogger {
+    fn file_wants(&self, level: Level) -> bool {
+        self.file.is_some() && level <= LevelFilter::Debug
+    }
+}
+
+impl Log for ShellLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.stderr_level || self.file_wants(metadata.level())
+    }
+
+    fn log(&self, record: &Record) {
+        if record.level() <= self.stderr_level {
+            match record.level() {
+                Level::Error => eprintln!("Error: {}", record.args()),
+                Level::Warn => eprintln!("Warning: {}", record.args()),
+                _ => eprintln!("{}", record.args()),
+            }
+        }
+        if self.file_wants(record.level())
+            && let Some(Ok(mut file)) = self.file.as_ref().map(|file| file.lock()) {
+            let _ = writeln!(
+                file,
+                "{} {:<5} {}: {}",
+                Local::now().format("%Y-%m-%d %H:%M:%S%.3f"),
+                record.level(),
+                record.target(),
+                record.args()
+            );
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(Ok(mut file)) = self.file.as_ref().map(|file| file.lock()) {
+            let _ = file.flush();
+        }
+    }
+}
+
+// --log-level wins, then RUST_LOG if it's a plain level name, info otherwise
+pub fn level_from(arg: Option<&str>) -> Result<LevelFilter, String> {
+    match arg {
+        Some(level) => level
+            .parse()
+            .map_err(|_| format!("--log-level: {} isn't one of off, error, warn, info, debug, trace", level)),
+        None => Ok(std::env::var("RUST_LOG").ok().and_then(|level| level.parse().ok()).unwrap_or(LevelFilter::Info)),
+    }
+}
+
+// appends to `log_file` so several runs can end up in the same file
+pub fn init(level: LevelFilter, log_file: Option<&Path>) -> io::Result<()> {
+    let file = log_file
+        .map(|path| OpenOptions::new().create(true).append(true).open(path))
+        .transpose()?;
+    let max_level = if file.is_some() { level.max(LevelFilter::Debug) } else { level };
+    log::set_boxed_logger(Box::new(ShellLogger { stderr_level: level, file: file.map(Mutex::new) }))
+        .map_err(io::Error::other)?;
+    log::set_max_level(max_level);
+    Ok(())
+}
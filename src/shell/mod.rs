@@ -2,4 +2,7 @@
 pub mod commands;
 pub mod types;
 pub mod help_cmd;
+pub mod logging;
+pub mod watch;
+pub mod wizard;
 pub use commands::bash_commands;
\ No newline at end of file
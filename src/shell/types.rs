@@ -1,3 +1,4 @@
+use rusty_analyser::analyzer::types::{FileInfo, ListOptions};
 use serde::Serialize;
 use std::collections::{HashMap, HashSet};
 
@@ -5,4 +6,14 @@ use std::collections::{HashMap, HashSet};
 pub struct CommandInfo {
     pub title: &'static str,
     pub description: &'static str,
+}
+
+// the last largest-files ranking and where it left off, `more` prints the next page of it
+pub struct PagedListing {
+    pub ranked: Vec<FileInfo>,
+    pub options: ListOptions,
+    // the analyzer's cache_generation when it was listed, any scan, delete, move or exclude since makes it stale
+    pub generation: u64,
+    pub next: usize,
+    pub page_size: usize,
 }
\ No newline at end of file
@@ -0,0 +1,81 @@
+use rusty_analyser::analyzer::progress;
+use chrono::Local;
+use colored::Colorize;
+use notify::{
+    event::{EventKind, ModifyKind},
+    RecursiveMode, Watcher,
+};
+use std::{
+    fs,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc,
+    },
+    time::Duration,
+};
+
+// set while a long running command (like watch-folder) is active
+// the Ctrl+C handler flips STOP_REQUESTED instead of killing the shell when it is
+pub static LONG_RUNNING: AtomicBool = AtomicBool::new(false);
+pub static STOP_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+pub fn install_ctrlc_handler() {
+    let result = ctrlc::set_handler(|| {
+        if LONG_RUNNING.load(Ordering::SeqCst) {
+            STOP_REQUESTED.store(true, Ordering::SeqCst);
+        } else if !progress::cancel_running_scan() {
+            // a running scan stops with AnalyzerError::Cancelled instead, the shell keeps going
+            std::process::exit(130);
+        }
+    });
+    if let Err(e) = result {
+        eprintln!("Warning: couldn't install the Ctrl+C handler: {}", e);
+    }
+}
+
+// prints file system events under `path` as they happen, until Ctrl+C
+pub fn watch_folder(path: &str) -> notify::Result<()> {
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    watcher.watch(Path::new(path), RecursiveMode::Recursive)?;
+
+    println!("Watching {} (press Ctrl+C to stop)..", path);
+    STOP_REQUESTED.store(false, Ordering::SeqCst);
+    LONG_RUNNING.store(true, Ordering::SeqCst);
+
+    while !STOP_REQUESTED.load(Ordering::SeqCst) {
+        let event = match rx.recv_timeout(Duration::from_millis(200)) {
+            Ok(Ok(event)) => event,
+            Ok(Err(e)) => {
+                eprintln!("watch error: {}", e);
+                continue;
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => continue,
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        };
+
+        let label = match event.kind {
+            EventKind::Create(_) => "Created ".green(),
+            EventKind::Modify(ModifyKind::Name(_)) => "Renamed ".cyan(),
+            EventKind::Modify(_) => "Modified".yellow(),
+            EventKind::Remove(_) => "Deleted ".red(),
+            _ => continue,
+        };
+        let timestamp = Local::now().format("%H:%M:%S");
+
+        for path in &event.paths {
+            if let EventKind::Create(_) = event.kind {
+                let size = fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+                println!("{} {} {} ({} bytes)", timestamp, label, path.display(), size);
+            } else {
+                println!("{} {} {}", timestamp, label, path.display());
+            }
+        }
+    }
+
+    LONG_RUNNING.store(false, Ordering::SeqCst);
+    STOP_REQUESTED.store(false, Ordering::SeqCst);
+    println!("Stopped watching {}", path);
+    Ok(())
+}
@@ -0,0 +1,161 @@
+use rusty_analyser::analyzer::{
+    StorageAnalyzer,
+    config::{Config, Units},
+    error::Result,
+    file_ops,
+    types::*,
+    utils::{bytes_to_gb, bytes_to_mb, format_gb, format_mb}
+};
+use chrono::Local;
+use colored::Colorize;
+use std::{
+    fs::OpenOptions,
+    io::{self, Write},
+    path::Path,
+};
+
+enum Answer {
+    Yes,
+    No,
+    All,
+    SkipCategory,
+    Quit,
+}
+
+fn ask(question: &str) -> Answer {
+    loop {
+        print!("{} [y/n/a/s/q] ", question);
+        io::stdout().flush().unwrap();
+        let mut answer = String::new();
+        if io::stdin().read_line(&mut answer).is_err() {
+            return Answer::Quit;
+        }
+        match answer.trim().to_lowercase().as_str() {
+            "y" => return Answer::Yes,
+            "n" => return Answer::No,
+            "a" => return Answer::All,
+            "s" => return Answer::SkipCategory,
+            "q" => return Answer::Quit,
+            _ => println!("y = delete, n = keep, a = delete the whole category, s = skip category, q = stop reviewing"),
+        }
+    }
+}
+
+// walks through the cleanup candidates one category at a time, queues what
+// the user approves and recycles it all in one go at the end
+pub fn run_cleanup_wizard(analyzer: &mut StorageAnalyzer, drive: &str) -> Result<()> {
+    let units = analyzer.units();
+    let candidates = analyzer.cleanup_candidates(drive)?;
+    if candidates.is_empty() {
+        println!("Nothing to clean up, nice");
+        return Ok(());
+    }
+
+    // categories with the most confident reasons first, then the biggest ones
+    let mut categories: Vec<(String, Vec<CleanupCandidate>)> = Vec::new();
+    for candidate in candidates {
+        match categories.iter_mut().find(|(reason, _)| *reason == candidate.reason) {
+            Some((_, items)) => items.push(candidate),
+            None => categories.push((candidate.reason.clone(), vec![candidate])),
+        }
+    }
+    let best_confidence = |items: &[CleanupCandidate]| items.iter().map(|c| c.confidence).max();
+    let subtotal = |items: &[CleanupCandidate]| items.iter().map(|c| c.size_bytes).sum::<u64>();
+    categories.sort_by(|a, b| {
+        best_confidence(&b.1)
+            .cmp(&best_confidence(&a.1))
+            .then(subtotal(&b.1).cmp(&subtotal(&a.1)))
+    });
+
+    let mut queue: Vec<CleanupCandidate> = Vec::new();
+    'categories: for (reason, items) in categories {
+        println!(
+            "\n=== {} ({} items, {}) ===",
+            reason.bright_white(), items.len(), format_mb(units, bytes_to_mb(units, subtotal(&items)))
+        );
+
+        let mut approve_rest = false;
+        for item in items {
+            if file_ops::is_protected(Path::new(&item.path)) {
+                println!("  {} {} (protected location, skipped)", "[x]".red(), item.path.display());
+                continue;
+            }
+            if approve_rest {
+                queue.push(item);
+                continue;
+            }
+
+            println!("\n  {}", item.path.display());
+            println!("  Size: {}, Confidence: {:?}", format_mb(units, bytes_to_mb(units, item.size_bytes)), item.confidence);
+            match ask("  Delete?") {
+                Answer::Yes => queue.push(item),
+                Answer::No => {}
+                Answer::All => {
+                    queue.push(item);
+                    approve_rest = true;
+                }
+                Answer::SkipCategory => continue 'categories,
+                Answer::Quit => break 'categories,
+            }
+        }
+    }
+
+    finish(analyzer, queue)
+}
+
+fn finish(analyzer: &mut StorageAnalyzer, queue: Vec<CleanupCandidate>) -> Result<()> {
+    if queue.is_empty() {
+        println!("\nNothing queued, no changes made.");
+        return Ok(());
+    }
+
+    let units = analyzer.units();
+    let total: u64 = queue.iter().map(|c| c.size_bytes).sum();
+    println!("\n=== Summary ===");
+    for item in &queue {
+        println!("  {:>14}  {}", format_mb(units, bytes_to_mb(units, item.size_bytes)), item.path.display());
+    }
+    println!("{} items, {} / {}", queue.len(), format_mb(units, bytes_to_mb(units, total)), format_gb(units, bytes_to_gb(units, total)));
+
+    print!("\nMove all of these to the Recycle Bin? [y/N] ");
+    io::stdout().flush().unwrap();
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    if !answer.trim().eq_ignore_ascii_case("y") {
+        println!("Cancelled, no changes made.");
+        return Ok(());
+    }
+
+    let paths: Vec<&Path> = queue.iter().map(|c| Path::new(&c.path)).collect();
+    let result = file_ops::safe_delete_many(&paths);
+
+    // even a failed batch may have recycled some of the items, so check each one
+    let deleted: Vec<&CleanupCandidate> = queue.iter().filter(|c| !Path::new(&c.path).exists()).collect();
+    for item in &deleted {
+        analyzer.forget_path(&item.path);
+    }
+    write_undo_log(&deleted, units)?;
+
+    let freed: u64 = deleted.iter().map(|c| c.size_bytes).sum();
+    println!("Recycled {} of {} items, freed {}", deleted.len(), queue.len(), format_mb(units, bytes_to_mb(units, freed)));
+    if let Err(e) = result {
+        eprintln!("Some items could not be deleted: {}", e);
+    }
+    Ok(())
+}
+
+// the Recycle Bin is the real safety net, this just records what went in there and from where
+fn write_undo_log(deleted: &[&CleanupCandidate], units: Units) -> io::Result<()> {
+    if deleted.is_empty() {
+        return Ok(());
+    }
+
+    let log_path = Config::data_dir()?.join("undo.log");
+    let mut log = OpenOptions::new().create(true).append(true).open(&log_path)?;
+    let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S");
+    for item in deleted {
+        writeln!(log, "{}\trecycled\t{}\t{}\t{}", timestamp, format_mb(units, bytes_to_mb(units, item.size_bytes)), item.reason, item.path.display())?;
+    }
+    println!("Undo log written to {} (restore items from the Recycle Bin)", log_path.display());
+    Ok(())
+}
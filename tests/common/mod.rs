@@ -0,0 +1,144 @@
+// builds throwaway directory trees for the integration tests, only std so it works anywhere
+#![allow(dead_code)]
+
+use rusty_analyser::{Config, StorageAnalyzer};
+use std::{
+    env,
+    fs::{self, File, FileTimes},
+    path::{Path, PathBuf},
+    process,
+    time::{Duration, SystemTime},
+};
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// A folder under the temp dir that's removed again when this is dropped.
+pub struct Fixture {
+    root: PathBuf,
+}
+
+impl Fixture {
+    // `name` keeps tests running side by side out of each other's folders
+    pub fn new(name: &str) -> Fixture {
+        let root = env::temp_dir().join(format!("rusty-analyser-fixture-{}-{}", name, process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        Fixture { root }
+    }
+
+    /// `size` zero bytes at `path` (relative, with / between folders), modified just now.
+    pub fn file(&self, path: &str, size: usize) -> &Self {
+        self.aged_file(path, size, 0)
+    }
+
+    /// Same as [`file`](Self::file), last modified and accessed `days_old` days ago.
+    pub fn aged_file(&self, path: &str, size: usize, days_old: u32) -> &Self {
+        self.write(path, &vec![0u8; size], days_old)
+    }
+
+    /// `size` copies of `byte`, so files of the same size can still differ.
+    pub fn filled(&self, path: &str, size: usize, byte: u8) -> &Self {
+        self.write(path, &vec![byte; size], 0)
+    }
+
+    fn write(&self, path: &str, contents: &[u8], days_old: u32) -> &Self {
+        let path = self.path(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, contents).unwrap();
+        let when = SystemTime::now() - DAY * days_old;
+        let file = File::options().write(true).open(&path).unwrap();
+        file.set_times(FileTimes::new().set_modified(when).set_accessed(when)).unwrap();
+        self
+    }
+
+    pub fn dir(&self, path: &str) -> &Self {
+        fs::create_dir_all(self.path(path)).unwrap();
+        self
+    }
+
+    /// A junction at `link` pointing at the folder `target`, junctions need no admin rights
+    /// or developer mode the way directory symlinks do.
+    pub fn junction(&self, link: &str, target: &str) -> &Self {
+        let (link, target) = (self.path(link), self.path(target));
+        fs::create_dir_all(link.parent().unwrap()).unwrap();
+        let made = process::Command::new("cmd")
+            .arg("/C")
+            .arg("mklink")
+            .arg("/J")
+            .arg(&link)
+            .arg(&target)
+            .output()
+            .unwrap();
+        assert!(made.status.success(), "mklink /J {}: {}", link.display(), String::from_utf8_lossy(&made.stderr));
+        self
+    }
+
+    pub fn path(&self, path: &str) -> PathBuf {
+        path.split('/').fold(self.root.clone(), |full, part| full.join(part))
+    }
+
+    // what the analyzer takes as the drive
+    pub fn drive(&self) -> String {
+        self.root.to_string_lossy().to_string()
+    }
+
+    /// `path` relative to the fixture with / between folders, for comparing against literals.
+    pub fn relative(&self, path: &Path) -> String {
+        let parts: Vec<_> = path
+            .strip_prefix(&self.root)
+            .unwrap_or(path)
+            .components()
+            .map(|part| part.as_os_str().to_string_lossy().to_string())
+            .collect();
+        parts.join("/")
+    }
+
+    pub fn relative_all<'a>(&self, paths: impl IntoIterator<Item = &'a Path>) -> Vec<String> {
+        paths.into_iter().map(|path| self.relative(path)).collect()
+    }
+
+    /// A quiet analyzer with the size thresholds at zero, so tiny fixtures show up in every report.
+    pub fn analyzer(&self) -> StorageAnalyzer {
+        self.analyzer_with(Config::default())
+    }
+
+    pub fn analyzer_with(&self, mut config: Config) -> StorageAnalyzer {
+        config.thresholds.min_folder_size_gb = 0.0;
+        config.thresholds.min_file_type_size_gb = 0.0;
+        StorageAnalyzer::builder().config(config).quiet(true).build().unwrap()
+    }
+}
+
+impl Drop for Fixture {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}
+
+/// The tree most tests share, 45_400 bytes in 7 files:
+///
+/// ```text
+/// readme.txt             100  today
+/// docs/report.pdf      4_000  1 day old
+/// docs/notes.txt         300  400 days old
+/// docs/archive/old.zip 9_000  400 days old
+/// media/clip.mp4      20_000  2 days old
+/// media/song.mp3       5_000  100 days old (neither recent nor old)
+/// .cache/blob.bin      7_000  today, in a hidden folder
+/// empty/               no files
+/// ```
+pub fn standard_tree(name: &str) -> Fixture {
+    let fixture = Fixture::new(name);
+    fixture
+        .file("readme.txt", 100)
+        .aged_file("docs/report.pdf", 4_000, 1)
+        .aged_file("docs/notes.txt", 300, 400)
+        .aged_file("docs/archive/old.zip", 9_000, 400)
+        .aged_file("media/clip.mp4", 20_000, 2)
+        .aged_file("media/song.mp3", 5_000, 100)
+        .file(".cache/blob.bin", 7_000)
+        .dir("empty");
+    fixture
+}
+
+pub const STANDARD_TREE_BYTES: u64 = 45_400;
@@ -0,0 +1,49 @@
+// duplicate detection across two scanned locations, over fixtures with known contents
+mod common;
+
+use common::Fixture;
+use rusty_analyser::AnalyzerError;
+
+const MB: usize = 1024 * 1024;
+
+#[test]
+fn files_with_a_copy_on_the_other_drive_are_paired() {
+    let main = Fixture::new("cross-dup-main");
+    main.filled("photos/trip.jpg", 2 * MB, 1)
+        .filled("photos/other.jpg", 2 * MB, 2)
+        .filled("music/album.flac", 3 * MB, 3)
+        .filled("small.txt", 10, 4);
+    let backup = Fixture::new("cross-dup-backup");
+    backup
+        .filled("old/trip.jpg", 2 * MB, 1)
+        .filled("old/album.flac", 3 * MB, 3)
+        // same size as other.jpg, different bytes
+        .filled("old/lookalike.jpg", 2 * MB, 9)
+        .filled("small.txt", 10, 4);
+
+    let mut analyzer = main.analyzer();
+    let pairs = analyzer.find_cross_drive_duplicates(&main.drive(), &backup.drive(), None).unwrap();
+
+    let names: Vec<_> = pairs.iter().map(|(file, copy)| (main.relative(&file.full_path), backup.relative(&copy.full_path))).collect();
+    assert_eq!(
+        names,
+        [
+            ("music/album.flac".to_string(), "old/album.flac".to_string()),
+            ("photos/trip.jpg".to_string(), "old/trip.jpg".to_string()),
+        ]
+    );
+
+    let pairs = analyzer.find_cross_drive_duplicates(&main.drive(), &backup.drive(), Some(3 * MB as u64)).unwrap();
+    assert_eq!(pairs.len(), 1);
+    assert_eq!(main.relative(&pairs[0].0.full_path), "music/album.flac");
+}
+
+#[test]
+fn overlapping_locations_are_refused() {
+    let fixture = Fixture::new("cross-dup-overlap");
+    fixture.filled("inner/a.bin", 2 * MB, 1);
+    let mut analyzer = fixture.analyzer();
+    let inner = fixture.path("inner").to_string_lossy().to_string();
+    let result = analyzer.find_cross_drive_duplicates(&fixture.drive(), &inner, None);
+    assert!(matches!(result, Err(AnalyzerError::InvalidArgument(_))));
+}
@@ -0,0 +1,334 @@
+// the scan and the reports built from it, checked against trees with known sizes and ages
+mod common;
+
+use common::{standard_tree, Fixture, STANDARD_TREE_BYTES};
+use rusty_analyser::{
+    analyzer::{exclude::ExcludeList, types::ListOptions, utils::normalize_path},
+    AnalyzerError, Config, FolderSize, ScanOptions, SortOrder, StorageAnalyzer,
+};
+use chrono::Utc;
+use std::{
+    sync::{Arc, RwLock},
+    thread,
+};
+
+#[test]
+fn every_file_is_scanned_hidden_ones_included() {
+    let fixture = standard_tree("every-file");
+    let mut analyzer = fixture.analyzer();
+    let files = analyzer.get_largest_files(&fixture.drive(), SortOrder::Descending).unwrap();
+
+    assert_eq!(
+        fixture.relative_all(files.iter().map(|f| f.full_path.as_path())),
+        [
+            "media/clip.mp4",
+            "docs/archive/old.zip",
+            ".cache/blob.bin",
+            "media/song.mp3",
+            "docs/report.pdf",
+            "docs/notes.txt",
+            "readme.txt",
+        ]
+    );
+    assert_eq!(files.iter().map(|f| f.size_bytes).sum::<u64>(), STANDARD_TREE_BYTES);
+
+    let smallest = analyzer.get_largest_files(&fixture.drive(), SortOrder::Ascending).unwrap();
+    assert_eq!(fixture.relative(&smallest[0].full_path), "readme.txt");
+}
+
+#[test]
+fn folders_add_up_their_files_and_leave_hidden_and_empty_ones_out() {
+    let fixture = standard_tree("folders");
+    let mut analyzer = fixture.analyzer();
+    analyzer.collect_and_cache_files(&fixture.drive()).unwrap();
+    let folders = analyzer.get_largest_folders(&fixture.drive(), SortOrder::Descending).unwrap();
+
+    let summary: Vec<_> = folders
+        .iter()
+        .map(|f| (fixture.relative(&f.folder), f.size_bytes, f.file_count, f.subfolder_count))
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            ("media".to_string(), 20_000 + 5_000, 2, 0),
+            ("docs".to_string(), 4_000 + 300 + 9_000, 3, 1),
+            ("docs/archive".to_string(), 9_000, 1, 0),
+        ]
+    );
+    let docs = &folders[1];
+    assert_eq!(fixture.relative(&docs.largest_file.as_ref().unwrap().full_path), "docs/archive/old.zip");
+}
+
+#[test]
+fn folder_shares_only_count_the_folders_right_below_the_root() {
+    let fixture = standard_tree("folder-percent");
+    let mut analyzer = fixture.analyzer();
+    let shares = analyzer.folder_space_percentages(&fixture.drive()).unwrap();
+
+    // docs/archive is already inside docs, .cache is hidden and empty/ holds nothing
+    let sized: Vec<_> = shares.iter().filter(|(_, bytes, _)| *bytes > 0).map(|(folder, bytes, _)| (fixture.relative(folder), *bytes)).collect();
+    assert_eq!(sized, [("media".to_string(), 25_000), ("docs".to_string(), 13_300)]);
+    // measured against the used space of the disk the temp dir is on, so only the order is fixed
+    assert!(shares[0].2 > 0.0 && shares[0].2 >= shares[1].2);
+}
+
+#[test]
+fn hidden_folders_are_listed_once_asked_for() {
+    let fixture = standard_tree("hidden");
+    let mut analyzer = fixture.analyzer();
+    analyzer.collect_and_cache_files(&fixture.drive()).unwrap();
+    let folders = analyzer.get_largest_folders(&fixture.drive(), SortOrder::Descending).unwrap();
+    assert_eq!(fixture.relative_all(folders.iter().map(|f| f.folder.as_path())), ["media", "docs", "docs/archive"]);
+
+    // the same cached scan answers once hidden folders are asked for, nothing is scanned again
+    let scanned_at = analyzer.cache_info(&fixture.drive()).unwrap().scanned_at;
+    analyzer.set_include_hidden(true);
+    let folders = analyzer.get_largest_folders(&fixture.drive(), SortOrder::Descending).unwrap();
+    assert_eq!(analyzer.cache_info(&fixture.drive()).unwrap().scanned_at, scanned_at);
+
+    let names = fixture.relative_all(folders.iter().map(|f| f.folder.as_path()));
+    assert_eq!(names, ["media", "docs", "docs/archive", ".cache"]);
+}
+
+#[test]
+fn type_distribution_groups_by_extension_with_recent_bytes() {
+    let fixture = standard_tree("types");
+    let mut analyzer = fixture.analyzer();
+    let distribution = analyzer.get_file_type_distribution(&fixture.drive()).unwrap();
+
+    // (extension, bytes, files, bytes modified in the last 30 days)
+    let expected = [
+        ("mp4", 20_000, 1, 20_000),
+        ("zip", 9_000, 1, 0),
+        ("bin", 7_000, 1, 7_000),
+        ("mp3", 5_000, 1, 0),
+        ("pdf", 4_000, 1, 4_000),
+        ("txt", 400, 2, 100),
+    ];
+    let expected: Vec<_> = expected.iter().map(|&(ext, bytes, count, recent)| (ext.to_string(), bytes, count, recent)).collect();
+    assert_eq!(distribution, expected);
+}
+
+#[test]
+fn type_distribution_drops_extensions_under_the_threshold() {
+    let fixture = standard_tree("types-threshold");
+    let mut config = Config::default();
+    // between pdf (4_000) and mp3 (5_000) whichever units the GB are read in
+    config.thresholds.min_file_type_size_gb = 4_500.0 / 1024f64.powi(3);
+    let mut analyzer = StorageAnalyzer::builder().config(config).quiet(true).build().unwrap();
+    let distribution = analyzer.get_file_type_distribution(&fixture.drive()).unwrap();
+
+    let extensions: Vec<_> = distribution.iter().map(|(ext, _, _, _)| ext.as_str()).collect();
+    assert_eq!(extensions, ["mp4", "zip", "bin", "mp3"]);
+    // nothing is left out of the categories, they still add up to the whole tree
+    let categories = analyzer.size_by_category(&fixture.drive()).unwrap();
+    assert_eq!(categories.iter().map(|(_, _, bytes)| bytes).sum::<u64>(), STANDARD_TREE_BYTES);
+}
+
+#[test]
+fn recent_and_old_files_follow_the_day_windows() {
+    let fixture = standard_tree("age");
+    let mut analyzer = fixture.analyzer();
+
+    let recent = analyzer.get_recent_large_files(&fixture.drive()).unwrap();
+    assert_eq!(
+        fixture.relative_all(recent.iter().map(|f| f.full_path.as_path())),
+        ["media/clip.mp4", ".cache/blob.bin", "docs/report.pdf", "readme.txt"]
+    );
+
+    let old = analyzer.get_old_large_files(&fixture.drive()).unwrap();
+    assert_eq!(fixture.relative_all(old.iter().map(|f| f.full_path.as_path())), ["docs/archive/old.zip", "docs/notes.txt"]);
+}
+
+#[test]
+fn recent_large_folders_need_a_recent_file_somewhere_inside() {
+    let fixture = standard_tree("recent-folders");
+    let mut analyzer = fixture.analyzer();
+    let names = |folders: Vec<FolderSize>| fixture.relative_all(folders.iter().map(|f| f.folder.as_path()));
+
+    // docs/archive only holds a 400 day old file
+    assert_eq!(names(analyzer.recent_large_folders(&fixture.drive(), 30, 0.0).unwrap()), ["media", "docs"]);
+    // between docs (13_300) and media (25_000) whichever units the GB are read in
+    let min_gb = 20_000.0 / 1024f64.powi(3);
+    assert_eq!(names(analyzer.recent_large_folders(&fixture.drive(), 30, min_gb).unwrap()), ["media"]);
+    assert!(analyzer.recent_large_folders(&fixture.drive(), 0, 0.0).unwrap().is_empty());
+    // a window longer than the calendar goes back counts every folder instead of overflowing
+    assert_eq!(names(analyzer.recent_large_folders(&fixture.drive(), u32::MAX, 0.0).unwrap()), ["media", "docs", "docs/archive"]);
+}
+
+#[test]
+fn list_filters_narrow_the_ranking() {
+    let fixture = standard_tree("filters");
+    let mut analyzer = fixture.analyzer();
+
+    let txt = ListOptions { ext: Some("txt".to_string()), ..ListOptions::default() };
+    let files = analyzer.ranked_files(&fixture.drive(), &txt).unwrap();
+    assert_eq!(fixture.relative_all(files.iter().map(|f| f.full_path.as_path())), ["docs/notes.txt", "readme.txt"]);
+
+    let big = ListOptions { min_bytes: Some(5_000), ..ListOptions::default() };
+    let files = analyzer.ranked_files(&fixture.drive(), &big).unwrap();
+    assert_eq!(
+        fixture.relative_all(files.iter().map(|f| f.full_path.as_path())),
+        ["media/clip.mp4", "docs/archive/old.zip", ".cache/blob.bin", "media/song.mp3"]
+    );
+
+    let page = ListOptions { offset: 2, ..ListOptions::default() };
+    let files = page.apply(analyzer.get_largest_files(&fixture.drive(), SortOrder::Descending).unwrap(), 2);
+    assert_eq!(fixture.relative_all(files.iter().map(|f| f.full_path.as_path())), [".cache/blob.bin", "media/song.mp3"]);
+}
+
+#[test]
+fn a_scan_behind_a_shared_lock_can_be_inspected_from_another_thread() {
+    let fixture = standard_tree("shared-cache");
+    let analyzer = Arc::new(RwLock::new(fixture.analyzer()));
+    let before = Utc::now();
+    {
+        let mut analyzer = analyzer.write().unwrap();
+        analyzer.set_scan_options(&ScanOptions { follow_links: Some(false), ..ScanOptions::default() });
+        analyzer.collect_and_cache_files(&fixture.drive()).unwrap();
+    }
+
+    let reader = Arc::clone(&analyzer);
+    let drive = fixture.drive();
+    let info = thread::spawn(move || reader.read().unwrap().cache_info(&drive)).join().unwrap().unwrap();
+    assert_eq!(info.file_count, 7);
+    assert!(info.scanned_at >= before && info.scanned_at <= Utc::now());
+    assert_eq!(info.options.follow_links, Some(false));
+    assert!(!info.is_partial());
+}
+
+#[test]
+fn scan_options_leave_files_out_of_the_cache() {
+    let fixture = standard_tree("scan-options");
+    let mut analyzer = fixture.analyzer();
+
+    let shallow = ScanOptions { max_depth: Some(1), ..ScanOptions::default() };
+    analyzer.collect_and_cache_files_with(&fixture.drive(), &shallow).unwrap();
+    let files = analyzer.cached_files(&fixture.drive()).unwrap();
+    assert_eq!(fixture.relative_all(files.iter().map(|f| f.full_path.as_path())), ["readme.txt"]);
+
+    // plain bytes, so the display units can't change what 6_000 means
+    let large_only = ScanOptions { min_file_size: Some("6000B".to_string()), ..ScanOptions::default() };
+    analyzer.collect_and_cache_files_with(&fixture.drive(), &large_only).unwrap();
+    let mut names = fixture.relative_all(analyzer.cached_files(&fixture.drive()).unwrap().iter().map(|f| f.full_path.as_path()));
+    names.sort();
+    assert_eq!(names, [".cache/blob.bin", "docs/archive/old.zip", "media/clip.mp4"]);
+}
+
+#[test]
+fn excluded_folders_are_never_walked() {
+    let fixture = standard_tree("exclude");
+    let mut config = Config::default();
+    config.scan.exclude = vec!["**/archive".to_string()];
+    let mut analyzer = fixture.analyzer_with(config);
+
+    let files = analyzer.get_largest_files(&fixture.drive(), SortOrder::Descending).unwrap();
+    assert_eq!(files.len(), 6);
+    assert!(files.iter().all(|f| !f.full_path.ends_with("old.zip")));
+    assert_eq!(analyzer.cache_info(&fixture.drive()).unwrap().excluded, 1);
+}
+
+#[test]
+fn system_folder_names_are_only_skipped_at_a_drive_root() {
+    let fixture = Fixture::new("system-names");
+    fixture.file("Windows/kept.dll", 4_096).file("$Recycle.Bin/kept.bin", 1_024);
+    let mut analyzer = fixture.analyzer();
+
+    let files = analyzer.get_largest_files(&fixture.drive(), SortOrder::Descending).unwrap();
+    assert_eq!(fixture.relative_all(files.iter().map(|f| f.full_path.as_path())), ["Windows/kept.dll", "$Recycle.Bin/kept.bin"]);
+}
+
+#[test]
+fn a_new_exclude_that_prunes_the_cache_makes_old_listings_stale() {
+    let fixture = standard_tree("exclude-later");
+    let mut analyzer = fixture.analyzer();
+    analyzer.get_largest_files(&fixture.drive(), SortOrder::Descending).unwrap();
+
+    let before = analyzer.cache_generation();
+    assert_eq!(analyzer.set_excludes(ExcludeList::new(&["**/nothing-here".to_string()]).unwrap()), 0);
+    assert_eq!(analyzer.cache_generation(), before);
+
+    assert_eq!(analyzer.set_excludes(ExcludeList::new(&["**/archive".to_string()]).unwrap()), 1);
+    assert_ne!(analyzer.cache_generation(), before);
+}
+
+#[test]
+fn a_broken_exclude_in_the_config_only_fails_patterns_given_to_the_builder() {
+    let config = Config { exclude: vec!["docs/[unclosed".to_string()], ..Config::default() };
+    assert!(StorageAnalyzer::builder().config(config).quiet(true).build().is_ok());
+    let built = StorageAnalyzer::builder().exclude("docs/[unclosed").quiet(true).build();
+    assert!(matches!(built, Err(AnalyzerError::InvalidArgument(_))));
+}
+
+#[test]
+fn a_poisoned_metrics_registry_still_lets_scans_be_cached() {
+    let fixture = standard_tree("poisoned-metrics");
+    let mut analyzer = fixture.analyzer();
+    let registry = analyzer.metrics_registry();
+    let _ = std::thread::spawn(move || {
+        let _held = registry.lock().unwrap();
+        panic!("metrics reader died holding the lock");
+    })
+    .join();
+    assert!(analyzer.metrics_registry().is_poisoned());
+
+    analyzer.collect_and_cache_files(&fixture.drive()).unwrap();
+    assert_eq!(analyzer.cache_info(&fixture.drive()).unwrap().file_count, 7);
+    assert!(!analyzer.cached_folders(&fixture.drive()).unwrap().is_empty());
+}
+
+#[test]
+fn only_a_junction_back_into_its_own_folder_is_a_cycle() {
+    let fixture = Fixture::new("junction-loop");
+    fixture.file("docs/notes.txt", 100).junction("docs/loop", "docs");
+    let cycles = fixture.analyzer().detect_symlink_cycles(&fixture.drive()).unwrap();
+    let link = normalize_path(&fixture.path("docs/loop").to_string_lossy());
+    assert_eq!(cycles.len(), 1, "{:?}", cycles);
+    assert!(cycles[0].starts_with(&format!("{} -> ", link)), "{:?}", cycles);
+
+    // a junction over to a sibling folder only shows the same files twice, it ends
+    let fixture = Fixture::new("junction-sibling");
+    fixture.file("b/notes.txt", 100).junction("a/to-b", "b");
+    assert!(fixture.analyzer().detect_symlink_cycles(&fixture.drive()).unwrap().is_empty());
+}
+
+#[test]
+fn a_missing_root_is_its_own_error() {
+    let fixture = Fixture::new("missing");
+    let mut analyzer = fixture.analyzer();
+    let missing = fixture.path("not-there").to_string_lossy().to_string();
+    assert!(matches!(analyzer.collect_and_cache_files(&missing), Err(AnalyzerError::RootNotFound(_))));
+}
+
+// sizes are whole bytes everywhere, a NaN can only come in through the GB thresholds
+#[test]
+fn nan_thresholds_leave_the_sorted_reports_working() {
+    let fixture = standard_tree("nan-thresholds");
+    let mut config = Config::default();
+    config.thresholds.min_folder_size_gb = f64::NAN;
+    config.thresholds.min_file_type_size_gb = f64::NAN;
+    let mut analyzer = StorageAnalyzer::builder().config(config).quiet(true).build().unwrap();
+    let drive = fixture.drive();
+
+    assert_eq!(analyzer.get_largest_files(&drive, SortOrder::Descending).unwrap().len(), 7);
+    assert_eq!(analyzer.get_file_type_distribution(&drive).unwrap().len(), 6);
+    assert_eq!(analyzer.get_largest_folders(&drive, SortOrder::Descending).unwrap().len(), 3);
+    assert_eq!(analyzer.get_recent_large_files(&drive).unwrap().len(), 4);
+    assert_eq!(analyzer.get_old_large_files(&drive).unwrap().len(), 2);
+}
+
+#[test]
+fn a_parallel_batch_answers_in_manifest_order_and_scans_each_target_once() {
+    let fixture = Fixture::new("batch-order");
+    fixture.file("a/one.bin", 10).file("b/two.bin", 20).file("c/three.bin", 30);
+    let target = |name: &str| fixture.path(name).to_string_lossy().to_string();
+    let mut analyzer = fixture.analyzer();
+    // b comes out of the cache, the others are walked
+    analyzer.collect_and_cache_files(&target("b")).unwrap();
+
+    let targets = [target("c"), target("b"), target("missing"), target("a"), target("c").replace('\\', "/")];
+    let results = analyzer.scan_batch(&targets, true);
+    let order: Vec<_> = results.iter().map(|(target, _)| fixture.relative(target.as_ref())).collect();
+    assert_eq!(order, ["c", "b", "a"]);
+    assert!(results.iter().all(|(_, result)| result.is_ok()));
+}
@@ -0,0 +1,46 @@
+// the scan timeout, in a binary of its own since a deadline going off cancels every scan in the process
+mod common;
+
+use common::Fixture;
+use rusty_analyser::{analyzer::types::ScanStatus, StorageAnalyzer};
+use std::time::Duration;
+
+// enough files that the walk is still going when a tiny timeout runs out
+fn wide_tree(name: &str) -> Fixture {
+    let fixture = Fixture::new(name);
+    for dir in 0..20 {
+        for file in 0..50 {
+            fixture.file(&format!("dir{}/file{}.bin", dir, file), 16);
+        }
+    }
+    fixture
+}
+
+fn analyzer(fixture: &Fixture, timeout: Option<Duration>) -> StorageAnalyzer {
+    let mut analyzer = fixture.analyzer();
+    analyzer.set_scan_timeout_duration(timeout);
+    analyzer
+}
+
+#[test]
+fn a_tiny_timeout_leaves_a_partial_scan_in_the_cache_and_says_so() {
+    let fixture = wide_tree("timeout");
+    let drive = fixture.drive();
+
+    let mut sequential = analyzer(&fixture, Some(Duration::from_nanos(1)));
+    let results = sequential.scan_batch(std::slice::from_ref(&drive), false);
+    assert!(matches!(results[0].1, Ok(ScanStatus::Partial)), "{:?}", results[0].1);
+    assert!(sequential.cache_info(&drive).unwrap().timed_out);
+    assert!(sequential.timeout_note(&drive).is_some());
+
+    let mut parallel = analyzer(&fixture, Some(Duration::from_nanos(1)));
+    let results = parallel.scan_batch(std::slice::from_ref(&drive), true);
+    assert!(matches!(results[0].1, Ok(ScanStatus::Partial)), "{:?}", results[0].1);
+    assert!(parallel.cache_info(&drive).unwrap().timed_out);
+
+    let mut unlimited = analyzer(&fixture, None);
+    let results = unlimited.scan_batch(std::slice::from_ref(&drive), true);
+    assert!(matches!(results[0].1, Ok(ScanStatus::Complete)), "{:?}", results[0].1);
+    assert_eq!(unlimited.cache_info(&drive).unwrap().file_count, 1_000);
+    assert!(unlimited.timeout_note(&drive).is_none());
+}